@@ -0,0 +1,28 @@
+//! Writes `opensearch.xml` into `OUT_DIR`, from where the trunk hook in
+//! `scripts/copy-opensearch.sh` copies it next to `index.html`.
+//!
+//! The description needs the absolute URL the page is served from; set
+//! `STARTPAGE_PUBLIC_URL` when building for a deployment. Without it the
+//! file points at `trunk serve`'s default address.
+
+use std::path::Path;
+
+#[path = "src/opensearch.rs"]
+mod opensearch;
+
+const DEFAULT_PUBLIC_URL: &str = "http://127.0.0.1:8080/";
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/opensearch.rs");
+    println!("cargo:rerun-if-env-changed=STARTPAGE_PUBLIC_URL");
+
+    let base = std::env::var("STARTPAGE_PUBLIC_URL")
+        .ok()
+        .filter(|u| !u.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PUBLIC_URL.to_string());
+    let xml = opensearch::description(&base);
+
+    let dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    std::fs::write(Path::new(&dir).join("opensearch.xml"), xml).expect("write opensearch.xml");
+}
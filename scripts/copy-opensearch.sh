@@ -0,0 +1,15 @@
+#!/bin/sh
+# Trunk post_build hook: copies opensearch.xml, which build.rs writes into its
+# OUT_DIR, into the staging directory trunk turns into dist.
+set -eu
+
+out_dir=$(cargo check --quiet --message-format=json |
+    grep '"reason":"build-script-executed"' |
+    grep -E '#startpage@|"startpage [0-9]' |
+    sed 's/.*"out_dir":"\([^"]*\)".*/\1/' |
+    tail -n 1)
+if [ -z "$out_dir" ]; then
+    echo "copy-opensearch: no build script output for startpage" >&2
+    exit 1
+fi
+cp "$out_dir/opensearch.xml" "$TRUNK_STAGING_DIR/opensearch.xml"
@@ -1,222 +1,2893 @@
 #![allow(non_snake_case)]
 
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::rc::Rc;
 
-use repose_core::{CursorIcon, PaddingValues, prelude::*, set_theme_default};
+use repose_core::{CursorIcon, Modifiers, PaddingValues, prelude::*, set_theme_default};
 use repose_material::material3;
 use repose_ui::overlay::{OverlayHandle, SnackbarAction, SnackbarController, SnackbarRequest};
 use repose_ui::scroll::{ScrollArea, remember_scroll_state};
 use repose_ui::*;
+use wasm_bindgen::{JsCast, JsValue};
 
-use crate::storage::{self, Bookmark};
+use crate::clipboard::{self, Copied};
+use crate::commands::CommandRegistry;
+use crate::context_menu::{self, BookmarkMenu, MenuAction};
+use crate::crypto;
+use crate::dashboards::{self, Dashboard};
+use crate::deeplink;
+use crate::health::{HealthCheck, HealthMonitor, HealthState, HealthStatus};
+use crate::linkcheck::{self, HttpProbe, LinkChecker, LinkStatus};
+use crate::links::{self, LinkTarget};
+use crate::metadata;
+use crate::net;
+use crate::opensearch;
+use crate::palette::{self, CommandPalette};
+use crate::paste;
+use crate::secret;
+use crate::selection::{self, Selection};
+use crate::settings::{self, SearchEngine, Settings};
+use crate::storage::{self, Bookmark, ThemeChoice, TileStyle};
+use crate::sync::webdav::{Conflict, Resolution};
+use crate::sync::{self, Backend, SyncStatus, Syncer, Target};
+use crate::urls::{self, Classified, Rejection, UrlPolicy};
+use crate::widgets::{self, WidgetLayout};
 
-fn open_url(url: &str) {
-    if let Some(w) = web_sys::window() {
-        if w.open_with_url_and_target(url, "_blank").is_ok() {
-            return;
+/// Open `url` where the settings say.
+pub(crate) fn open_url(url: &str) {
+    open_url_in(url, None);
+}
+
+/// Open `url` as `target`, or where the settings say when `None`, if the link
+/// policy allows it. Falls back to the same tab when a new tab is refused.
+pub(crate) fn open_url_in(url: &str, target: Option<LinkTarget>) {
+    let settings = settings::load();
+    if let Err(e) = settings.url_policy().check(url) {
+        log::warn!("not opening {url}: {e}");
+        return;
+    }
+    let target = target.unwrap_or(settings.link_target);
+    if !links::open(url, target) && target != LinkTarget::SameTab {
+        links::open(url, LinkTarget::SameTab);
+    }
+}
+
+/// Open a bookmark clicked with `modifiers` held.
+fn open_bookmark(bm: &Bookmark, modifiers: Modifiers) {
+    open_url_in(&bm.url, links::for_click(modifiers).or(bm.open_in));
+}
+
+/// Open each URL the link policy allows in a new tab. Browsers usually let a
+/// click open only one tab, so this says how many were blocked.
+fn open_all(urls: &[String], snackbar: &Rc<SnackbarController>) {
+    let policy = settings::load().url_policy();
+    let allowed: Vec<&String> = urls.iter().filter(|u| policy.check(u).is_ok()).collect();
+    let blocked = allowed
+        .iter()
+        .filter(|u| !links::open(u, LinkTarget::NewTab))
+        .count();
+    if blocked > 0 {
+        notify(
+            snackbar,
+            &format!(
+                "The browser blocked {blocked} of {} tabs; allow pop-ups for this page to open them all",
+                allowed.len()
+            ),
+        );
+    }
+}
+
+/// Remove the bookmarks with `urls`, offering to put them back.
+fn remove_with_undo(
+    bookmarks: &Rc<Signal<Vec<Bookmark>>>,
+    snackbar: &Rc<SnackbarController>,
+    urls: &[String],
+) {
+    let mut removed = None;
+    bookmarks.update(|v| removed = Some(selection::remove(v, urls)));
+    let Some(removed) = removed.filter(|r| r.len() > 0) else {
+        return;
+    };
+    storage::save_bookmarks(&bookmarks.get());
+    let count = removed.len();
+    let removed = RefCell::new(Some(removed));
+    let bookmarks = bookmarks.clone();
+    notify_undo(
+        snackbar,
+        &format!(
+            "Removed {count} bookmark{}",
+            if count == 1 { "" } else { "s" }
+        ),
+        move || {
+            if let Some(r) = removed.borrow_mut().take() {
+                bookmarks.update(|v| selection::restore(v, r));
+                storage::save_bookmarks(&bookmarks.get());
+            }
+        },
+    );
+}
+
+/// Copy `text`, confirming with a snackbar; `what` names it ("Link", …).
+fn copy_text(text: &str, what: &'static str, snackbar: &Rc<SnackbarController>) {
+    let snackbar = snackbar.clone();
+    clipboard::copy(text, move |copied| {
+        if copied == Copied::Clipboard {
+            notify(&snackbar, &format!("{what} copied"));
+        }
+    });
+}
+
+/// What the search box would open for `input`, as a title and URL to copy.
+fn search_link(settings: &Settings, input: &str) -> (String, String) {
+    let url = search_target(settings, input);
+    let searched = |q: &str| match settings.engine {
+        SearchEngine::Custom => q.to_string(),
+        e => format!("{q} – {}", e.label()),
+    };
+    let title = match urls::classify(input, &settings.intranet_hosts) {
+        Classified::Url(u) if u == url => truncate_url(&url),
+        Classified::Url(_) => searched(input.trim()),
+        Classified::Search(q) => searched(&q),
+    };
+    (title, url)
+}
+
+/// Carry out what was picked in a tile's context menu.
+fn run_menu_action(
+    bm: &Bookmark,
+    action: MenuAction,
+    bookmarks: &Rc<Signal<Vec<Bookmark>>>,
+    editing: &Signal<Option<EditState>>,
+    snackbar: &Rc<SnackbarController>,
+) {
+    match action {
+        MenuAction::Open(target) => open_url_in(&bm.url, Some(target)),
+        MenuAction::Edit => editing.set(Some(EditState::new(bm.clone()))),
+        // Bookmarks are told apart by URL, so the copy needs a new one first.
+        MenuAction::Duplicate => editing.set(Some(EditState::copy_of(bm.clone()))),
+        MenuAction::CopyLink => copy_text(&bm.url, "Link", snackbar),
+        MenuAction::CopyMarkdown => copy_text(
+            &clipboard::markdown_link(&bm.title, &bm.url),
+            "Markdown link",
+            snackbar,
+        ),
+        MenuAction::MoveTo(group) => {
+            bookmarks
+                .update(|v| selection::move_to_group(v, std::slice::from_ref(&bm.url), &group));
+            storage::save_bookmarks(&bookmarks.get());
+        }
+        MenuAction::TogglePin => update_bookmark(bookmarks, &bm.url, |b| b.pinned = !b.pinned),
+        MenuAction::Delete => remove_with_undo(bookmarks, snackbar, std::slice::from_ref(&bm.url)),
+    }
+}
+
+/// Open a clicked tile, or pick it while selecting (Shift picks a range).
+fn click_tile(
+    bm: &Bookmark,
+    modifiers: Modifiers,
+    bookmarks: &Signal<Vec<Bookmark>>,
+    selection: &Signal<Option<Selection>>,
+) {
+    if selection.get().is_none() {
+        open_bookmark(bm, modifiers);
+        return;
+    }
+    let order = selection::grid_order(&bookmarks.get());
+    selection.update(|s| {
+        if let Some(s) = s {
+            if modifiers.shift {
+                s.extend_to(&order, &bm.url);
+            } else {
+                s.toggle(&bm.url);
+            }
+        }
+    });
+}
+
+/// Offer `text` to the user as a file download.
+fn download_text(filename: &str, mime: &str, text: &str) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or("no window")?;
+    let document = window.document().ok_or("no document")?;
+
+    let parts = js_sys::Array::of1(&JsValue::from_str(text));
+    let opts = web_sys::BlobPropertyBag::new();
+    opts.set_type(mime);
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &opts)?;
+    let href = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let a: web_sys::HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    a.set_href(&href);
+    a.set_download(filename);
+    a.click();
+    web_sys::Url::revoke_object_url(&href)
+}
+
+/// Download everything as an export file, encrypted unless `passphrase` is empty.
+fn export_to_file(snackbar: &Rc<SnackbarController>, passphrase: &str) {
+    let result = storage::check_bookmarks()
+        .and_then(|()| Ok(serde_json::to_string_pretty(&storage::export_all())?))
+        .and_then(|json| {
+            if passphrase.is_empty() {
+                Ok(json)
+            } else {
+                Ok(crypto::encrypt(passphrase, &json)?)
+            }
+        })
+        .and_then(|text| {
+            download_text("startpage-export.json", "application/json", &text)
+                .map_err(|_| anyhow::anyhow!("the browser refused the download"))
+        });
+    match result {
+        Ok(()) if passphrase.is_empty() => notify(snackbar, "Exported startpage-export.json"),
+        Ok(()) => notify(snackbar, "Exported startpage-export.json, encrypted"),
+        Err(e) => notify(snackbar, &format!("Export failed: {e:#}")),
+    }
+}
+
+/// Show a short message in the snackbar with a dismiss action.
+pub(crate) fn notify(snackbar: &Rc<SnackbarController>, message: &str) {
+    let message = message.to_string();
+    snackbar.show(SnackbarRequest {
+        message: message.clone(),
+        action: None,
+        duration_ms: 3000,
+        builder: Rc::new({
+            let sb = snackbar.clone();
+            move || {
+                material3::Snackbar(
+                    message.clone(),
+                    Some(SnackbarAction {
+                        label: "Dismiss".to_string(),
+                        on_click: Rc::new({
+                            let sb = sb.clone();
+                            move || sb.dismiss()
+                        }),
+                    }),
+                    Modifier::new()
+                        .absolute()
+                        .offset(Some(16.0), None, Some(16.0), None),
+                )
+            }
+        }),
+    });
+}
+
+/// Like `notify`, with an "Undo" action in place of "Dismiss".
+fn notify_undo(snackbar: &Rc<SnackbarController>, message: &str, undo: impl Fn() + 'static) {
+    let message = message.to_string();
+    let undo = Rc::new(undo);
+    snackbar.show(SnackbarRequest {
+        message: message.clone(),
+        action: None,
+        duration_ms: 6000,
+        builder: Rc::new({
+            let sb = snackbar.clone();
+            move || {
+                material3::Snackbar(
+                    message.clone(),
+                    Some(SnackbarAction {
+                        label: "Undo".to_string(),
+                        on_click: Rc::new({
+                            let sb = sb.clone();
+                            let undo = undo.clone();
+                            move || {
+                                undo();
+                                sb.dismiss()
+                            }
+                        }),
+                    }),
+                    Modifier::new()
+                        .absolute()
+                        .offset(Some(16.0), None, Some(16.0), None),
+                )
+            }
+        }),
+    });
+}
+
+/// `s` as a URL, or `None` when it reads as a search.
+fn normalize_url(settings: &Settings, s: &str) -> Option<String> {
+    match urls::classify(s, &settings.intranet_hosts) {
+        Classified::Url(url) => Some(url),
+        Classified::Search(_) => None,
+    }
+}
+
+/// The URL typed into the search box, or a search for it with the configured
+/// engine. URLs the link policy rejects are searched for instead.
+fn search_target(settings: &Settings, input: &str) -> String {
+    match urls::classify(input, &settings.intranet_hosts) {
+        Classified::Url(url) if settings.url_policy().check(&url).is_ok() => url,
+        Classified::Url(_) => settings.search_url(input),
+        Classified::Search(query) => settings.search_url(&query),
+    }
+}
+
+/// `cfg` searching with `engine`, when a deep link picked one for this page
+/// load; the saved default stays as it is.
+fn with_engine(mut cfg: Settings, engine: Option<SearchEngine>) -> Settings {
+    if let Some(e) = engine {
+        cfg.engine = e;
+    }
+    cfg
+}
+
+fn search_or_open(settings: &Settings, input: &str) {
+    open_url_in(
+        &search_target(settings, input),
+        links::for_click(links::enter_modifiers()),
+    );
+}
+
+/// Apply `f` to the settings and save them.
+fn update_settings(settings: &Signal<Settings>, f: impl FnOnce(&mut Settings)) {
+    settings.update(f);
+    settings::save(&settings.get());
+}
+
+fn theme_for(choice: ThemeChoice) -> Theme {
+    match choice {
+        ThemeChoice::Midnight => theme_pro(),
+        ThemeChoice::Graphite => Theme {
+            background: Color::from_hex("#0F0F10"),
+            surface: Color::from_hex("#18181B"),
+            primary: Color::from_hex("#F59E0B"),
+            outline: Color::from_hex("#2A2A2E"),
+            focus: Color::from_hex("#FBBF24"),
+            button_bg: Color::from_hex("#232326"),
+            button_bg_hover: Color::from_hex("#2A2A2E"),
+            button_bg_pressed: Color::from_hex("#34343A"),
+            ..theme_pro()
+        },
+        ThemeChoice::Forest => Theme {
+            background: Color::from_hex("#0A120F"),
+            surface: Color::from_hex("#10201A"),
+            primary: Color::from_hex("#10B981"),
+            outline: Color::from_hex("#1F3A2E"),
+            focus: Color::from_hex("#34D399"),
+            button_bg: Color::from_hex("#163024"),
+            button_bg_hover: Color::from_hex("#1F3A2E"),
+            button_bg_pressed: Color::from_hex("#28493A"),
+            ..theme_pro()
+        },
+    }
+}
+
+fn theme_pro() -> Theme {
+    Theme {
+        background: Color::from_hex("#0B0F14"),
+        surface: Color::from_hex("#111827"),
+        on_surface: Color::from_hex("#E5E7EB"),
+        primary: Color::from_hex("#3B82F6"),
+        on_primary: Color::WHITE,
+        outline: Color::from_hex("#243041"),
+        focus: Color::from_hex("#60A5FA"),
+        button_bg: Color::from_hex("#1F2937"),
+        button_bg_hover: Color::from_hex("#243041"),
+        button_bg_pressed: Color::from_hex("#2B3A52"),
+        scrollbar_track: Color(0xFF, 0xFF, 0xFF, 16),
+        scrollbar_thumb: Color(0xFF, 0xFF, 0xFF, 80),
+        ..Theme::default()
+    }
+}
+
+fn EnginePill(label: &str, selected: bool, on_click: impl Fn() + 'static) -> View {
+    let bg = if selected {
+        Color(theme().primary.0, theme().primary.1, theme().primary.2, 48)
+    } else {
+        Color(0, 0, 0, 0)
+    };
+
+    Button(
+        Text(label).size(13.0).single_line().color(if selected {
+            theme().primary
+        } else {
+            Color::from_hex("#9CA3AF")
+        }),
+        on_click,
+    )
+    .modifier(
+        Modifier::new()
+            .padding_values(PaddingValues {
+                left: 12.0,
+                right: 12.0,
+                top: 6.0,
+                bottom: 6.0,
+            })
+            .background(bg)
+            .clip_rounded(999.0),
+    )
+}
+
+fn IconButton(icon: &str, on_click: impl Fn() + 'static) -> View {
+    Box(Modifier::new()
+        .size(32.0, 32.0)
+        .background(Color(0, 0, 0, 0))
+        .clip_rounded(8.0)
+        .clickable()
+        .on_pointer_down(move |_| on_click()))
+    .child(Text(icon).size(18.0).color(Color::from_hex("#9CA3AF")))
+}
+
+fn remove_bookmark(
+    bookmarks: &Signal<Vec<Bookmark>>,
+    snackbar: &Rc<SnackbarController>,
+    url: &str,
+) {
+    bookmarks.update(|v| {
+        if let Some(pos) = v.iter().position(|x| x.url == url) {
+            v.remove(pos);
+        }
+    });
+    storage::save_bookmarks(&bookmarks.get());
+    notify(snackbar, "Bookmark removed");
+}
+
+fn BookmarkTile(
+    bm: Bookmark,
+    health: Option<HealthState>,
+    show_url: bool,
+    bookmarks: Rc<Signal<Vec<Bookmark>>>,
+    selection: Rc<Signal<Option<Selection>>>,
+    snackbar: Rc<SnackbarController>,
+) -> View {
+    let title = bm.title.clone();
+    let mut parts = Vec::new();
+    if bm.pinned {
+        parts.push("pinned".to_string());
+    }
+    if let Some(h) = &health {
+        parts.push(health_summary(h));
+    }
+    if show_url {
+        parts.push(truncate_url(&bm.url));
+    }
+    parts.extend(bm.tags.iter().map(|t| format!("#{t}")));
+    let subtitle = parts.join(" · ");
+    let selected = selection.get().map(|s| s.contains(&bm.url));
+
+    Box(Modifier::new()
+        .fill_max_width()
+        .background(theme().surface)
+        .border(
+            if selected == Some(true) { 2.0 } else { 1.0 },
+            if selected == Some(true) {
+                theme().primary
+            } else {
+                theme().outline
+            },
+            10.0,
+        )
+        .clip_rounded(10.0)
+        .padding_values(PaddingValues {
+            left: 14.0,
+            right: 10.0,
+            top: 12.0,
+            bottom: 12.0,
+        })
+        .clickable()
+        .on_pointer_down({
+            let bm = bm.clone();
+            let bookmarks = bookmarks.clone();
+            let selection = selection.clone();
+            move |e| click_tile(&bm, e.modifiers, &bookmarks, &selection)
+        })
+        .painter({
+            let url = bm.url.clone();
+            move |_, rect| links::register(rect, &url)
+        })
+        .cursor(CursorIcon::Pointer))
+    .child(
+        Row(Modifier::new()
+            .fill_max_width()
+            .align_items(AlignItems::Center))
+        .child((
+            // Content area (title + url)
+            Box(Modifier::new().weight(1.0).min_width(0.0)).child(
+                Column(Modifier::new()).child((
+                    Row(Modifier::new()
+                        .fill_max_width()
+                        .align_items(AlignItems::Center))
+                    .child((
+                        match &health {
+                            Some(h) => Box(Modifier::new().padding_values(PaddingValues {
+                                right: 8.0,
+                                ..Default::default()
+                            }))
+                            .child(HealthDot(h.status)),
+                            None => Box(Modifier::new()),
+                        },
+                        Text(title)
+                            .size(15.0)
+                            .single_line()
+                            .overflow_ellipsize()
+                            .color(theme().on_surface)
+                            .modifier(Modifier::new().weight(1.0).min_width(0.0)),
+                    )),
+                    if subtitle.is_empty() {
+                        Box(Modifier::new())
+                    } else {
+                        Text(subtitle)
+                            .size(12.0)
+                            .single_line()
+                            .overflow_ellipsize()
+                            .color(Color::from_hex("#6B7280"))
+                            .modifier(Modifier::new().fill_max_width())
+                    },
+                )),
+            ),
+            match selected {
+                Some(checked) => Checkbox(checked, move |_| {
+                    selection.update(|s| {
+                        if let Some(s) = s {
+                            s.toggle(&bm.url);
+                        }
+                    })
+                }),
+                // Remove button (only visible on hover/interaction)
+                None => IconButton("×", move || {
+                    remove_bookmark(&bookmarks, &snackbar, &bm.url)
+                }),
+            },
+        )),
+    )
+}
+
+/// Square new-tab style tile: monogram on top, title underneath. Not the
+/// site's favicon: the canvas only draws images whose bytes the page can
+/// read, and other sites' icons are off limits to it without CORS.
+fn SpeedDialTile(
+    bm: Bookmark,
+    health: Option<HealthState>,
+    bookmarks: Rc<Signal<Vec<Bookmark>>>,
+    selection: Rc<Signal<Option<Selection>>>,
+    snackbar: Rc<SnackbarController>,
+) -> View {
+    let accent = monogram_color(&bm.url);
+    let selected = selection.get().map(|s| s.contains(&bm.url));
+
+    Stack(Modifier::new().fill_max_width().aspect_ratio(1.0)).child((
+        Box(Modifier::new()
+            .fill_max_size()
+            .background(theme().surface)
+            .border(
+                if selected == Some(true) { 2.0 } else { 1.0 },
+                if selected == Some(true) {
+                    theme().primary
+                } else {
+                    theme().outline
+                },
+                14.0,
+            )
+            .clip_rounded(14.0)
+            .padding(10.0)
+            .clickable()
+            .on_pointer_down({
+                let bm = bm.clone();
+                let bookmarks = bookmarks.clone();
+                let selection = selection.clone();
+                move |e| click_tile(&bm, e.modifiers, &bookmarks, &selection)
+            })
+            .painter({
+                let url = bm.url.clone();
+                move |_, rect| links::register(rect, &url)
+            })
+            .cursor(CursorIcon::Pointer))
+        .child(
+            Column(
+                Modifier::new()
+                    .fill_max_size()
+                    .align_items(AlignItems::Center)
+                    .justify_content(JustifyContent::Center),
+            )
+            .child((
+                Box(Modifier::new()
+                    .size(48.0, 48.0)
+                    .background(accent)
+                    .clip_rounded(12.0)
+                    .align_items(AlignItems::Center)
+                    .justify_content(JustifyContent::Center))
+                .child(Text(monogram(&bm)).size(22.0).color(Color::WHITE)),
+                Text(bm.title.clone())
+                    .size(13.0)
+                    .single_line()
+                    .overflow_ellipsize()
+                    .color(theme().on_surface)
+                    .modifier(
+                        Modifier::new()
+                            .max_width(120.0)
+                            .padding_values(PaddingValues {
+                                top: 10.0,
+                                ..Default::default()
+                            }),
+                    ),
+            )),
+        ),
+        match health {
+            Some(h) => Box(Modifier::new()
+                .absolute()
+                .offset(Some(10.0), Some(10.0), None, None))
+            .child(HealthDot(h.status)),
+            None => Box(Modifier::new()),
+        },
+        Box(Modifier::new()
+            .absolute()
+            .offset(None, Some(2.0), Some(2.0), None))
+        .child(match selected {
+            Some(checked) => Checkbox(checked, move |_| {
+                selection.update(|s| {
+                    if let Some(s) = s {
+                        s.toggle(&bm.url);
+                    }
+                })
+            }),
+            None => IconButton("×", move || {
+                remove_bookmark(&bookmarks, &snackbar, &bm.url)
+            }),
+        }),
+    ))
+}
+
+fn HealthDot(status: HealthStatus) -> View {
+    let color = match status {
+        HealthStatus::Unknown => Color::from_hex("#4B5563"),
+        HealthStatus::Up => Color::from_hex("#22C55E"),
+        HealthStatus::Degraded => Color::from_hex("#F59E0B"),
+        HealthStatus::Down => Color::from_hex("#EF4444"),
+    };
+    Box(Modifier::new()
+        .size(8.0, 8.0)
+        .clip_rounded(4.0)
+        .background(color))
+}
+
+/// "up · 3m ago", "HTTP 503 · 20s ago", …
+fn health_summary(h: &HealthState) -> String {
+    if h.status == HealthStatus::Unknown {
+        return "checking…".to_string();
+    }
+    let what = if h.detail.is_empty() { "up" } else { &h.detail };
+    format!(
+        "{what} · {}",
+        ago(widgets::unix_now().saturating_sub(h.checked_at))
+    )
+}
+
+fn ago(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}s ago"),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// Header above a group of tiles, with "open all" and a toggle for the
+/// group's tile style.
+fn GroupHeader(
+    name: &str,
+    style: TileStyle,
+    on_open_all: impl Fn() + 'static,
+    on_toggle: impl Fn() + 'static,
+) -> View {
+    Row(Modifier::new()
+        .fill_max_width()
+        .align_items(AlignItems::Center)
+        .padding_values(PaddingValues {
+            bottom: 8.0,
+            ..Default::default()
+        }))
+    .child((
+        Text(if name.is_empty() { "Bookmarks" } else { name })
+            .size(13.0)
+            .single_line()
+            .color(Color::from_hex("#9CA3AF")),
+        Spacer(),
+        IconButton("⇗", on_open_all),
+        IconButton(
+            match style {
+                TileStyle::List => "▦",
+                TileStyle::SpeedDial => "☰",
+            },
+            on_toggle,
+        ),
+    ))
+}
+
+/// Bulk actions for the tiles picked in multi-select mode.
+fn SelectionBar(
+    sel: Selection,
+    selection: Rc<Signal<Option<Selection>>>,
+    bookmarks: Rc<Signal<Vec<Bookmark>>>,
+    snackbar: Rc<SnackbarController>,
+    epoch: Rc<Signal<u64>>,
+) -> View {
+    let field = |key: &str| {
+        Modifier::new()
+            .key(hash64(key) ^ epoch.get())
+            .height(40.0)
+            .weight(1.0)
+            .min_width(0.0)
+            .background(Color::from_hex("#0F172A"))
+            .border(1.0, theme().outline, 10.0)
+            .clip_rounded(10.0)
+    };
+    let bulk_edit = {
+        let bookmarks = bookmarks.clone();
+        let selection = selection.clone();
+        let epoch = epoch.clone();
+        Rc::new(move |f: &dyn Fn(&mut Vec<Bookmark>, &[String])| {
+            let Some(sel) = selection.get() else {
+                return;
+            };
+            bookmarks.update(|v| f(v, sel.urls()));
+            storage::save_bookmarks(&bookmarks.get());
+            epoch.update(|e| *e = e.wrapping_add(1));
+        })
+    };
+
+    let bar = Column(
+        Modifier::new()
+            .fill_max_width()
+            .background(theme().surface)
+            .border(1.0, theme().outline, 12.0)
+            .clip_rounded(12.0)
+            .padding(12.0),
+    )
+    .child((
+        Row(Modifier::new()
+            .fill_max_width()
+            .flex_wrap(FlexWrap::Wrap)
+            .align_items(AlignItems::Center))
+        .child((
+            Text(format!("{} selected", sel.len()))
+                .size(14.0)
+                .color(theme().on_surface),
+            Spacer(),
+            GhostButton("Select all", {
+                let selection = selection.clone();
+                let bookmarks = bookmarks.clone();
+                move || {
+                    let order = selection::grid_order(&bookmarks.get());
+                    selection.update(|s| {
+                        if let Some(s) = s {
+                            s.select_all(&order);
+                        }
+                    })
+                }
+            }),
+            GhostButton("Open all", {
+                let urls = sel.urls().to_vec();
+                let snackbar = snackbar.clone();
+                move || open_all(&urls, &snackbar)
+            }),
+            GhostButton("Delete", {
+                let urls = sel.urls().to_vec();
+                let selection = selection.clone();
+                let bookmarks = bookmarks.clone();
+                let snackbar = snackbar.clone();
+                move || {
+                    remove_with_undo(&bookmarks, &snackbar, &urls);
+                    selection.set(Some(Selection::default()));
+                }
+            }),
+            GhostButton("Done", {
+                let selection = selection.clone();
+                move || selection.set(None)
+            }),
+        )),
+        Row(Modifier::new()
+            .fill_max_width()
+            .padding_values(PaddingValues {
+                top: 8.0,
+                ..Default::default()
+            }))
+        .child((
+            TextField(
+                "Move to group…",
+                field("bulk.group"),
+                None::<fn(String)>,
+                Some({
+                    let bulk_edit = bulk_edit.clone();
+                    move |s: String| {
+                        let group = s.trim().to_string();
+                        bulk_edit(&|v, urls| selection::move_to_group(v, urls, &group))
+                    }
+                }),
+            ),
+            Box(Modifier::new().width(10.0).height(1.0)),
+            TextField(
+                "Add tag…",
+                field("bulk.tag"),
+                None::<fn(String)>,
+                Some(move |s: String| {
+                    if let Some(tag) = selection::clean_tag(&s) {
+                        bulk_edit(&|v, urls| selection::add_tag(v, urls, &tag))
+                    }
+                }),
+            ),
+        )),
+    ));
+    Box(Modifier::new()
+        .fill_max_width()
+        .padding_values(PaddingValues {
+            bottom: 16.0,
+            ..Default::default()
+        }))
+    .child(bar)
+}
+
+/// First letter of the title, falling back to the host.
+fn monogram(bm: &Bookmark) -> String {
+    bm.title
+        .chars()
+        .chain(truncate_url(&bm.url).chars())
+        .find(|c| c.is_alphanumeric())
+        .map(|c| c.to_uppercase().collect())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+fn monogram_color(url: &str) -> Color {
+    const PALETTE: [&str; 8] = [
+        "#2563EB", "#7C3AED", "#DB2777", "#DC2626", "#D97706", "#059669", "#0891B2", "#4B5563",
+    ];
+    Color::from_hex(PALETTE[(hash64(&truncate_url(url)) % PALETTE.len() as u64) as usize])
+}
+
+/// Group names in first-seen order.
+fn group_names(bookmarks: &[Bookmark]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for bm in bookmarks {
+        if !names.contains(&bm.group) {
+            names.push(bm.group.clone());
+        }
+    }
+    names
+}
+
+fn group_urls(bookmarks: &[Bookmark], group: &str) -> Vec<String> {
+    selection::group_members(bookmarks, group)
+        .into_iter()
+        .map(|bm| bm.url.clone())
+        .collect()
+}
+
+/// Tags in first-seen order, ignoring case.
+fn tag_names(bookmarks: &[Bookmark]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for tag in bookmarks.iter().flat_map(|bm| &bm.tags) {
+        if !names.iter().any(|n| n.eq_ignore_ascii_case(tag)) {
+            names.push(tag.clone());
+        }
+    }
+    names
+}
+
+/// Number of grid columns that fit `width` when tiles are at least `min_tile` wide.
+fn grid_columns(width: f32, min_tile: f32, gap: f32) -> usize {
+    (((width + gap) / (min_tile + gap)).floor() as usize).max(1)
+}
+
+fn GhostButton(label: &str, on_click: impl Fn() + 'static) -> View {
+    Button(
+        Text(label).size(14.0).color(Color::from_hex("#6B7280")),
+        on_click,
+    )
+    .modifier(
+        Modifier::new()
+            .padding_values(PaddingValues {
+                left: 16.0,
+                right: 16.0,
+                top: 10.0,
+                bottom: 10.0,
+            })
+            .background(Color(0, 0, 0, 0))
+            .clip_rounded(8.0)
+            .border(1.0, theme().outline, 8.0),
+    )
+}
+
+/// Whether a secret is set, with buttons to enter one (in `secret`'s masked
+/// dialog) or remove it; `save` gets the new value, empty to remove.
+fn SecretRow(status: &str, title: &'static str, is_set: bool, save: Rc<dyn Fn(String)>) -> View {
+    Row(Modifier::new()
+        .fill_max_width()
+        .align_items(AlignItems::Center))
+    .child((
+        Text(status)
+            .size(13.0)
+            .color(Color::from_hex("#9CA3AF"))
+            .modifier(Modifier::new().weight(1.0).min_width(0.0)),
+        GhostButton(if is_set { "Change…" } else { "Set…" }, {
+            let save = save.clone();
+            move || {
+                let save = save.clone();
+                secret::ask(title, move |value| save(value))
+            }
+        }),
+        if is_set {
+            GhostButton("Remove", move || save(String::new()))
+        } else {
+            Box(Modifier::new())
+        },
+    ))
+}
+
+/// Paste an export file, see what it would change, then apply it. An
+/// encrypted file is opened into `decrypted` once its passphrase is entered.
+fn ImportPanel(
+    import_text: Rc<Signal<String>>,
+    decrypted: Rc<Signal<Option<Result<String, String>>>>,
+    show: Rc<Signal<bool>>,
+    policy: UrlPolicy,
+) -> View {
+    let raw = import_text.get();
+    let encrypted = crypto::is_encrypted(&raw);
+    let text = if encrypted {
+        decrypted.get().and_then(Result::ok).unwrap_or_default()
+    } else {
+        raw.clone()
+    };
+    let parsed = if text.trim().is_empty() {
+        None
+    } else {
+        Some(storage::parse_export(&text))
+    };
+
+    let mut rejected = Vec::new();
+    let summary = match &parsed {
+        None if encrypted => match decrypted.get() {
+            Some(Err(e)) => format!("Can't decrypt: {e}"),
+            _ => "This export is encrypted. Enter its passphrase.".to_string(),
+        },
+        None => "Paste the contents of an export file".to_string(),
+        Some(Err(e)) => format!("Not a valid export: {e}"),
+        Some(Ok(data)) => {
+            let p = storage::preview_import(data, &policy);
+            rejected = p.rejected;
+            format!(
+                "{} new bookmark(s), {} already present, {} new dashboard(s); {} widget setting(s){} will be replaced",
+                p.new_bookmarks,
+                p.duplicate_bookmarks,
+                p.new_dashboards,
+                p.widget_entries,
+                if p.replaces_settings {
+                    " and your settings"
+                } else {
+                    ""
+                }
+            )
+        }
+    };
+    let data = parsed.and_then(Result::ok);
+
+    Box(Modifier::new()
+        .fill_max_width()
+        .max_width(500.0)
+        .background(theme().surface)
+        .border(1.0, theme().outline, 12.0)
+        .clip_rounded(12.0)
+        .padding(16.0))
+    .child(
+        Column(Modifier::new().fill_max_width()).child((
+            Row(Modifier::new()
+                .fill_max_width()
+                .align_items(AlignItems::Center)
+                .padding_values(PaddingValues {
+                    bottom: 12.0,
+                    ..Default::default()
+                }))
+            .child((
+                Text("Import").size(14.0).color(Color::from_hex("#9CA3AF")),
+                Spacer(),
+                IconButton("×", {
+                    let show = show.clone();
+                    move || show.set(false)
+                }),
+            )),
+            TextArea(
+                "{ \"version\": 1, … }",
+                Modifier::new()
+                    .key(hash64("import"))
+                    .height(120.0)
+                    .fill_max_width()
+                    .background(Color::from_hex("#0F172A"))
+                    .border(1.0, theme().outline, 10.0)
+                    .clip_rounded(10.0),
+                Some({
+                    let import_text = import_text.clone();
+                    let decrypted = decrypted.clone();
+                    move |s| {
+                        import_text.set(s);
+                        decrypted.set(None);
+                    }
+                }),
+                None::<fn(String)>,
+            ),
+            if encrypted {
+                Box(Modifier::new().padding_values(PaddingValues {
+                    top: 8.0,
+                    ..Default::default()
+                }))
+                .child(GhostButton("Enter passphrase…", move || {
+                    let decrypted = decrypted.clone();
+                    let raw = raw.clone();
+                    secret::ask("Passphrase of this export", move |passphrase| {
+                        decrypted.set(Some(
+                            crypto::decrypt(&passphrase, &raw).map_err(|e| e.to_string()),
+                        ))
+                    })
+                }))
+            } else {
+                Box(Modifier::new())
+            },
+            Text(summary)
+                .size(13.0)
+                .color(Color::from_hex("#9CA3AF"))
+                .modifier(Modifier::new().padding_values(PaddingValues {
+                    top: 8.0,
+                    ..Default::default()
+                })),
+            RejectedList(&rejected),
+            if let Some(data) = data {
+                Button(Text("Import").color(theme().on_primary), move || {
+                    storage::apply_import(&data, &policy);
+                    // Widgets hold their config in remembered state; reload to pick up the new data.
+                    if let Some(w) = web_sys::window() {
+                        let _ = w.location().reload();
+                    }
+                })
+                .modifier(
+                    Modifier::new()
+                        .padding_values(PaddingValues {
+                            top: 12.0,
+                            ..Default::default()
+                        })
+                        .background(theme().primary)
+                        .clip_rounded(10.0),
+                )
+            } else {
+                Box(Modifier::new())
+            },
+        )),
+    )
+}
+
+/// Bookmarks an import will skip because of their URL.
+fn RejectedList(rejected: &[(String, Rejection)]) -> View {
+    const SHOWN: usize = 5;
+    let mut lines: Vec<View> = rejected
+        .iter()
+        .take(SHOWN)
+        .map(|(url, why)| {
+            Text(format!("Skipped {url} ({why})"))
+                .size(12.0)
+                .single_line()
+                .overflow_ellipsize()
+                .color(theme().error)
+        })
+        .collect();
+    if rejected.len() > SHOWN {
+        lines.push(
+            Text(format!("…and {} more", rejected.len() - SHOWN))
+                .size(12.0)
+                .color(theme().error),
+        );
+    }
+    Column(
+        Modifier::new()
+            .fill_max_width()
+            .padding_values(PaddingValues {
+                top: 4.0,
+                ..Default::default()
+            }),
+    )
+    .child(lines)
+}
+
+/// Apply `f` to the bookmark with `url` and save. An edit that leaves the
+/// bookmark with a URL the link policy rejects is dropped.
+fn update_bookmark(bookmarks: &Signal<Vec<Bookmark>>, url: &str, f: impl FnOnce(&mut Bookmark)) {
+    let policy = settings::load().url_policy();
+    bookmarks.update(|v| {
+        if let Some(bm) = v.iter_mut().find(|b| b.url == url) {
+            let mut edited = bm.clone();
+            f(&mut edited);
+            match policy.check(&edited.url) {
+                Ok(()) => *bm = edited,
+                Err(e) => log::warn!("not saving {}: {e}", edited.url),
+            }
+        }
+    });
+    storage::save_bookmarks(&bookmarks.get());
+}
+
+/// Turn health checks on or off per bookmark and edit what they expect.
+fn HealthPanel(
+    bookmarks: Rc<Signal<Vec<Bookmark>>>,
+    monitor: Rc<HealthMonitor>,
+    show: Rc<Signal<bool>>,
+) -> View {
+    let states = monitor.state.get();
+    let field = |hint: String, key: &str, on_change: Box<dyn Fn(String)>| {
+        TextField(
+            hint,
+            Modifier::new()
+                .key(hash64(key))
+                .height(36.0)
+                .weight(1.0)
+                .min_width(0.0)
+                .background(Color::from_hex("#0F172A"))
+                .border(1.0, theme().outline, 8.0)
+                .clip_rounded(8.0),
+            Some(on_change),
+            None::<fn(String)>,
+        )
+    };
+
+    let rows = bookmarks
+        .get()
+        .into_iter()
+        .map(|bm| {
+            let url = bm.url.clone();
+            let edit = {
+                let bookmarks = bookmarks.clone();
+                let url = url.clone();
+                Rc::new(move |f: &dyn Fn(&mut HealthCheck)| {
+                    update_bookmark(&bookmarks, &url, |b| {
+                        if let Some(check) = b.health.as_mut() {
+                            f(check)
+                        }
+                    })
+                })
+            };
+
+            let details = match &bm.health {
+                None => Box(Modifier::new()),
+                Some(check) => Column(Modifier::new().fill_max_width().padding_values(
+                    PaddingValues {
+                        left: 28.0,
+                        bottom: 8.0,
+                        ..Default::default()
+                    },
+                ))
+                .child((
+                    Row(Modifier::new().fill_max_width()).child(field(
+                        if check.url.is_empty() {
+                            "Status URL (defaults to the bookmark)".to_string()
+                        } else {
+                            check.url.clone()
+                        },
+                        &format!("health-url:{url}"),
+                        Box::new({
+                            let edit = edit.clone();
+                            move |s| {
+                                // Probes only speak http(s); keep the last valid URL otherwise.
+                                let s = s.trim().to_string();
+                                if s.is_empty() || UrlPolicy::default().check(&s).is_ok() {
+                                    edit(&|c| c.url = s.clone())
+                                }
+                            }
+                        }),
+                    )),
+                    Row(Modifier::new()
+                        .fill_max_width()
+                        .padding_values(PaddingValues {
+                            top: 6.0,
+                            ..Default::default()
+                        }))
+                    .child((
+                        field(
+                            match check.expect_status {
+                                Some(code) => format!("Status {code}"),
+                                None => "Expected status (any 2xx)".to_string(),
+                            },
+                            &format!("health-status:{url}"),
+                            Box::new({
+                                let edit = edit.clone();
+                                move |s| {
+                                    let s = s.trim();
+                                    if s.is_empty() {
+                                        edit(&|c| c.expect_status = None);
+                                    } else if let Ok(code) = s.parse::<u16>() {
+                                        edit(&|c| c.expect_status = Some(code));
+                                    }
+                                }
+                            }),
+                        ),
+                        Box(Modifier::new().width(8.0).height(1.0)),
+                        field(
+                            if check.expect_body.is_empty() {
+                                "Body contains (optional)".to_string()
+                            } else {
+                                check.expect_body.clone()
+                            },
+                            &format!("health-body:{url}"),
+                            Box::new({
+                                let edit = edit.clone();
+                                move |s| edit(&|c| c.expect_body = s.clone())
+                            }),
+                        ),
+                    )),
+                    Text(format!(
+                        "Check every {}",
+                        interval_label(check.interval_secs)
+                    ))
+                    .size(13.0)
+                    .color(Color::from_hex("#9CA3AF"))
+                    .modifier(Modifier::new().padding_values(PaddingValues {
+                        top: 6.0,
+                        ..Default::default()
+                    })),
+                    Slider(
+                        check.interval_secs as f32,
+                        (30.0, 1800.0),
+                        Some(30.0),
+                        move |v| edit(&|c| c.interval_secs = v.round() as u32),
+                    ),
+                )),
+            };
+
+            Column(Modifier::new().fill_max_width()).child((
+                Row(Modifier::new()
+                    .fill_max_width()
+                    .align_items(AlignItems::Center))
+                .child((
+                    Checkbox(bm.health.is_some(), {
+                        let bookmarks = bookmarks.clone();
+                        let url = url.clone();
+                        move |on| {
+                            update_bookmark(&bookmarks, &url, |b| {
+                                b.health = on.then(HealthCheck::default)
+                            })
+                        }
+                    }),
+                    Text(bm.title.clone())
+                        .size(14.0)
+                        .single_line()
+                        .overflow_ellipsize()
+                        .color(theme().on_surface)
+                        .modifier(Modifier::new().weight(1.0).min_width(0.0).padding_values(
+                            PaddingValues {
+                                left: 8.0,
+                                ..Default::default()
+                            },
+                        )),
+                    match (&bm.health, states.get(&url)) {
+                        (Some(_), Some(h)) => Text(health_summary(h))
+                            .size(12.0)
+                            .single_line()
+                            .color(Color::from_hex("#6B7280")),
+                        _ => Box(Modifier::new()),
+                    },
+                    if bm.health.is_some() {
+                        IconButton("↻", {
+                            let monitor = monitor.clone();
+                            move || monitor.probe(&bm, widgets::unix_now())
+                        })
+                    } else {
+                        Box(Modifier::new())
+                    },
+                )),
+                details,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    Box(Modifier::new()
+        .fill_max_width()
+        .max_width(500.0)
+        .background(theme().surface)
+        .border(1.0, theme().outline, 12.0)
+        .clip_rounded(12.0)
+        .padding(16.0))
+    .child(
+        Column(Modifier::new().fill_max_width()).child((
+            Row(Modifier::new()
+                .fill_max_width()
+                .align_items(AlignItems::Center)
+                .padding_values(PaddingValues {
+                    bottom: 12.0,
+                    ..Default::default()
+                }))
+            .child((
+                Text("Health checks")
+                    .size(14.0)
+                    .color(Color::from_hex("#9CA3AF")),
+                Spacer(),
+                IconButton("×", move || show.set(false)),
+            )),
+            if rows.is_empty() {
+                Text("Add a bookmark first")
+                    .size(13.0)
+                    .color(Color::from_hex("#6B7280"))
+            } else {
+                Column(Modifier::new().fill_max_width()).child(rows)
+            },
+        )),
+    )
+}
+
+/// Run the link check and act on its report: delete dead bookmarks or point
+/// redirected ones at their new URL, in bulk.
+fn LinkCheckPanel(
+    bookmarks: Rc<Signal<Vec<Bookmark>>>,
+    checker: Rc<LinkChecker>,
+    show: Rc<Signal<bool>>,
+    snackbar: Rc<SnackbarController>,
+) -> View {
+    let report = checker.report.get();
+    let saved = bookmarks.get();
+    let muted = Color::from_hex("#9CA3AF");
+    // Results for bookmarks deleted or edited since the run are left out.
+    let problems: Vec<(&Bookmark, &LinkStatus)> = report
+        .results
+        .iter()
+        .filter(|(_, s)| !matches!(s, LinkStatus::Ok(_)))
+        .filter_map(|(url, s)| saved.iter().find(|bm| &bm.url == url).map(|bm| (bm, s)))
+        .collect();
+    let dead: Vec<String> = report
+        .dead()
+        .into_iter()
+        .filter(|u| saved.iter().any(|bm| &bm.url == u))
+        .collect();
+    let redirects: Vec<(String, String)> = report
+        .redirects()
+        .into_iter()
+        .filter(|(from, _)| saved.iter().any(|bm| &bm.url == from))
+        .collect();
+
+    let summary = if report.running {
+        format!("Checking… {} of {}", report.results.len(), report.total)
+    } else if report.total == 0 {
+        "Probe every bookmark once to find dead and moved links.".to_string()
+    } else {
+        format!(
+            "Checked {} links: {} dead, {} moved, {} other",
+            report.total,
+            dead.len(),
+            redirects.len(),
+            problems.len() - dead.len() - redirects.len()
+        )
+    };
+
+    let actions = Row(Modifier::new()
+        .fill_max_width()
+        .flex_wrap(FlexWrap::Wrap)
+        .align_items(AlignItems::Center))
+    .child((
+        if report.running {
+            GhostButton("Cancel", {
+                let checker = checker.clone();
+                move || checker.cancel()
+            })
+        } else {
+            GhostButton(
+                if report.total == 0 {
+                    "Check links"
+                } else {
+                    "Check again"
+                },
+                {
+                    let checker = checker.clone();
+                    let bookmarks = bookmarks.clone();
+                    move || start_link_check(&checker, &bookmarks.get())
+                },
+            )
+        },
+        if dead.is_empty() {
+            Box(Modifier::new())
+        } else {
+            GhostButton(&format!("Delete {} dead", dead.len()), {
+                let checker = checker.clone();
+                let bookmarks = bookmarks.clone();
+                let snackbar = snackbar.clone();
+                move || {
+                    remove_with_undo(&bookmarks, &snackbar, &dead);
+                    checker.edit(|r| r.forget(&dead));
+                }
+            })
+        },
+        if redirects.is_empty() {
+            Box(Modifier::new())
+        } else {
+            GhostButton(&format!("Update {} moved", redirects.len()), {
+                let checker = checker.clone();
+                let bookmarks = bookmarks.clone();
+                let snackbar = snackbar.clone();
+                move || update_redirects(&bookmarks, &checker, &snackbar, &redirects)
+            })
+        },
+    ));
+
+    let rows = problems
+        .iter()
+        .map(|(bm, status)| {
+            Column(
+                Modifier::new()
+                    .fill_max_width()
+                    .padding_values(PaddingValues {
+                        top: 8.0,
+                        ..Default::default()
+                    }),
+            )
+            .child((
+                Text(bm.title.clone())
+                    .size(14.0)
+                    .single_line()
+                    .overflow_ellipsize()
+                    .color(theme().on_surface),
+                Text(status.label())
+                    .size(12.0)
+                    .single_line()
+                    .overflow_ellipsize()
+                    .color(match status {
+                        LinkStatus::Dead(_) => theme().error,
+                        _ => Color::from_hex("#6B7280"),
+                    }),
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    Box(Modifier::new()
+        .fill_max_width()
+        .max_width(500.0)
+        .background(theme().surface)
+        .border(1.0, theme().outline, 12.0)
+        .clip_rounded(12.0)
+        .padding(16.0))
+    .child(
+        Column(Modifier::new().fill_max_width()).child((
+            Row(Modifier::new()
+                .fill_max_width()
+                .align_items(AlignItems::Center)
+                .padding_values(PaddingValues {
+                    bottom: 12.0,
+                    ..Default::default()
+                }))
+            .child((
+                Text("Check links").size(14.0).color(muted),
+                Spacer(),
+                IconButton("×", move || show.set(false)),
+            )),
+            Text(summary).size(13.0).color(theme().on_surface),
+            actions,
+            Column(Modifier::new().fill_max_width()).child(rows),
+            Text("Links the browser can't reach from this page, such as intranet hosts off the VPN, count as dead too.")
+                .size(12.0)
+                .color(muted)
+                .modifier(Modifier::new().padding_values(PaddingValues {
+                    top: 8.0,
+                    ..Default::default()
+                })),
+        )),
+    )
+}
+
+fn start_link_check(checker: &LinkChecker, bookmarks: &[Bookmark]) {
+    // Only http(s) can be probed.
+    let urls = bookmarks
+        .iter()
+        .map(|bm| bm.url.clone())
+        .filter(|u| UrlPolicy::default().check(u).is_ok())
+        .collect();
+    checker.start(urls, widgets::unix_now());
+}
+
+/// Point bookmarks at the URLs they redirect to, with an undo.
+fn update_redirects(
+    bookmarks: &Rc<Signal<Vec<Bookmark>>>,
+    checker: &LinkChecker,
+    snackbar: &Rc<SnackbarController>,
+    redirects: &[(String, String)],
+) {
+    let policy = settings::load().url_policy();
+    let allowed: Vec<(String, String)> = redirects
+        .iter()
+        .filter(|(_, to)| policy.check(to).is_ok())
+        .cloned()
+        .collect();
+    let mut applied = Vec::new();
+    bookmarks.update(|v| applied = linkcheck::apply_redirects(v, &allowed));
+    storage::save_bookmarks(&bookmarks.get());
+    let from: Vec<String> = applied.iter().map(|(f, _)| f.clone()).collect();
+    checker.edit(|r| r.forget(&from));
+
+    let count = applied.len();
+    let skipped = redirects.len() - count;
+    let mut message = format!(
+        "Updated {count} bookmark{}",
+        if count == 1 { "" } else { "s" }
+    );
+    if skipped > 0 {
+        message.push_str(&format!(
+            "; {skipped} skipped, already saved or not allowed"
+        ));
+    }
+    let bookmarks = bookmarks.clone();
+    notify_undo(snackbar, &message, move || {
+        bookmarks.update(|v| {
+            for (from, to) in &applied {
+                if let Some(bm) = v.iter_mut().find(|bm| &bm.url == to) {
+                    bm.url = from.clone();
+                }
+            }
+        });
+        storage::save_bookmarks(&bookmarks.get());
+    });
+}
+
+fn interval_label(secs: u32) -> String {
+    if secs < 60 {
+        format!("{secs}s")
+    } else if secs.is_multiple_of(60) {
+        format!("{} min", secs / 60)
+    } else {
+        format!("{} min {}s", secs / 60, secs % 60)
+    }
+}
+
+/// Engines offered in the pills and the palette; Custom only once it has a URL.
+fn engines(cfg: &Settings) -> Vec<SearchEngine> {
+    SearchEngine::ALL
+        .into_iter()
+        .filter(|e| *e != SearchEngine::Custom || !cfg.custom_search_url.is_empty())
+        .collect()
+}
+
+/// What the reset confirmation dialog is asking about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ResetKind {
+    Settings,
+    Everything,
+}
+
+/// Handles the settings drawer needs from the rest of the app.
+struct DrawerCtx {
+    settings: Rc<Signal<Settings>>,
+    show: Rc<Signal<bool>>,
+    confirm_reset: Rc<Signal<Option<ResetKind>>>,
+    show_import: Rc<Signal<bool>>,
+    show_health: Rc<Signal<bool>>,
+    search_url_error: Rc<Signal<Option<String>>>,
+    snackbar: Rc<SnackbarController>,
+    bookmark_count: usize,
+    dashboards: Rc<Signal<Vec<Dashboard>>>,
+    active_dashboard: Rc<Signal<String>>,
+    confirm_delete_dashboard: Rc<Signal<Option<String>>>,
+    syncer: Rc<Syncer>,
+    /// Encrypts exports when set; kept for the session only.
+    export_passphrase: Rc<Signal<String>>,
+}
+
+fn SettingsDrawer(
+    ctx: DrawerCtx,
+    page_widgets: &widgets::Widgets,
+    widget_layout: Rc<Signal<WidgetLayout>>,
+    width: f32,
+) -> View {
+    let cfg = ctx.settings.get();
+    let muted = Color::from_hex("#9CA3AF");
+    let label = |text: String| {
+        Text(text)
+            .size(13.0)
+            .color(muted)
+            .modifier(Modifier::new().padding_values(PaddingValues {
+                top: 8.0,
+                bottom: 6.0,
+                ..Default::default()
+            }))
+    };
+    let close_and = |target: Rc<Signal<bool>>| {
+        let show = ctx.show.clone();
+        move || {
+            show.set(false);
+            target.set(true);
+        }
+    };
+
+    let search = Column(Modifier::new().fill_max_width()).child((
+        label("Default engine".to_string()),
+        Row(Modifier::new().flex_wrap(FlexWrap::Wrap)).child(
+            SearchEngine::ALL
+                .into_iter()
+                .map(|e| {
+                    Chip(e.label(), cfg.engine == e, {
+                        let settings = ctx.settings.clone();
+                        let error = ctx.search_url_error.clone();
+                        move || {
+                            if e == SearchEngine::Custom
+                                && settings.get().custom_search_url.is_empty()
+                            {
+                                error.set(Some("Enter a custom search URL first".to_string()));
+                                return;
+                            }
+                            update_settings(&settings, |s| s.engine = e)
+                        }
+                    })
+                })
+                .collect::<Vec<_>>(),
+        ),
+        label("Custom search URL".to_string()),
+        TextField(
+            if cfg.custom_search_url.is_empty() {
+                "https://example.com/search?q=%s".to_string()
+            } else {
+                cfg.custom_search_url.clone()
+            },
+            Modifier::new()
+                .key(hash64("settings.custom_search_url"))
+                .height(40.0)
+                .fill_max_width()
+                .background(Color::from_hex("#0F172A"))
+                .border(1.0, theme().outline, 10.0)
+                .clip_rounded(10.0),
+            Some({
+                let settings = ctx.settings.clone();
+                let error = ctx.search_url_error.clone();
+                move |s: String| {
+                    let s = s.trim().to_string();
+                    if s.is_empty() {
+                        error.set(None);
+                        update_settings(&settings, |c| {
+                            c.custom_search_url.clear();
+                            if c.engine == SearchEngine::Custom {
+                                c.engine = Settings::default().engine;
+                            }
+                        });
+                        return;
+                    }
+                    match settings::validate_search_template(&s) {
+                        Ok(()) => {
+                            error.set(None);
+                            update_settings(&settings, |c| c.custom_search_url = s);
+                        }
+                        Err(e) => error.set(Some(e.to_string())),
+                    }
+                }
+            }),
+            None::<fn(String)>,
+        ),
+        match ctx.search_url_error.get() {
+            Some(e) => Text(e).size(12.0).color(theme().error),
+            None => Box(Modifier::new()),
+        },
+        Column(Modifier::new().fill_max_width()).child((
+            label("Intranet hosts".to_string()),
+            TextField(
+                if cfg.intranet_hosts.is_empty() {
+                    "wiki, jira, nas".to_string()
+                } else {
+                    cfg.intranet_hosts.join(", ")
+                },
+                Modifier::new()
+                    .key(hash64("settings.intranet_hosts"))
+                    .height(40.0)
+                    .fill_max_width()
+                    .background(Color::from_hex("#0F172A"))
+                    .border(1.0, theme().outline, 10.0)
+                    .clip_rounded(10.0),
+                Some({
+                    let settings = ctx.settings.clone();
+                    move |s: String| {
+                        update_settings(&settings, |c| {
+                            c.intranet_hosts = settings::parse_host_list(&s);
+                        })
+                    }
+                }),
+                None::<fn(String)>,
+            ),
+            Text("Single-word names that open as http://name instead of searching. Start with ? to search anyway.")
+                .size(12.0)
+                .color(muted),
+            label("Use as your browser's search engine".to_string()),
+            Text(opensearch::search_template(&deeplink::page_url(), "%s"))
+                .size(13.0)
+                .color(theme().on_surface),
+            Text("Add it as a custom search engine, or from the browser's address bar menu.")
+                .size(12.0)
+                .color(muted),
+        )),
+    ));
+
+    let bookmark_section = Column(Modifier::new().fill_max_width()).child((
+        label("Tile style for new groups".to_string()),
+        Row(Modifier::new()).child(
+            [
+                (TileStyle::List, "List"),
+                (TileStyle::SpeedDial, "Speed dial"),
+            ]
+            .into_iter()
+            .map(|(style, name)| {
+                Chip(name, cfg.default_tile_style == style, {
+                    let settings = ctx.settings.clone();
+                    move || update_settings(&settings, |s| s.default_tile_style = style)
+                })
+            })
+            .collect::<Vec<_>>(),
+        ),
+        widgets::SwitchRow("Show URLs on list tiles", cfg.show_tile_urls, {
+            let settings = ctx.settings.clone();
+            move |on| update_settings(&settings, |s| s.show_tile_urls = on)
+        }),
+        label(format!("Minimum tile width: {} dp", cfg.tile_min_width)),
+        Slider(
+            cfg.tile_min_width as f32,
+            (
+                *Settings::TILE_MIN_WIDTH.start() as f32,
+                *Settings::TILE_MIN_WIDTH.end() as f32,
+            ),
+            Some(10.0),
+            {
+                let settings = ctx.settings.clone();
+                move |v| update_settings(&settings, |s| s.tile_min_width = v.round() as u32)
+            },
+        ),
+        Column(Modifier::new().fill_max_width()).child((
+            label("Extra link schemes".to_string()),
+            TextField(
+                if cfg.extra_schemes.is_empty() {
+                    "ftp, mailto".to_string()
+                } else {
+                    cfg.extra_schemes.join(", ")
+                },
+                Modifier::new()
+                    .key(hash64("settings.extra_schemes"))
+                    .height(40.0)
+                    .fill_max_width()
+                    .background(Color::from_hex("#0F172A"))
+                    .border(1.0, theme().outline, 10.0)
+                    .clip_rounded(10.0),
+                Some({
+                    let settings = ctx.settings.clone();
+                    move |s: String| {
+                        update_settings(&settings, |c| {
+                            c.extra_schemes = settings::parse_scheme_list(&s);
+                        })
+                    }
+                }),
+                None::<fn(String)>,
+            ),
+            Text("http and https are always allowed; javascript: and data: never are.")
+                .size(12.0)
+                .color(muted),
+            label("Open links in".to_string()),
+            Row(Modifier::new()).child(
+                LinkTarget::ALL
+                    .into_iter()
+                    .map(|t| {
+                        Chip(t.label(), cfg.link_target == t, {
+                            let settings = ctx.settings.clone();
+                            move || update_settings(&settings, |s| s.link_target = t)
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Text("Ctrl/⌘-click or middle-click opens a new tab, Shift-click a new window.")
+                .size(12.0)
+                .color(muted),
+        )),
+        Column(Modifier::new().fill_max_width()).child((
+            widgets::SwitchRow("Look up titles of new bookmarks", cfg.lookup_titles, {
+                let settings = ctx.settings.clone();
+                move |on| update_settings(&settings, |s| s.lookup_titles = on)
+            }),
+            label("Title lookup proxy".to_string()),
+            TextField(
+                if cfg.metadata_proxy.is_empty() {
+                    "/metadata?url=".to_string()
+                } else {
+                    cfg.metadata_proxy.clone()
+                },
+                Modifier::new()
+                    .key(hash64("settings.metadata_proxy"))
+                    .height(40.0)
+                    .fill_max_width()
+                    .background(Color::from_hex("#0F172A"))
+                    .border(1.0, theme().outline, 10.0)
+                    .clip_rounded(10.0),
+                Some({
+                    let settings = ctx.settings.clone();
+                    let snackbar = ctx.snackbar.clone();
+                    move |s: String| match settings::validate_proxy(&s) {
+                        Ok(()) => update_settings(&settings, |c| {
+                            c.metadata_proxy = s.trim().to_string();
+                        }),
+                        Err(e) => notify(&snackbar, e),
+                    }
+                }),
+                None::<fn(String)>,
+            ),
+            Text("The page URL is appended, encoded. The proxy returns the page, or JSON with its title. Empty fetches pages directly, which most sites refuse.")
+                .size(12.0)
+                .color(muted),
+        )),
+        Box(Modifier::new().padding_values(PaddingValues {
+            top: 8.0,
+            ..Default::default()
+        }))
+        .child(GhostButton(
+            "Health checks…",
+            close_and(ctx.show_health.clone()),
+        )),
+    ));
+
+    let appearance = Column(Modifier::new().fill_max_width()).child((
+        label("Default theme".to_string()),
+        Row(Modifier::new()).child(
+            ThemeChoice::ALL
+                .into_iter()
+                .map(|t| {
+                    Chip(t.label(), cfg.theme == t, {
+                        let settings = ctx.settings.clone();
+                        move || update_settings(&settings, |s| s.theme = t)
+                    })
+                })
+                .collect::<Vec<_>>(),
+        ),
+        label(format!("Content width: {} dp", cfg.content_width)),
+        Slider(
+            cfg.content_width as f32,
+            (
+                *Settings::CONTENT_WIDTH.start() as f32,
+                *Settings::CONTENT_WIDTH.end() as f32,
+            ),
+            Some(50.0),
+            {
+                let settings = ctx.settings.clone();
+                move |v| update_settings(&settings, |s| s.content_width = v.round() as u32)
+            },
+        ),
+    ));
+
+    let dashboard_list = ctx.dashboards.get();
+    let dashboard_section = Column(Modifier::new().fill_max_width()).child((
+        Column(Modifier::new().fill_max_width()).child(
+            dashboard_list
+                .iter()
+                .map(|d| DashboardRow(d.clone(), &ctx))
+                .collect::<Vec<_>>(),
+        ),
+        label("New dashboard".to_string()),
+        TextField(
+            "Name, then Enter",
+            Modifier::new()
+                // Re-keyed per dashboard count so the field clears after adding.
+                .key(hash64("settings.new_dashboard") ^ dashboard_list.len() as u64)
+                .height(40.0)
+                .fill_max_width()
+                .background(Color::from_hex("#0F172A"))
+                .border(1.0, theme().outline, 10.0)
+                .clip_rounded(10.0),
+            None::<fn(String)>,
+            Some({
+                let list = ctx.dashboards.clone();
+                let active = ctx.active_dashboard.clone();
+                move |name: String| {
+                    if name.trim().is_empty() {
+                        return;
+                    }
+                    let id = dashboards::add(&list, &name);
+                    dashboards::select(&active, &id);
+                }
+            }),
+        ),
+        Text("Switch with the tabs above the search box, Alt+1…9, or #name in the URL.")
+            .size(12.0)
+            .color(muted)
+            .modifier(Modifier::new().padding_values(PaddingValues {
+                top: 6.0,
+                ..Default::default()
+            })),
+    ));
+
+    let data = Column(Modifier::new().fill_max_width()).child((
+        Row(Modifier::new().flex_wrap(FlexWrap::Wrap)).child((
+            GhostButton("Export", {
+                let snackbar = ctx.snackbar.clone();
+                let passphrase = ctx.export_passphrase.clone();
+                move || export_to_file(&snackbar, &passphrase.get())
+            }),
+            Box(Modifier::new().width(8.0).height(1.0)),
+            GhostButton("Import…", close_and(ctx.show_import.clone())),
+        )),
+        Column(Modifier::new().fill_max_width()).child((
+            label("Export passphrase".to_string()),
+            SecretRow(
+                if ctx.export_passphrase.get().is_empty() {
+                    "None; exports are not encrypted"
+                } else {
+                    "Set until the page is closed"
+                },
+                "Passphrase to encrypt exports with",
+                !ctx.export_passphrase.get().is_empty(),
+                Rc::new({
+                    let passphrase = ctx.export_passphrase.clone();
+                    move |p: String| passphrase.set(p)
+                }),
+            ),
+        )),
+        Row(Modifier::new()
+            .flex_wrap(FlexWrap::Wrap)
+            .padding_values(PaddingValues {
+                top: 8.0,
+                ..Default::default()
+            }))
+        .child((
+            GhostButton("Reset settings…", {
+                let confirm = ctx.confirm_reset.clone();
+                move || confirm.set(Some(ResetKind::Settings))
+            }),
+            Box(Modifier::new().width(8.0).height(1.0)),
+            GhostButton("Erase all data…", {
+                let confirm = ctx.confirm_reset.clone();
+                move || confirm.set(Some(ResetKind::Everything))
+            }),
+        )),
+        SyncSettings(&ctx),
+    ));
+
+    let sync_state = sync::load_state();
+    let about = Column(Modifier::new().fill_max_width()).child((
+        Text(format!("Startpage {}", env!("CARGO_PKG_VERSION")))
+            .size(14.0)
+            .color(theme().on_surface),
+        Text(if sync_state.url.is_empty() {
+            "Everything is stored locally in this browser.".to_string()
+        } else {
+            format!("Stored in this browser and synced with {}.", sync_state.url)
+        })
+        .size(13.0)
+        .color(muted),
+        Text(format!(
+            "{} bookmark(s) on this dashboard. Press Ctrl+K (⌘K) for the command palette.",
+            ctx.bookmark_count
+        ))
+        .size(13.0)
+        .color(muted),
+    ));
+
+    material3::BottomSheet(
+        true,
+        {
+            let show = ctx.show.clone();
+            move || show.set(false)
+        },
+        Modifier::new()
+            .width(width)
+            .background(theme().background)
+            .border(1.0, theme().outline, 0.0),
+        ScrollArea(
+            Modifier::new().fill_max_size(),
+            remember_scroll_state("settings_scroll"),
+            Column(Modifier::new().fill_max_width().padding(16.0)).child((
+                Row(Modifier::new()
+                    .fill_max_width()
+                    .align_items(AlignItems::Center)
+                    .padding_values(PaddingValues {
+                        bottom: 12.0,
+                        ..Default::default()
+                    }))
+                .child((
+                    Text("Settings").size(18.0).color(theme().on_surface),
+                    Spacer(),
+                    IconButton("×", {
+                        let show = ctx.show.clone();
+                        move || show.set(false)
+                    }),
+                )),
+                SettingsSection("Search", search),
+                SettingsSection("Dashboards", dashboard_section),
+                SettingsSection("Bookmarks", bookmark_section),
+                SettingsSection("Appearance", appearance),
+                SettingsSection(
+                    "Widgets",
+                    widgets::WidgetsSettings(page_widgets, widget_layout),
+                ),
+                SettingsSection("Data", data),
+                SettingsSection("About", about),
+            )),
+        ),
+    )
+}
+
+/// Sync backend, server, credentials and the state of the last pass, in the
+/// Data section.
+fn SyncSettings(ctx: &DrawerCtx) -> View {
+    let muted = Color::from_hex("#9CA3AF");
+    let state = sync::load_state();
+    let webdav = state.backend == Backend::WebDav;
+    let label = |text: &str| {
+        Text(text)
+            .size(13.0)
+            .color(muted)
+            .modifier(Modifier::new().padding_values(PaddingValues {
+                top: 8.0,
+                bottom: 6.0,
+                ..Default::default()
+            }))
+    };
+    let field = |key: u64| {
+        Modifier::new()
+            .key(key)
+            .height(40.0)
+            .fill_max_width()
+            .background(Color::from_hex("#0F172A"))
+            .border(1.0, theme().outline, 10.0)
+            .clip_rounded(10.0)
+    };
+    let (status, color) = match ctx.syncer.status.get() {
+        SyncStatus::Off if webdav => (
+            "Sync is off. Enter the URL of a JSON file on a WebDAV server, e.g. in Nextcloud."
+                .to_string(),
+            muted,
+        ),
+        SyncStatus::Off => (
+            "Sync is off. It needs a server speaking the protocol in docs/sync-protocol.md."
+                .to_string(),
+            muted,
+        ),
+        SyncStatus::Syncing => ("Syncing…".to_string(), muted),
+        SyncStatus::Idle if state.last_synced == 0 => ("Not synced yet.".to_string(), muted),
+        SyncStatus::Idle => (
+            format!(
+                "Last synced {}.",
+                ago(widgets::unix_now().saturating_sub(state.last_synced))
+            ),
+            muted,
+        ),
+        SyncStatus::Failed(e) => (format!("Sync failed: {e}"), theme().error),
+        SyncStatus::Conflict => (
+            "This browser and the file both changed since the last sync. Sync now to choose what to keep."
+                .to_string(),
+            theme().error,
+        ),
+    };
+    // Saves one field of the target, keeping the others.
+    let save = {
+        let syncer = ctx.syncer.clone();
+        move |edit: fn(&mut Target, String), value: String| {
+            let mut target = sync::load_state().target();
+            edit(&mut target, value);
+            let on = !target.url.is_empty();
+            syncer.configure(target);
+            if on {
+                syncer.sync_now(widgets::unix_now());
+            }
         }
-        let _ = w.location().set_href(url);
+    };
+
+    let server = Column(Modifier::new().fill_max_width()).child((
+        Row(Modifier::new().flex_wrap(FlexWrap::Wrap)).child(
+            Backend::ALL
+                .into_iter()
+                .map(|b| {
+                    Chip(b.label(), state.backend == b, {
+                        let syncer = ctx.syncer.clone();
+                        // Another kind of server starts out turned off.
+                        move || {
+                            if sync::load_state().backend != b {
+                                syncer.configure(Target {
+                                    backend: b,
+                                    ..Target::default()
+                                });
+                            }
+                        }
+                    })
+                })
+                .collect::<Vec<_>>(),
+        ),
+        label(if webdav { "File URL" } else { "Server URL" }),
+        TextField(
+            if !state.url.is_empty() {
+                state.url.clone()
+            } else if webdav {
+                "https://cloud.example.com/remote.php/dav/files/me/startpage.json".to_string()
+            } else {
+                "https://sync.example.com/startpage, then Enter".to_string()
+            },
+            // Re-keyed per server so the field shows the saved URL after a change.
+            field(hash64("settings.sync_url") ^ hash64(&state.url) ^ webdav as u64),
+            None::<fn(String)>,
+            Some({
+                let save = save.clone();
+                let snackbar = ctx.snackbar.clone();
+                move |url: String| {
+                    if url.trim().is_empty() {
+                        return;
+                    }
+                    match sync::validate_url(&url) {
+                        Ok(()) => save(|t, url| t.url = url, url),
+                        Err(e) => notify(&snackbar, e),
+                    }
+                }
+            }),
+        ),
+    ));
+
+    let credentials = Column(Modifier::new().fill_max_width()).child((
+        if webdav {
+            Column(Modifier::new().fill_max_width()).child((
+                label("User name"),
+                TextField(
+                    if state.user.is_empty() {
+                        "User name, then Enter".to_string()
+                    } else {
+                        state.user.clone()
+                    },
+                    field(hash64("settings.sync_user") ^ hash64(&state.user)),
+                    None::<fn(String)>,
+                    Some({
+                        let save = save.clone();
+                        move |user: String| save(|t, user| t.user = user, user)
+                    }),
+                ),
+            ))
+        } else {
+            Box(Modifier::new())
+        },
+        label(if webdav { "Password" } else { "Token" }),
+        SecretRow(
+            match (state.token.is_empty(), webdav) {
+                (true, true) => "None; enter an app password",
+                (true, false) => "None; set one if the server asks for a token",
+                (false, _) => "Set",
+            },
+            if webdav {
+                "WebDAV password"
+            } else {
+                "Sync server token"
+            },
+            !state.token.is_empty(),
+            Rc::new({
+                let save = save.clone();
+                move |token: String| save(|t, token| t.token = token, token)
+            }),
+        ),
+        label("Encryption passphrase"),
+        SecretRow(
+            if state.passphrase.is_empty() {
+                "None; what is synced is not encrypted"
+            } else {
+                "Set; every browser syncing here needs the same one"
+            },
+            "Passphrase to encrypt what is synced",
+            !state.passphrase.is_empty(),
+            Rc::new(move |p: String| save(|t, p| t.passphrase = p, p)),
+        ),
+    ));
+
+    Column(
+        Modifier::new()
+            .fill_max_width()
+            .padding_values(PaddingValues {
+                top: 8.0,
+                ..Default::default()
+            }),
+    )
+    .child((
+        server,
+        credentials,
+        Text(status)
+            .size(12.0)
+            .color(color)
+            .modifier(Modifier::new().padding_values(PaddingValues {
+                top: 6.0,
+                ..Default::default()
+            })),
+        if state.url.is_empty() {
+            Box(Modifier::new())
+        } else {
+            Row(Modifier::new()
+                .flex_wrap(FlexWrap::Wrap)
+                .padding_values(PaddingValues {
+                    top: 8.0,
+                    ..Default::default()
+                }))
+            .child((
+                GhostButton("Sync now", {
+                    let syncer = ctx.syncer.clone();
+                    move || syncer.sync_now(widgets::unix_now())
+                }),
+                Box(Modifier::new().width(8.0).height(1.0)),
+                GhostButton("Turn off sync", {
+                    let syncer = ctx.syncer.clone();
+                    move || {
+                        syncer.configure(Target {
+                            backend: state.backend,
+                            ..Target::default()
+                        })
+                    }
+                }),
+            ))
+        },
+    ))
+}
+
+/// Sync state next to the header buttons; nothing while sync is off.
+fn SyncBadge(status: &SyncStatus) -> View {
+    match status.label() {
+        Some(text) => Text(text)
+            .size(12.0)
+            .color(match status {
+                SyncStatus::Failed(_) => theme().error,
+                _ => Color::from_hex("#9CA3AF"),
+            })
+            .modifier(Modifier::new().padding_values(PaddingValues {
+                right: 8.0,
+                ..Default::default()
+            })),
+        None => Box(Modifier::new()),
     }
 }
 
-fn normalize_url(s: &str) -> Option<String> {
-    let t = s.trim();
-    if t.is_empty() {
-        return None;
-    }
-    if t.starts_with("http://") || t.starts_with("https://") {
-        return Some(t.to_string());
-    }
-    if !t.contains(' ') && t.contains('.') {
-        return Some(format!("https://{t}"));
-    }
-    None
+/// Name, theme override and delete button of one dashboard.
+fn DashboardRow(d: Dashboard, ctx: &DrawerCtx) -> View {
+    let shown = d.id == ctx.active_dashboard.get();
+    Column(
+        Modifier::new()
+            .fill_max_width()
+            .padding_values(PaddingValues {
+                top: 8.0,
+                ..Default::default()
+            }),
+    )
+    .child((
+        Row(Modifier::new()
+            .fill_max_width()
+            .align_items(AlignItems::Center)
+            .padding_values(PaddingValues {
+                bottom: 6.0,
+                ..Default::default()
+            }))
+        .child((
+            TextField(
+                d.name.clone(),
+                Modifier::new()
+                    .key(hash64(&format!("settings.dashboard.{}", d.id)))
+                    .height(36.0)
+                    .weight(1.0)
+                    .min_width(0.0)
+                    .background(Color::from_hex("#0F172A"))
+                    .border(1.0, theme().outline, 10.0)
+                    .clip_rounded(10.0),
+                Some({
+                    let list = ctx.dashboards.clone();
+                    let id = d.id.clone();
+                    move |name: String| {
+                        let name = name.trim().to_string();
+                        if !name.is_empty() {
+                            dashboards::update(&list, &id, |d| d.name = name);
+                        }
+                    }
+                }),
+                None::<fn(String)>,
+            ),
+            Box(Modifier::new().width(8.0).height(1.0)),
+            if shown {
+                Text("Shown")
+                    .size(13.0)
+                    .color(theme().primary)
+                    .modifier(Modifier::new().padding(8.0))
+            } else {
+                GhostButton("Show", {
+                    let active = ctx.active_dashboard.clone();
+                    let id = d.id.clone();
+                    move || dashboards::select(&active, &id)
+                })
+            },
+            if d.id == dashboards::HOME {
+                Box(Modifier::new())
+            } else {
+                IconButton("×", {
+                    let confirm = ctx.confirm_delete_dashboard.clone();
+                    let id = d.id.clone();
+                    move || confirm.set(Some(id.clone()))
+                })
+            },
+        )),
+        Row(Modifier::new().flex_wrap(FlexWrap::Wrap)).child(
+            std::iter::once(None)
+                .chain(ThemeChoice::ALL.into_iter().map(Some))
+                .map(|t| {
+                    Chip(
+                        t.map_or("Default theme", ThemeChoice::label),
+                        d.theme == t,
+                        {
+                            let list = ctx.dashboards.clone();
+                            let id = d.id.clone();
+                            move || dashboards::update(&list, &id, |d| d.theme = t)
+                        },
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ),
+    ))
+}
+
+fn SettingsSection(title: &str, content: View) -> View {
+    Box(Modifier::new()
+        .fill_max_width()
+        .padding_values(PaddingValues {
+            bottom: 12.0,
+            ..Default::default()
+        }))
+    .child(material3::Card(
+        Modifier::new().fill_max_width(),
+        false,
+        Column(Modifier::new().fill_max_width())
+            .child((Text(title).size(15.0).color(theme().on_surface), content)),
+    ))
+}
+
+fn Chip(label: &str, selected: bool, on_click: impl Fn() + 'static) -> View {
+    Box(Modifier::new().padding_values(PaddingValues {
+        right: 6.0,
+        bottom: 6.0,
+        ..Default::default()
+    }))
+    .child(material3::FilterChip(
+        selected,
+        on_click,
+        Text(label).size(13.0).single_line().color(if selected {
+            theme().on_primary
+        } else {
+            theme().on_surface
+        }),
+        None,
+    ))
+}
+
+/// Asks before resetting settings or erasing all stored data.
+fn ResetDialog(
+    kind: ResetKind,
+    confirm_reset: Rc<Signal<Option<ResetKind>>>,
+    settings: Rc<Signal<Settings>>,
+    snackbar: Rc<SnackbarController>,
+) -> View {
+    let (title, text, action) = match kind {
+        ResetKind::Settings => (
+            "Reset settings?",
+            "Search, bookmark and appearance settings go back to their defaults. Bookmarks and widgets are kept.",
+            "Reset",
+        ),
+        ResetKind::Everything => (
+            "Erase all data?",
+            "Bookmarks, widgets and settings are removed from this browser. Export first if you want a copy.",
+            "Erase",
+        ),
+    };
+    let dismiss = {
+        let confirm_reset = confirm_reset.clone();
+        move || confirm_reset.set(None)
+    };
+
+    material3::AlertDialog(
+        true,
+        dismiss.clone(),
+        Text(title).size(18.0).color(theme().on_surface),
+        Text(text).size(14.0).color(Color::from_hex("#9CA3AF")),
+        Button(Text(action).color(theme().on_primary), move || {
+            confirm_reset.set(None);
+            match kind {
+                ResetKind::Settings => {
+                    settings.set(Settings::default());
+                    settings::save(&settings.get());
+                    notify(&snackbar, "Settings reset to defaults");
+                }
+                ResetKind::Everything => {
+                    storage::clear_all();
+                    if let Some(w) = web_sys::window() {
+                        let _ = w.location().reload();
+                    }
+                }
+            }
+        })
+        .modifier(
+            Modifier::new()
+                .background(theme().primary)
+                .clip_rounded(10.0),
+        ),
+        Some(Button(Text("Cancel").color(theme().on_surface), dismiss)),
+    )
+}
+
+/// Asks what to do when this browser and the WebDAV file both changed.
+fn SyncConflictDialog(conflict: &Conflict, syncer: Syncer) -> View {
+    let local: usize = dashboards::load()
+        .iter()
+        .map(|d| storage::load_dashboard_bookmarks(&d.id).map_or(0, |v| v.len()))
+        .sum();
+    let text = format!(
+        "This browser ({local} bookmarks) and the file on the server ({} bookmarks) both changed since the last sync. Merging keeps the bookmarks of both and takes the server's settings and widgets.",
+        conflict.remote_bookmarks()
+    );
+    let choose = |choice: Resolution| {
+        let syncer = syncer.clone();
+        move || syncer.resolve(choice, widgets::unix_now())
+    };
+    let dismiss = {
+        let syncer = syncer.clone();
+        move || syncer.conflict.set(None)
+    };
+
+    material3::AlertDialog(
+        true,
+        dismiss.clone(),
+        Text("Sync conflict").size(18.0).color(theme().on_surface),
+        Column(Modifier::new().fill_max_width()).child((
+            Text(text).size(14.0).color(Color::from_hex("#9CA3AF")),
+            Row(Modifier::new()
+                .flex_wrap(FlexWrap::Wrap)
+                .padding_values(PaddingValues {
+                    top: 12.0,
+                    ..Default::default()
+                }))
+            .child((
+                GhostButton("Keep this browser's", choose(Resolution::KeepLocal)),
+                Box(Modifier::new().width(8.0).height(1.0)),
+                GhostButton("Use the server's", choose(Resolution::UseRemote)),
+            )),
+        )),
+        Button(
+            Text("Merge").color(theme().on_primary),
+            choose(Resolution::Merge),
+        )
+        .modifier(
+            Modifier::new()
+                .background(theme().primary)
+                .clip_rounded(10.0),
+        ),
+        Some(Button(Text("Later").color(theme().on_surface), dismiss)),
+    )
+}
+
+/// Asks before deleting a dashboard along with its bookmarks and widgets.
+fn DeleteDashboardDialog(
+    id: String,
+    confirm: Rc<Signal<Option<String>>>,
+    list: Rc<Signal<Vec<Dashboard>>>,
+    active: Rc<Signal<String>>,
+) -> View {
+    let name = list
+        .get()
+        .into_iter()
+        .find(|d| d.id == id)
+        .map_or_else(|| id.clone(), |d| d.name);
+    let dismiss = {
+        let confirm = confirm.clone();
+        move || confirm.set(None)
+    };
+
+    material3::AlertDialog(
+        true,
+        dismiss.clone(),
+        Text(format!("Delete “{name}”?"))
+            .size(18.0)
+            .color(theme().on_surface),
+        Text("Its bookmarks, tile styles and widgets are removed from this browser.")
+            .size(14.0)
+            .color(Color::from_hex("#9CA3AF")),
+        Button(Text("Delete").color(theme().on_primary), move || {
+            confirm.set(None);
+            dashboards::remove(&list, &active, &id);
+        })
+        .modifier(
+            Modifier::new()
+                .background(theme().primary)
+                .clip_rounded(10.0),
+        ),
+        Some(Button(Text("Cancel").color(theme().on_surface), dismiss)),
+    )
+}
+
+/// A bookmark being edited. Fields left empty keep their old value.
+#[derive(Clone)]
+struct EditState {
+    original: Bookmark,
+    /// Saving adds a copy of `original` under the typed URL instead of changing it.
+    copy: bool,
+    title: String,
+    url: String,
+    group: String,
+    open_in: Option<LinkTarget>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum SearchEngine {
-    DuckDuckGo,
-    Google,
-    Brave,
-}
-
-impl SearchEngine {
-    fn label(self) -> &'static str {
-        match self {
-            SearchEngine::DuckDuckGo => "DuckDuckGo",
-            SearchEngine::Google => "Google",
-            SearchEngine::Brave => "Brave",
+impl EditState {
+    fn new(original: Bookmark) -> Self {
+        Self {
+            open_in: original.open_in,
+            original,
+            copy: false,
+            title: String::new(),
+            url: String::new(),
+            group: String::new(),
         }
     }
 
-    fn url(self, query: &str) -> String {
-        let q = urlencoding::encode(query.trim());
-        match self {
-            SearchEngine::DuckDuckGo => format!("https://duckduckgo.com/?q={q}"),
-            SearchEngine::Google => format!("https://www.google.com/search?q={q}"),
-            SearchEngine::Brave => format!("https://search.brave.com/search?q={q}"),
+    fn copy_of(original: Bookmark) -> Self {
+        Self {
+            copy: true,
+            ..Self::new(Bookmark {
+                title: format!("{} (copy)", original.title),
+                pinned: false,
+                ..original
+            })
         }
     }
 }
 
-fn search_or_open(engine: SearchEngine, input: &str) {
-    if let Some(url) = normalize_url(input) {
-        open_url(&url);
-        return;
-    }
-    open_url(&engine.url(input));
-}
-
-fn theme_pro() -> Theme {
-    let mut t = Theme::default();
-    t.background = Color::from_hex("#0B0F14");
-    t.surface = Color::from_hex("#111827");
-    t.on_surface = Color::from_hex("#E5E7EB");
-    t.primary = Color::from_hex("#3B82F6");
-    t.on_primary = Color::WHITE;
-    t.outline = Color::from_hex("#243041");
-    t.focus = Color::from_hex("#60A5FA");
-    t.button_bg = Color::from_hex("#1F2937");
-    t.button_bg_hover = Color::from_hex("#243041");
-    t.button_bg_pressed = Color::from_hex("#2B3A52");
-    t.scrollbar_track = Color(0xFF, 0xFF, 0xFF, 16);
-    t.scrollbar_thumb = Color(0xFF, 0xFF, 0xFF, 80);
-    t
-}
-
-fn EnginePill(label: &str, selected: bool, on_click: impl Fn() + 'static) -> View {
-    let bg = if selected {
-        Color(theme().primary.0, theme().primary.1, theme().primary.2, 48)
-    } else {
-        Color(0, 0, 0, 0)
+/// Edit title, URL, group and link target of a bookmark, or of a copy to add
+/// after it. The fields start empty and show the current value as their hint.
+fn EditBookmarkDialog(
+    state: EditState,
+    editing: Rc<Signal<Option<EditState>>>,
+    bookmarks: Rc<Signal<Vec<Bookmark>>>,
+    settings: Settings,
+    snackbar: Rc<SnackbarController>,
+) -> View {
+    let dismiss = {
+        let editing = editing.clone();
+        move || editing.set(None)
+    };
+    let field = |hint: String, key: &str, f: fn(&mut EditState, String)| {
+        TextField(
+            hint,
+            Modifier::new()
+                .key(hash64(key) ^ hash64(&state.original.url) ^ state.copy as u64)
+                .height(40.0)
+                .fill_max_width()
+                .background(Color::from_hex("#0F172A"))
+                .border(1.0, theme().outline, 10.0)
+                .clip_rounded(10.0),
+            Some({
+                let editing = editing.clone();
+                move |s: String| {
+                    editing.update(|e| {
+                        if let Some(e) = e {
+                            f(e, s)
+                        }
+                    })
+                }
+            }),
+            None::<fn(String)>,
+        )
     };
+    let gap = || Box(Modifier::new().width(1.0).height(10.0));
+    let orig = &state.original;
 
-    Button(
-        Text(label).size(13.0).single_line().color(if selected {
-            theme().primary
+    let form = Column(Modifier::new().fill_max_width().min_width(280.0)).child((
+        field(orig.title.clone(), "edit.title", |e, s| e.title = s),
+        gap(),
+        field(
+            if state.copy {
+                format!("URL of the copy, not {}", orig.url)
+            } else {
+                orig.url.clone()
+            },
+            "edit.url",
+            |e, s| e.url = s,
+        ),
+        gap(),
+        field(
+            if orig.group.is_empty() {
+                "Group (optional)".to_string()
+            } else {
+                orig.group.clone()
+            },
+            "edit.group",
+            |e, s| e.group = s,
+        ),
+        gap(),
+        Row(Modifier::new().flex_wrap(FlexWrap::Wrap)).child(
+            std::iter::once(None)
+                .chain(LinkTarget::ALL.into_iter().map(Some))
+                .map(|t| {
+                    Chip(
+                        t.map_or("Default", LinkTarget::label),
+                        state.open_in == t,
+                        {
+                            let editing = editing.clone();
+                            move || {
+                                editing.update(|e| {
+                                    if let Some(e) = e {
+                                        e.open_in = t
+                                    }
+                                })
+                            }
+                        },
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ),
+    ));
+
+    material3::AlertDialog(
+        true,
+        dismiss.clone(),
+        Text(if state.copy {
+            "Duplicate bookmark"
         } else {
-            Color::from_hex("#9CA3AF")
-        }),
-        on_click,
-    )
-    .modifier(
-        Modifier::new()
-            .padding_values(PaddingValues {
-                left: 12.0,
-                right: 12.0,
-                top: 6.0,
-                bottom: 6.0,
-            })
-            .background(bg)
-            .clip_rounded(999.0),
+            "Edit bookmark"
+        })
+        .size(18.0)
+        .color(theme().on_surface),
+        form,
+        Button(Text("Save").color(theme().on_primary), move || {
+            let Some(e) = editing.get() else {
+                return;
+            };
+            let url = match e.url.trim() {
+                "" if e.copy => {
+                    notify(&snackbar, "Enter a URL for the copy");
+                    return;
+                }
+                "" => e.original.url.clone(),
+                raw => match normalize_url(&settings, raw) {
+                    Some(url) => url,
+                    None => {
+                        notify(&snackbar, "Invalid URL format");
+                        return;
+                    }
+                },
+            };
+            if let Err(err) = settings.url_policy().check(&url) {
+                notify(&snackbar, &format!("Can't save {url}: {err}"));
+                return;
+            }
+            let keep_or = |typed: &str, old: &str| match typed.trim() {
+                "" => old.to_string(),
+                new => new.to_string(),
+            };
+            if e.copy {
+                if bookmarks.get().iter().any(|b| b.url == url) {
+                    notify(&snackbar, &format!("{url} is already saved"));
+                    return;
+                }
+                let copy = Bookmark {
+                    title: keep_or(&e.title, &e.original.title),
+                    url,
+                    group: keep_or(&e.group, &e.original.group),
+                    open_in: e.open_in,
+                    ..e.original.clone()
+                };
+                bookmarks.update(|v| {
+                    let at = v
+                        .iter()
+                        .position(|b| b.url == e.original.url)
+                        .map_or(v.len(), |i| i + 1);
+                    v.insert(at, copy);
+                });
+                storage::save_bookmarks(&bookmarks.get());
+                editing.set(None);
+                return;
+            }
+            update_bookmark(&bookmarks, &e.original.url, |b| {
+                b.title = keep_or(&e.title, &e.original.title);
+                b.url = url;
+                b.group = keep_or(&e.group, &e.original.group);
+                b.open_in = e.open_in;
+            });
+            editing.set(None);
+        })
+        .modifier(
+            Modifier::new()
+                .background(theme().primary)
+                .clip_rounded(10.0),
+        ),
+        Some(Button(Text("Cancel").color(theme().on_surface), dismiss)),
     )
 }
 
-fn IconButton(icon: &str, on_click: impl Fn() + 'static) -> View {
-    Box(Modifier::new()
-        .size(32.0, 32.0)
-        .background(Color(0, 0, 0, 0))
-        .clip_rounded(8.0)
-        .clickable()
-        .on_pointer_down(move |_| on_click()))
-    .child(Text(icon).size(18.0).color(Color::from_hex("#9CA3AF")))
+/// Links pasted or dropped at once, waiting for the batch-add preview to be confirmed.
+#[derive(Clone, Debug, PartialEq)]
+struct PastedLinks {
+    urls: Vec<String>,
+    group: String,
 }
 
-fn BookmarkTile(
-    bm: Bookmark,
+/// Rows listed in the batch-add preview before it says how many more there are.
+const PREVIEW_ROWS: usize = 12;
+
+/// Preview of several pasted or dropped links, added together to one group.
+/// Links that are already saved are listed but skipped.
+fn PastedLinksDialog(
+    links: PastedLinks,
+    batch: Rc<Signal<Option<PastedLinks>>>,
     bookmarks: Rc<Signal<Vec<Bookmark>>>,
     snackbar: Rc<SnackbarController>,
 ) -> View {
-    let url = bm.url.clone();
-    let url_clone = bm.url.clone();
-    let title = bm.title.clone();
-
-    let bms = bookmarks.clone();
-    let snackbar_remove = snackbar.clone();
-    let url_for_remove = bm.url.clone();
+    let dismiss = {
+        let batch = batch.clone();
+        move || batch.set(None)
+    };
+    let saved = bookmarks.get();
+    let is_saved = |url: &str| saved.iter().any(|bm| bm.url == url);
+    let new_count = links.urls.iter().filter(|u| !is_saved(u)).count();
 
-    Box(Modifier::new()
-        .fill_max_width()
-        .background(theme().surface)
-        .border(1.0, theme().outline, 10.0)
-        .clip_rounded(10.0)
-        .padding_values(PaddingValues {
-            left: 14.0,
-            right: 10.0,
-            top: 12.0,
-            bottom: 12.0,
+    let mut rows: Vec<View> = links
+        .urls
+        .iter()
+        .take(PREVIEW_ROWS)
+        .map(|url| {
+            let title = paste::title_for_url(url);
+            let sub = if is_saved(url) {
+                format!("{} · already saved", truncate_url(url))
+            } else {
+                truncate_url(url)
+            };
+            Column(
+                Modifier::new()
+                    .fill_max_width()
+                    .padding_values(PaddingValues {
+                        bottom: 8.0,
+                        ..Default::default()
+                    }),
+            )
+            .child((
+                Text(title)
+                    .size(14.0)
+                    .color(theme().on_surface)
+                    .single_line(),
+                Text(sub)
+                    .size(12.0)
+                    .color(Color::from_hex("#9CA3AF"))
+                    .single_line(),
+            ))
         })
-        .clickable()
-        .on_pointer_down(move |_| open_url(&url))
-        .cursor(CursorIcon::Pointer))
-    .child(
-        Row(Modifier::new()
-            .fill_max_width()
-            .align_items(AlignItems::Center))
-        .child((
-            // Content area (title + url)
-            Box(Modifier::new().weight(1.0).min_width(0.0)).child(
-                Column(Modifier::new()).child((
-                    Text(title)
-                        .size(15.0)
-                        .single_line()
-                        .overflow_ellipsize()
-                        .color(theme().on_surface)
-                        .modifier(Modifier::new().fill_max_width()),
-                    Text(truncate_url(&url_clone))
-                        .size(12.0)
-                        .single_line()
-                        .overflow_ellipsize()
-                        .color(Color::from_hex("#6B7280"))
-                        .modifier(Modifier::new().fill_max_width()),
-                )),
-            ),
-            // Remove button (only visible on hover/interaction)
-            IconButton("×", {
-                let bms = bms.clone();
-                let snackbar_remove = snackbar_remove.clone();
-                let url_for_remove = url_for_remove.clone();
-                move || {
-                    bms.update(|v| {
-                        if let Some(pos) = v.iter().position(|x| x.url == url_for_remove) {
-                            v.remove(pos);
+        .collect();
+    if links.urls.len() > PREVIEW_ROWS {
+        rows.push(
+            Text(format!("and {} more…", links.urls.len() - PREVIEW_ROWS))
+                .size(12.0)
+                .color(Color::from_hex("#9CA3AF")),
+        );
+    }
+
+    let form = Column(Modifier::new().fill_max_width().min_width(280.0)).child((
+        Column(Modifier::new().fill_max_width()).child(rows),
+        TextField(
+            if links.group.is_empty() {
+                "Group (optional)".to_string()
+            } else {
+                links.group.clone()
+            },
+            Modifier::new()
+                .key(hash64("paste.group") ^ hash64(&links.urls.join(" ")))
+                .height(40.0)
+                .fill_max_width()
+                .background(Color::from_hex("#0F172A"))
+                .border(1.0, theme().outline, 10.0)
+                .clip_rounded(10.0),
+            Some({
+                let batch = batch.clone();
+                move |s: String| {
+                    batch.update(|b| {
+                        if let Some(b) = b {
+                            b.group = s
                         }
-                    });
-                    storage::save_bookmarks(&bms.get());
-
-                    let sb = snackbar_remove.clone();
-                    sb.show(SnackbarRequest {
-                        message: "Bookmark removed".to_string(),
-                        action: None,
-                        duration_ms: 3000,
-                        builder: Rc::new({
-                            let sb = snackbar_remove.clone();
-                            move || {
-                                material3::Snackbar(
-                                    "Bookmark removed",
-                                    Some(SnackbarAction {
-                                        label: "Dismiss".to_string(),
-                                        on_click: Rc::new({
-                                            let sb = sb.clone();
-                                            move || sb.dismiss()
-                                        }),
-                                    }),
-                                    Modifier::new().absolute().offset(
-                                        Some(16.0),
-                                        None,
-                                        Some(16.0),
-                                        None,
-                                    ),
-                                )
-                            }
-                        }),
-                    });
+                    })
                 }
             }),
-        )),
+            None::<fn(String)>,
+        ),
+    ));
+
+    material3::AlertDialog(
+        true,
+        dismiss.clone(),
+        Text(format!("Add {} links", links.urls.len()))
+            .size(18.0)
+            .color(theme().on_surface),
+        form,
+        Button(
+            Text(format!("Add {new_count}")).color(theme().on_primary),
+            move || {
+                let Some(links) = batch.get() else {
+                    return;
+                };
+                let group = links.group.trim().to_string();
+                let mut added = Vec::new();
+                bookmarks.update(|v| {
+                    for url in links.urls {
+                        if v.iter().any(|bm| bm.url == url) {
+                            continue;
+                        }
+                        v.push(Bookmark {
+                            title: paste::title_for_url(&url),
+                            url,
+                            group: group.clone(),
+                            health: None,
+                            open_in: None,
+                            tags: Vec::new(),
+                            pinned: false,
+                        });
+                        added.push(v[v.len() - 1].clone());
+                    }
+                });
+                storage::save_bookmarks(&bookmarks.get());
+                for bm in &added {
+                    look_up_metadata(&bookmarks, &bm.url, &bm.title);
+                }
+                batch.set(None);
+                notify(
+                    &snackbar,
+                    &match added.len() {
+                        0 => "All of these links are already saved".to_string(),
+                        1 => "Added 1 bookmark".to_string(),
+                        n => format!("Added {n} bookmarks"),
+                    },
+                );
+            },
+        )
+        .modifier(
+            Modifier::new()
+                .background(theme().primary)
+                .clip_rounded(10.0),
+        ),
+        Some(Button(Text("Cancel").color(theme().on_surface), dismiss)),
     )
 }
 
+/// Replace the URL-derived `placeholder` title of the bookmark at `url` with
+/// the page's own once the lookup answers. A title edited in the meantime is
+/// kept.
+fn look_up_metadata(bookmarks: &Rc<Signal<Vec<Bookmark>>>, url: &str, placeholder: &str) {
+    let Some(fetcher) = metadata::fetcher(&settings::load(), net::browser()) else {
+        return;
+    };
+    // The user may switch dashboards before the answer arrives.
+    let dashboard = storage::dashboard();
+    let bookmarks = bookmarks.clone();
+    let placeholder = placeholder.to_string();
+    let target = url.to_string();
+    fetcher.fetch(
+        url,
+        Box::new(move |result| {
+            let meta = match result {
+                Ok(meta) => meta,
+                Err(e) => {
+                    log::info!("title lookup for {target}: {e}");
+                    return;
+                }
+            };
+            bookmarks.update(|v| {
+                if let Some(bm) = v.iter_mut().find(|b| b.url == target)
+                    && let Some(title) = meta.title
+                    && bm.title == placeholder
+                {
+                    bm.title = title;
+                }
+            });
+            storage::save_dashboard_bookmarks(&dashboard, &bookmarks.get());
+        }),
+    );
+}
+
+/// Links to add from text pasted or dropped onto the page. Says why in the
+/// snackbar when there is nothing to add.
+fn pasted_links(
+    text: &str,
+    settings: &Settings,
+    bookmarks: &[Bookmark],
+    snackbar: &Rc<SnackbarController>,
+) -> Vec<String> {
+    let found = paste::find_urls(text, &settings.intranet_hosts, &settings.url_policy());
+    match found.urls.as_slice() {
+        [] if found.rejected > 0 => notify(
+            snackbar,
+            "The pasted link uses a scheme that isn't allowed (see Settings)",
+        ),
+        [] => notify(snackbar, "Nothing to add: no link in the pasted text"),
+        [url] if bookmarks.iter().any(|bm| &bm.url == url) => {
+            notify(snackbar, &format!("{url} is already saved"))
+        }
+        _ => return found.urls,
+    }
+    Vec::new()
+}
+
 fn truncate_url(url: &str) -> String {
     url.replace("https://", "")
         .replace("http://", "")
@@ -233,34 +2904,440 @@ fn hash64(s: &str) -> u64 {
 }
 
 pub fn app(s: &mut Scheduler) -> View {
-    set_theme_default(theme_pro());
+    let settings = remember(|| signal(settings::load()));
+    let dashboard_list = remember(|| signal(dashboards::load()));
+    let syncer = remember(|| Syncer::new(net::browser()));
+    syncer.tick(widgets::unix_now());
+    // Bumped when a sync pass changed stored data; state loaded from storage
+    // is reloaded, and per-dashboard state is keyed by it below.
+    let synced = syncer.applied.get();
+    let seen_synced = remember(|| Cell::new(0u64));
+    if seen_synced.replace(synced) != synced {
+        settings.set(settings::load());
+        dashboard_list.set(dashboards::load());
+    }
+    // Engine from `?engine=`, used until another one is picked.
+    let engine_override = remember(|| signal(None::<SearchEngine>));
+    let cfg = with_engine(settings.get(), engine_override.get());
+
+    let active_dashboard = remember(|| signal(dashboards::initial(&dashboard_list.get())));
+    let dashboard = dashboard_list
+        .get()
+        .into_iter()
+        .find(|d| d.id == active_dashboard.get())
+        .unwrap_or_else(Dashboard::home);
+    // Everything loaded below through `storage::scoped` belongs to this dashboard.
+    storage::set_dashboard(&dashboard.id);
+    let dash_key = |name: &str| format!("{name}@{}", dashboard.id);
+    set_theme_default(theme_for(dashboard.theme.unwrap_or(cfg.theme)));
 
     // State
-    let bookmarks = remember(|| signal(storage::load_bookmarks()));
+    // Why the bookmarks couldn't be loaded; shown once the snackbar exists.
+    let load_error = remember(|| signal(None::<String>));
+    let bookmarks = remember_with_key(dash_key(&format!("bookmarks#{synced}")), || {
+        signal(storage::load_bookmarks().unwrap_or_else(|e| {
+            log::error!("{e:#}");
+            load_error.set(Some(format!("{e:#}")));
+            Vec::new()
+        }))
+    });
     let query = remember(|| signal(String::new()));
-    let engine = remember(|| signal(SearchEngine::DuckDuckGo));
     let new_title = remember(|| signal(String::new()));
     let new_url = remember(|| signal(String::new()));
+    let new_group = remember(|| signal(String::new()));
+    let new_open_in = remember(|| signal(None::<LinkTarget>));
+    let tile_styles = remember_with_key(dash_key(&format!("tile_styles#{synced}")), || {
+        signal(storage::load_tile_styles())
+    });
     let show_add_form = remember(|| signal(false));
     let form_epoch = remember(|| signal(0u64));
+    // Tiles picked in multi-select mode; `None` when not selecting.
+    let selection = remember_with_key(dash_key("selection"), || signal(None::<Selection>));
+    let bulk_epoch = remember(|| signal(0u64));
     let root_scroll = remember_scroll_state("root_scroll");
+    let widget_layout = remember_with_key(dash_key(&format!("widget_layout#{synced}")), || {
+        signal(widgets::load_layout())
+    });
+    let show_settings = remember(|| signal(false));
+    let confirm_reset = remember(|| signal(None::<ResetKind>));
+    let search_url_error = remember(|| signal(None::<String>));
+    let show_import = remember(|| signal(false));
+    let import_text = remember(|| signal(String::new()));
+    let import_decrypted = remember(|| signal(None::<Result<String, String>>));
+    let export_passphrase = remember(|| signal(String::new()));
+    let show_health = remember(|| signal(false));
+    let show_link_check = remember(|| signal(false));
+    let confirm_delete_dashboard = remember(|| signal(None::<String>));
+    let editing = remember(|| signal(None::<EditState>));
+    // Text pasted or dropped onto the page, handled on the next frame.
+    let pasted = remember(|| signal(None::<String>));
+    let batch = remember(|| signal(None::<PastedLinks>));
 
-    let overlay = remember(|| OverlayHandle::new());
+    let overlay = remember(OverlayHandle::new);
     let snackbar = remember(|| SnackbarController::new((*overlay).clone()));
+    if let Some(e) = load_error.get() {
+        load_error.set(None);
+        notify(&snackbar, &format!("Bookmarks not loaded: {e}"));
+    }
+    let page_widgets = remember_with_key(dash_key(&format!("widgets#{synced}")), || {
+        widgets::Widgets::new(widgets::WidgetContext {
+            snackbar: snackbar.clone(),
+            http: net::browser(),
+        })
+    });
+    let health = remember_with_key(dash_key("health"), || HealthMonitor::new(net::browser()));
+    let link_checker = remember_with_key(dash_key("link_checker"), || {
+        LinkChecker::new(Rc::new(HttpProbe::new(net::browser())))
+    });
+    let palette = remember(|| {
+        let p = Rc::new(CommandPalette::new((*overlay).clone()));
+        palette::install_keyboard(p.clone());
+        p
+    });
+    let menu = remember(|| {
+        let m = Rc::new(BookmarkMenu::new((*overlay).clone()));
+        context_menu::install_listeners(m.clone());
+        m
+    });
+    menu.set_context(bookmarks.get(), {
+        let bookmarks = bookmarks.clone();
+        let editing = editing.clone();
+        let snackbar = snackbar.clone();
+        move |bm, action| run_menu_action(bm, action, &bookmarks, &editing, &snackbar)
+    });
+    // Content y (px) of each group section, recorded while painting; used to jump to a group.
+    let group_anchors = remember_with_key(dash_key("group_anchors"), || {
+        RefCell::new(HashMap::<String, f32>::new())
+    });
+    // Group named by a `#dashboard/group` link, scrolled to once it has been painted.
+    let pending_group = remember(|| signal(None::<String>));
+
+    // Deep links (`?q=`, `?engine=`, `#dashboard/group`), applied once on load.
+    remember(|| {
+        let link = deeplink::current();
+        if let Some(e) = link.engine {
+            if engines(&settings.get()).contains(&e) {
+                engine_override.set(Some(e));
+            } else {
+                log::warn!("deep link: {} search is not configured", e.label());
+            }
+        }
+        match link.query {
+            Some(q) if link.go => deeplink::forward_to(&search_target(
+                &with_engine(settings.get(), engine_override.get()),
+                &q,
+            )),
+            Some(q) => query.set(q),
+            None => {}
+        }
+        pending_group.set(link.target.group);
+
+        dashboards::install_keyboard(dashboard_list.clone(), active_dashboard.clone());
+        deeplink::install_hash_listener({
+            let list = dashboard_list.clone();
+            let active = active_dashboard.clone();
+            let pending = pending_group.clone();
+            move |target| {
+                if let Some(id) = target.dashboard
+                    && list.get().iter().any(|d| d.id == id)
+                {
+                    dashboards::select(&active, &id);
+                }
+                pending.set(target.group);
+            }
+        });
+        links::install_listeners(|url| open_url_in(url, Some(LinkTarget::NewTab)));
+        paste::install_listeners({
+            let pasted = pasted.clone();
+            move |text| pasted.set(Some(text))
+        });
+    });
+    // Middle clicks, pastes and drops only reach the page when no panel or
+    // dialog covers it.
+    let page_idle = !show_settings.get()
+        && !show_import.get()
+        && !show_health.get()
+        && !show_link_check.get()
+        && confirm_reset.get().is_none()
+        && confirm_delete_dashboard.get().is_none()
+        && editing.get().is_none()
+        && batch.get().is_none()
+        && syncer.conflict.get().is_none()
+        && !palette.is_open()
+        && !menu.is_open();
+    links::begin_frame(page_idle);
+    paste::begin_frame(page_idle, s.focused.is_some());
+
+    if let Some(text) = pasted.get() {
+        pasted.set(None);
+        match pasted_links(&text, &cfg, &bookmarks.get(), &snackbar).as_slice() {
+            [] => {}
+            [url] => {
+                new_title.set(paste::title_for_url(url));
+                new_url.set(url.clone());
+                form_epoch.update(|e| *e = e.wrapping_add(1));
+                show_add_form.set(true);
+                // The form sits just below the header.
+                root_scroll.set_offset(0.0);
+            }
+            urls => batch.set(Some(PastedLinks {
+                urls: urls.to_vec(),
+                group: String::new(),
+            })),
+        }
+    }
 
     let px_w = s.size.0 as f32;
     let scale = repose_core::locals::density().scale * repose_core::locals::ui_scale().0;
     let dp_w = if scale > 0.0 { px_w / scale } else { px_w };
 
-    let cols = if dp_w < 520.0 {
-        1
-    } else if dp_w < 860.0 {
-        2
-    } else if dp_w < 1180.0 {
-        3
-    } else {
-        4
-    };
+    // Page padding on both sides; content is capped at the configured width.
+    let max_w = cfg.content_width as f32;
+    let content_w = (dp_w - 48.0).clamp(0.0, max_w);
+
+    health.tick(&bookmarks.get(), widgets::unix_now());
+    let health_state = health.state.get();
+
+    let groups = group_names(&bookmarks.get());
+    if let Some(g) = pending_group.get()
+        && !groups.iter().any(|name| name.eq_ignore_ascii_case(&g))
+    {
+        log::warn!("deep link: no group named {g:?} on this dashboard");
+        pending_group.set(None);
+    }
+
+    let mut commands = CommandRegistry::new();
+    commands.add("Bookmarks", "Add bookmark", {
+        let show = show_add_form.clone();
+        move || show.set(true)
+    });
+    commands.add("Bookmarks", "Health checks", {
+        let show = show_health.clone();
+        move || show.set(true)
+    });
+    commands.add("Bookmarks", "Check links", {
+        let show = show_link_check.clone();
+        let checker = link_checker.clone();
+        let bookmarks = bookmarks.clone();
+        move || {
+            show.set(true);
+            if !checker.report.get().running {
+                start_link_check(&checker, &bookmarks.get());
+            }
+        }
+    });
+    commands.add("Bookmarks", "Select bookmarks", {
+        let selection = selection.clone();
+        move || selection.set(Some(Selection::default()))
+    });
+    for group in &groups {
+        commands.add(
+            "Bookmarks",
+            format!(
+                "Open all in {}",
+                if group.is_empty() { "Bookmarks" } else { group }
+            ),
+            {
+                let bookmarks = bookmarks.clone();
+                let snackbar = snackbar.clone();
+                let group = group.clone();
+                move || open_all(&group_urls(&bookmarks.get(), &group), &snackbar)
+            },
+        );
+    }
+    for tag in tag_names(&bookmarks.get()) {
+        commands.add("Bookmarks", format!("Open all tagged #{tag}"), {
+            let bookmarks = bookmarks.clone();
+            let snackbar = snackbar.clone();
+            move || {
+                let urls: Vec<String> = bookmarks
+                    .get()
+                    .iter()
+                    .filter(|bm| bm.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)))
+                    .map(|bm| bm.url.clone())
+                    .collect();
+                open_all(&urls, &snackbar)
+            }
+        });
+    }
+    for group in &groups {
+        commands.add(
+            "Go to",
+            format!(
+                "Group: {}",
+                if group.is_empty() { "Bookmarks" } else { group }
+            ),
+            {
+                let anchors = group_anchors.clone();
+                let scroll = root_scroll.clone();
+                let group = group.clone();
+                move || {
+                    if let Some(y) = anchors.borrow().get(&group) {
+                        scroll.set_offset(y - 16.0);
+                    }
+                }
+            },
+        );
+    }
+    if !query.get().trim().is_empty() {
+        let (title, url) = search_link(&cfg, &query.get());
+        commands.add("Search", "Copy search link", {
+            let url = url.clone();
+            let snackbar = snackbar.clone();
+            move || copy_text(&url, "Link", &snackbar)
+        });
+        commands.add("Search", "Copy search as Markdown", {
+            let snackbar = snackbar.clone();
+            move || {
+                copy_text(
+                    &clipboard::markdown_link(&title, &url),
+                    "Markdown link",
+                    &snackbar,
+                )
+            }
+        });
+    }
+    commands.add("Go to", "Top of page", {
+        let scroll = root_scroll.clone();
+        move || scroll.set_offset(0.0)
+    });
+    for d in dashboard_list.get() {
+        commands.add("Dashboards", format!("Dashboard: {}", d.name), {
+            let active = active_dashboard.clone();
+            move || dashboards::select(&active, &d.id)
+        });
+    }
+    commands.add("Dashboards", "New dashboard", {
+        let show = show_settings.clone();
+        move || show.set(true)
+    });
+    for e in engines(&cfg) {
+        commands.add("Search", format!("Search with {}", e.label()), {
+            let settings = settings.clone();
+            let engine_override = engine_override.clone();
+            move || {
+                engine_override.set(None);
+                update_settings(&settings, |s| s.engine = e)
+            }
+        });
+    }
+    for t in ThemeChoice::ALL {
+        commands.add("Appearance", format!("Theme: {}", t.label()), {
+            let settings = settings.clone();
+            move || update_settings(&settings, |s| s.theme = t)
+        });
+    }
+    commands.add("Data", "Export data", {
+        let snackbar = snackbar.clone();
+        let passphrase = export_passphrase.clone();
+        move || export_to_file(&snackbar, &passphrase.get())
+    });
+    commands.add("Data", "Import data", {
+        let show = show_import.clone();
+        move || show.set(true)
+    });
+    commands.add("Settings", "Open settings", {
+        let show = show_settings.clone();
+        move || show.set(true)
+    });
+    widgets::register_commands(&page_widgets, widget_layout.clone(), &mut commands);
+    palette.set_commands(commands);
+    let group_sections = groups
+        .iter()
+        .map(|group| {
+            let style = tile_styles
+                .get()
+                .get(group)
+                .copied()
+                .unwrap_or(cfg.default_tile_style);
+            let cols = match style {
+                TileStyle::List => grid_columns(content_w, cfg.tile_min_width as f32, 12.0),
+                TileStyle::SpeedDial => grid_columns(content_w, 104.0, 12.0),
+            };
+            let all = bookmarks.get();
+            let tiles = selection::group_members(&all, group)
+                .into_iter()
+                .map(|bm| {
+                    let bm = bm.clone();
+                    let health = bm
+                        .health
+                        .as_ref()
+                        .map(|_| health_state.get(&bm.url).cloned().unwrap_or_default());
+                    match style {
+                        TileStyle::List => BookmarkTile(
+                            bm,
+                            health,
+                            cfg.show_tile_urls,
+                            bookmarks.clone(),
+                            selection.clone(),
+                            snackbar.clone(),
+                        ),
+                        TileStyle::SpeedDial => SpeedDialTile(
+                            bm,
+                            health,
+                            bookmarks.clone(),
+                            selection.clone(),
+                            snackbar.clone(),
+                        ),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            Column(
+                Modifier::new()
+                    .fill_max_width()
+                    .padding_values(PaddingValues {
+                        bottom: 16.0,
+                        ..Default::default()
+                    })
+                    .painter({
+                        let anchors = group_anchors.clone();
+                        let scroll = root_scroll.clone();
+                        let pending = pending_group.clone();
+                        let group = group.clone();
+                        move |_, rect| {
+                            paste::register_grid(rect);
+                            let y = rect.y + scroll.get();
+                            anchors.borrow_mut().insert(group.clone(), y);
+                            if pending
+                                .get()
+                                .is_some_and(|g| g.eq_ignore_ascii_case(&group))
+                            {
+                                pending.set(None);
+                                scroll.set_offset(y - 16.0);
+                            }
+                        }
+                    }),
+            )
+            .child((
+                GroupHeader(
+                    group,
+                    style,
+                    {
+                        let bookmarks = bookmarks.clone();
+                        let snackbar = snackbar.clone();
+                        let group = group.clone();
+                        move || open_all(&group_urls(&bookmarks.get(), &group), &snackbar)
+                    },
+                    {
+                        let tile_styles = tile_styles.clone();
+                        let group = group.clone();
+                        move || {
+                            let next = match style {
+                                TileStyle::List => TileStyle::SpeedDial,
+                                TileStyle::SpeedDial => TileStyle::List,
+                            };
+                            tile_styles.update(|m| {
+                                m.insert(group.clone(), next);
+                            });
+                            storage::save_tile_styles(&tile_styles.get());
+                        }
+                    },
+                ),
+                Grid(cols, Modifier::new().fill_max_width(), tiles, 12.0, 12.0),
+            ))
+        })
+        .collect::<Vec<_>>();
 
     let content = Surface(
         Modifier::new()
@@ -272,7 +3349,7 @@ pub fn app(s: &mut Scheduler) -> View {
             Column(Modifier::new().fill_max_width().padding(24.0)).child(
                 Box(Modifier::new()
                     .fill_max_width()
-                    .max_width(900.0)
+                    .max_width(max_w)
                     .align_self_center()
                     .min_width(0.0))
                 .child(
@@ -282,15 +3359,52 @@ pub fn app(s: &mut Scheduler) -> View {
                             .align_items(AlignItems::Center),
                     )
                     .child((
-                        // Header - minimal
-                        Text("Startpage")
-                            .size(32.0)
-                            .color(theme().on_surface)
-                            .modifier(Modifier::new().padding_values(PaddingValues {
-                                top: 40.0,
-                                bottom: 32.0,
-                                ..Default::default()
-                            })),
+                        // Header - configurable widgets
+                        Stack(Modifier::new().fill_max_width()).child((
+                            widgets::HeaderWidgets(&page_widgets, &widget_layout.get()),
+                            Box(Modifier::new().absolute().offset(
+                                None,
+                                Some(8.0),
+                                Some(0.0),
+                                None,
+                            ))
+                            .child(
+                                Row(Modifier::new().align_items(AlignItems::Center)).child((
+                                    SyncBadge(&syncer.status.get()),
+                                    IconButton("⌘", {
+                                        let palette = palette.clone();
+                                        move || palette.toggle()
+                                    }),
+                                    IconButton("⚙", {
+                                        let show = show_settings.clone();
+                                        move || show.set(true)
+                                    }),
+                                )),
+                            ),
+                        )),
+                        // Dashboard tabs, only once there is more than one
+                        if dashboard_list.get().len() > 1 {
+                            Row(Modifier::new().flex_wrap(FlexWrap::Wrap).padding_values(
+                                PaddingValues {
+                                    bottom: 16.0,
+                                    ..Default::default()
+                                },
+                            ))
+                            .child(
+                                dashboard_list
+                                    .get()
+                                    .into_iter()
+                                    .map(|d| {
+                                        EnginePill(&d.name, d.id == dashboard.id, {
+                                            let active = active_dashboard.clone();
+                                            move || dashboards::select(&active, &d.id)
+                                        })
+                                    })
+                                    .collect::<Vec<_>>(),
+                            )
+                        } else {
+                            Box(Modifier::new())
+                        },
                         // Search Section - Dominant, centered
                         Box(Modifier::new()
                             .fill_max_width()
@@ -306,58 +3420,99 @@ pub fn app(s: &mut Scheduler) -> View {
                                     .align_items(AlignItems::Center),
                             )
                             .child((
-                                // Large search input
-                                Box(Modifier::new().fill_max_width()).child(TextField(
-                                    "Search or type a URL…",
-                                    Modifier::new()
-                                        .key(0xA11CE_u64)
-                                        .height(56.0)
-                                        .fill_max_width()
-                                        .background(Color::from_hex("#0F172A"))
-                                        .border(1.0, theme().outline, 16.0)
-                                        .clip_rounded(16.0),
-                                    Some({
-                                        let query = query.clone();
-                                        move |s| query.set(s)
-                                    }),
-                                    Some({
-                                        let engine = engine.clone();
-                                        move |submitted: String| {
-                                            search_or_open(engine.get(), &submitted)
-                                        }
-                                    }),
+                                // Large search input, with copy actions once there's a query
+                                Row(Modifier::new()
+                                    .fill_max_width()
+                                    .align_items(AlignItems::Center))
+                                .child((
+                                    TextField(
+                                        // A `?q=` link pre-fills the query; it shows as the hint
+                                        // and Enter on the empty field searches for it.
+                                        match query.get() {
+                                            q if q.is_empty() => {
+                                                "Search or type a URL…".to_string()
+                                            }
+                                            q => q,
+                                        },
+                                        Modifier::new()
+                                            .key(0xA11CE_u64)
+                                            .height(56.0)
+                                            .weight(1.0)
+                                            .min_width(0.0)
+                                            .background(Color::from_hex("#0F172A"))
+                                            .border(1.0, theme().outline, 16.0)
+                                            .clip_rounded(16.0),
+                                        Some({
+                                            let query = query.clone();
+                                            move |s| query.set(s)
+                                        }),
+                                        Some({
+                                            let settings = settings.clone();
+                                            let engine_override = engine_override.clone();
+                                            let query = query.clone();
+                                            move |submitted: String| {
+                                                let input = if submitted.trim().is_empty() {
+                                                    query.get()
+                                                } else {
+                                                    submitted
+                                                };
+                                                search_or_open(
+                                                    &with_engine(
+                                                        settings.get(),
+                                                        engine_override.get(),
+                                                    ),
+                                                    &input,
+                                                )
+                                            }
+                                        }),
+                                    ),
+                                    if query.get().trim().is_empty() {
+                                        Box(Modifier::new())
+                                    } else {
+                                        Row(Modifier::new().padding_values(PaddingValues {
+                                            left: 6.0,
+                                            ..Default::default()
+                                        }))
+                                        .child((
+                                            IconButton("⧉", {
+                                                let (_, url) = search_link(&cfg, &query.get());
+                                                let snackbar = snackbar.clone();
+                                                move || copy_text(&url, "Link", &snackbar)
+                                            }),
+                                            IconButton("M", {
+                                                let (title, url) = search_link(&cfg, &query.get());
+                                                let snackbar = snackbar.clone();
+                                                move || {
+                                                    copy_text(
+                                                        &clipboard::markdown_link(&title, &url),
+                                                        "Markdown link",
+                                                        &snackbar,
+                                                    )
+                                                }
+                                            }),
+                                        ))
+                                    },
                                 )),
                                 // Engine pills - subtle, inline
                                 Row(Modifier::new().padding_values(PaddingValues {
                                     top: 12.0,
                                     ..Default::default()
                                 }))
-                                .child((
-                                    EnginePill(
-                                        SearchEngine::DuckDuckGo.label(),
-                                        engine.get() == SearchEngine::DuckDuckGo,
-                                        {
-                                            let engine = engine.clone();
-                                            move || engine.set(SearchEngine::DuckDuckGo)
-                                        },
-                                    ),
-                                    EnginePill(
-                                        SearchEngine::Google.label(),
-                                        engine.get() == SearchEngine::Google,
-                                        {
-                                            let engine = engine.clone();
-                                            move || engine.set(SearchEngine::Google)
-                                        },
-                                    ),
-                                    EnginePill(
-                                        SearchEngine::Brave.label(),
-                                        engine.get() == SearchEngine::Brave,
-                                        {
-                                            let engine = engine.clone();
-                                            move || engine.set(SearchEngine::Brave)
-                                        },
-                                    ),
-                                )),
+                                .child(
+                                    engines(&cfg)
+                                        .into_iter()
+                                        .map(|e| {
+                                            EnginePill(e.label(), cfg.engine == e, {
+                                                let settings = settings.clone();
+                                                let engine_override = engine_override.clone();
+                                                move || {
+                                                    engine_override.set(None);
+                                                    update_settings(&settings, |s| s.engine = e)
+                                                }
+                                            })
+                                        })
+                                        .collect::<Vec<_>>(),
+                                ),
                             )),
                         ),
                         // Bookmarks Grid - Flat tiles
@@ -369,20 +3524,21 @@ pub fn app(s: &mut Scheduler) -> View {
                                     bottom: 24.0,
                                     ..Default::default()
                                 }))
-                            .child(Grid(
-                                cols,
-                                Modifier::new().fill_max_width(),
-                                bookmarks
-                                    .get()
-                                    .iter()
-                                    .map(|bm| {
-                                        let bm = bm.clone();
-                                        BookmarkTile(bm, bookmarks.clone(), snackbar.clone())
-                                    })
-                                    .collect::<Vec<_>>(),
-                                12.0,
-                                12.0,
-                            ))
+                            .child(
+                                Column(Modifier::new().fill_max_width()).child((
+                                    match selection.get() {
+                                        Some(sel) => SelectionBar(
+                                            sel,
+                                            selection.clone(),
+                                            bookmarks.clone(),
+                                            snackbar.clone(),
+                                            bulk_epoch.clone(),
+                                        ),
+                                        None => Box(Modifier::new()),
+                                    },
+                                    Column(Modifier::new().fill_max_width()).child(group_sections),
+                                )),
+                            )
                         } else {
                             Box(Modifier::new())
                         },
@@ -423,7 +3579,10 @@ pub fn app(s: &mut Scheduler) -> View {
                                     )),
                                     Row(Modifier::new().fill_max_width()).child((
                                         TextField(
-                                            "Title",
+                                            match new_title.get() {
+                                                t if t.is_empty() => "Title (optional)".to_string(),
+                                                t => t,
+                                            },
                                             Modifier::new()
                                                 .key(hash64("title") ^ form_epoch.get())
                                                 .height(40.0)
@@ -440,7 +3599,10 @@ pub fn app(s: &mut Scheduler) -> View {
                                         ),
                                         Box(Modifier::new().width(10.0).height(1.0)),
                                         TextField(
-                                            "URL",
+                                            match new_url.get() {
+                                                u if u.is_empty() => "URL".to_string(),
+                                                u => u,
+                                            },
                                             Modifier::new()
                                                 .key(hash64("url") ^ form_epoch.get())
                                                 .height(40.0)
@@ -456,10 +3618,55 @@ pub fn app(s: &mut Scheduler) -> View {
                                             None::<fn(String)>,
                                         ),
                                     )),
+                                    Box(Modifier::new().fill_max_width().padding_values(
+                                        PaddingValues {
+                                            top: 10.0,
+                                            ..Default::default()
+                                        },
+                                    ))
+                                    .child(TextField(
+                                        "Group (optional)",
+                                        Modifier::new()
+                                            .key(hash64("group") ^ form_epoch.get())
+                                            .height(40.0)
+                                            .fill_max_width()
+                                            .background(Color::from_hex("#0F172A"))
+                                            .border(1.0, theme().outline, 10.0)
+                                            .clip_rounded(10.0),
+                                        Some({
+                                            let new_group = new_group.clone();
+                                            move |s| new_group.set(s)
+                                        }),
+                                        None::<fn(String)>,
+                                    )),
+                                    Row(Modifier::new().flex_wrap(FlexWrap::Wrap).padding_values(
+                                        PaddingValues {
+                                            top: 10.0,
+                                            ..Default::default()
+                                        },
+                                    ))
+                                    .child(
+                                        std::iter::once(None)
+                                            .chain(LinkTarget::ALL.into_iter().map(Some))
+                                            .map(|t| {
+                                                Chip(
+                                                    t.map_or("Default", LinkTarget::label),
+                                                    new_open_in.get() == t,
+                                                    {
+                                                        let new_open_in = new_open_in.clone();
+                                                        move || new_open_in.set(t)
+                                                    },
+                                                )
+                                            })
+                                            .collect::<Vec<_>>(),
+                                    ),
                                     Button(Text("Add Bookmark").color(theme().on_primary), {
                                         let bookmarks = bookmarks.clone();
                                         let new_title = new_title.clone();
                                         let new_url = new_url.clone();
+                                        let new_group = new_group.clone();
+                                        let new_open_in = new_open_in.clone();
+                                        let settings = settings.clone();
                                         let snackbar = snackbar.clone();
                                         let form_epoch = form_epoch.clone();
                                         let show_form = show_add_form.clone();
@@ -467,105 +3674,55 @@ pub fn app(s: &mut Scheduler) -> View {
                                         move || {
                                             let title = new_title.get().trim().to_string();
                                             let url_raw = new_url.get().trim().to_string();
+                                            let group = new_group.get().trim().to_string();
 
-                                            if title.is_empty() || url_raw.is_empty() {
-                                                let sb = snackbar.clone();
-                                                sb.show(SnackbarRequest {
-                                                    message: "Title and URL are required"
-                                                        .to_string(),
-                                                    action: None,
-                                                    duration_ms: 4000,
-                                                    builder: Rc::new({
-                                                        let sb = sb.clone();
-                                                        move || {
-                                                            material3::Snackbar(
-                                                                "Title and URL are required",
-                                                                Some(SnackbarAction {
-                                                                    label: "Dismiss".to_string(),
-                                                                    on_click: Rc::new({
-                                                                        let sb = sb.clone();
-                                                                        move || sb.dismiss()
-                                                                    }),
-                                                                }),
-                                                                Modifier::new().absolute().offset(
-                                                                    Some(16.0),
-                                                                    None,
-                                                                    Some(16.0),
-                                                                    None,
-                                                                ),
-                                                            )
-                                                        }
-                                                    }),
-                                                });
+                                            if url_raw.is_empty() {
+                                                notify(&snackbar, "A URL is required");
                                                 return;
                                             }
 
-                                            let Some(url) = normalize_url(&url_raw) else {
-                                                let sb = snackbar.clone();
-                                                sb.show(SnackbarRequest {
-                                                    message: "Invalid URL format".to_string(),
-                                                    action: None,
-                                                    duration_ms: 4000,
-                                                    builder: Rc::new({
-                                                        let sb = sb.clone();
-                                                        move || {
-                                                            material3::Snackbar(
-                                                                "Invalid URL format",
-                                                                Some(SnackbarAction {
-                                                                    label: "Dismiss".to_string(),
-                                                                    on_click: Rc::new({
-                                                                        let sb = sb.clone();
-                                                                        move || sb.dismiss()
-                                                                    }),
-                                                                }),
-                                                                Modifier::new().absolute().offset(
-                                                                    Some(16.0),
-                                                                    None,
-                                                                    Some(16.0),
-                                                                    None,
-                                                                ),
-                                                            )
-                                                        }
-                                                    }),
-                                                });
+                                            let Some(url) =
+                                                normalize_url(&settings.get(), &url_raw)
+                                            else {
+                                                notify(&snackbar, "Invalid URL format");
                                                 return;
                                             };
+                                            if let Err(e) = settings.get().url_policy().check(&url)
+                                            {
+                                                notify(&snackbar, &format!("Can't add {url}: {e}"));
+                                                return;
+                                            }
 
-                                            bookmarks.update(|v| v.push(Bookmark { title, url }));
+                                            // Without a title, or with the one made
+                                            // from a pasted URL, the page gets asked.
+                                            let derived = paste::title_for_url(&url);
+                                            let look_up = title.is_empty() || title == derived;
+                                            let title =
+                                                if title.is_empty() { derived } else { title };
+                                            bookmarks.update(|v| {
+                                                v.push(Bookmark {
+                                                    title: title.clone(),
+                                                    url: url.clone(),
+                                                    group,
+                                                    health: None,
+                                                    open_in: new_open_in.get(),
+                                                    tags: Vec::new(),
+                                                    pinned: false,
+                                                })
+                                            });
                                             storage::save_bookmarks(&bookmarks.get());
+                                            if look_up {
+                                                look_up_metadata(&bookmarks, &url, &title);
+                                            }
 
                                             new_title.set(String::new());
                                             new_url.set(String::new());
+                                            new_group.set(String::new());
+                                            new_open_in.set(None);
                                             form_epoch.update(|e| *e = e.wrapping_add(1));
                                             show_form.set(false);
 
-                                            let sb = snackbar.clone();
-                                            sb.show(SnackbarRequest {
-                                                message: "Bookmark added".to_string(),
-                                                action: None,
-                                                duration_ms: 3000,
-                                                builder: Rc::new({
-                                                    let sb = sb.clone();
-                                                    move || {
-                                                        material3::Snackbar(
-                                                            "Bookmark added",
-                                                            Some(SnackbarAction {
-                                                                label: "Dismiss".to_string(),
-                                                                on_click: Rc::new({
-                                                                    let sb = sb.clone();
-                                                                    move || sb.dismiss()
-                                                                }),
-                                                            }),
-                                                            Modifier::new().absolute().offset(
-                                                                Some(16.0),
-                                                                None,
-                                                                Some(16.0),
-                                                                None,
-                                                            ),
-                                                        )
-                                                    }
-                                                }),
-                                            });
+                                            notify(&snackbar, "Bookmark added");
                                         }
                                     })
                                     .modifier(
@@ -580,34 +3737,164 @@ pub fn app(s: &mut Scheduler) -> View {
                                 )),
                             )
                         } else {
-                            // Collapsed - just the + button
-                            Button(
-                                Text("+ Add bookmark")
-                                    .size(14.0)
-                                    .color(Color::from_hex("#6B7280")),
-                                {
+                            // Collapsed - add button plus data actions
+                            Row(Modifier::new()).child((
+                                GhostButton("+ Add bookmark", {
                                     let show = show_add_form.clone();
                                     move || show.set(true)
-                                },
-                            )
-                            .modifier(
-                                Modifier::new()
-                                    .padding_values(PaddingValues {
-                                        left: 16.0,
-                                        right: 16.0,
-                                        top: 10.0,
-                                        bottom: 10.0,
-                                    })
-                                    .background(Color(0, 0, 0, 0))
-                                    .clip_rounded(8.0)
-                                    .border(1.0, theme().outline, 8.0),
-                            )
+                                }),
+                                Box(Modifier::new().width(8.0).height(1.0)),
+                                GhostButton("Export", {
+                                    let snackbar = snackbar.clone();
+                                    let passphrase = export_passphrase.clone();
+                                    move || export_to_file(&snackbar, &passphrase.get())
+                                }),
+                                Box(Modifier::new().width(8.0).height(1.0)),
+                                GhostButton("Import", {
+                                    let show = show_import.clone();
+                                    move || show.update(|v| *v = !*v)
+                                }),
+                                Box(Modifier::new().width(8.0).height(1.0)),
+                                Row(Modifier::new()).child((
+                                    GhostButton("Health checks", {
+                                        let show = show_health.clone();
+                                        move || show.update(|v| *v = !*v)
+                                    }),
+                                    Box(Modifier::new().width(8.0).height(1.0)),
+                                    GhostButton("Check links", {
+                                        let show = show_link_check.clone();
+                                        move || show.update(|v| *v = !*v)
+                                    }),
+                                    Box(Modifier::new().width(8.0).height(1.0)),
+                                    GhostButton("Select", {
+                                        let selection = selection.clone();
+                                        move || selection.set(Some(Selection::default()))
+                                    }),
+                                )),
+                            ))
                         }),
+                        if show_import.get() {
+                            Box(Modifier::new()
+                                .fill_max_width()
+                                .padding_values(PaddingValues {
+                                    top: 16.0,
+                                    ..Default::default()
+                                }))
+                            .child(ImportPanel(
+                                import_text.clone(),
+                                import_decrypted.clone(),
+                                show_import.clone(),
+                                cfg.url_policy(),
+                            ))
+                        } else {
+                            Box(Modifier::new())
+                        },
+                        Column(Modifier::new().fill_max_width()).child((
+                            if show_health.get() {
+                                Box(Modifier::new().fill_max_width().padding_values(
+                                    PaddingValues {
+                                        top: 16.0,
+                                        ..Default::default()
+                                    },
+                                ))
+                                .child(HealthPanel(
+                                    bookmarks.clone(),
+                                    health.clone(),
+                                    show_health.clone(),
+                                ))
+                            } else {
+                                Box(Modifier::new())
+                            },
+                            if show_link_check.get() {
+                                Box(Modifier::new().fill_max_width().padding_values(
+                                    PaddingValues {
+                                        top: 16.0,
+                                        ..Default::default()
+                                    },
+                                ))
+                                .child(LinkCheckPanel(
+                                    bookmarks.clone(),
+                                    link_checker.clone(),
+                                    show_link_check.clone(),
+                                    snackbar.clone(),
+                                ))
+                            } else {
+                                Box(Modifier::new())
+                            },
+                        )),
+                        // Page widgets (notes, …)
+                        widgets::PageWidgets(&page_widgets, &widget_layout.get()),
                     )),
                 ),
             ),
         ),
     );
 
-    overlay.host(Modifier::new().fill_max_size(), content)
+    let page = Stack(Modifier::new().fill_max_size()).child((
+        content,
+        if show_settings.get() {
+            SettingsDrawer(
+                DrawerCtx {
+                    settings: settings.clone(),
+                    show: show_settings.clone(),
+                    confirm_reset: confirm_reset.clone(),
+                    show_import: show_import.clone(),
+                    show_health: show_health.clone(),
+                    search_url_error: search_url_error.clone(),
+                    snackbar: snackbar.clone(),
+                    bookmark_count: bookmarks.get().len(),
+                    dashboards: dashboard_list.clone(),
+                    active_dashboard: active_dashboard.clone(),
+                    confirm_delete_dashboard: confirm_delete_dashboard.clone(),
+                    syncer: syncer.clone(),
+                    export_passphrase: export_passphrase.clone(),
+                },
+                &page_widgets,
+                widget_layout.clone(),
+                dp_w.min(420.0),
+            )
+        } else {
+            Box(Modifier::new())
+        },
+        match confirm_reset.get() {
+            Some(kind) => ResetDialog(
+                kind,
+                confirm_reset.clone(),
+                settings.clone(),
+                snackbar.clone(),
+            ),
+            None => Box(Modifier::new()),
+        },
+        match confirm_delete_dashboard.get() {
+            Some(id) => DeleteDashboardDialog(
+                id,
+                confirm_delete_dashboard.clone(),
+                dashboard_list.clone(),
+                active_dashboard.clone(),
+            ),
+            None => Box(Modifier::new()),
+        },
+        match editing.get() {
+            Some(state) => EditBookmarkDialog(
+                state,
+                editing.clone(),
+                bookmarks.clone(),
+                cfg.clone(),
+                snackbar.clone(),
+            ),
+            None => Box(Modifier::new()),
+        },
+        match batch.get() {
+            Some(links) => {
+                PastedLinksDialog(links, batch.clone(), bookmarks.clone(), snackbar.clone())
+            }
+            None => Box(Modifier::new()),
+        },
+        match syncer.conflict.get() {
+            Some(conflict) => SyncConflictDialog(&conflict, (*syncer).clone()),
+            None => Box(Modifier::new()),
+        },
+    ));
+
+    overlay.host(Modifier::new().fill_max_size(), page)
 }
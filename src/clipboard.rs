@@ -0,0 +1,155 @@
+//! Copying text with the async Clipboard API.
+//!
+//! Browsers refuse the API on insecure origins and when permission is denied.
+//! The canvas can't show selectable text, so the fallback is a small DOM
+//! dialog with the text selected in a textarea, ready for Ctrl/⌘+C.
+
+use std::cell::RefCell;
+
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+const DIALOG_ID: &str = "startpage-copy-fallback";
+
+type Listener = Closure<dyn Fn(web_sys::Event)>;
+
+thread_local! {
+    /// Listeners of the open dialog; dropped when it closes.
+    static LISTENERS: RefCell<Vec<Listener>> = RefCell::default();
+}
+
+const DIALOG_STYLE: &str = "position:fixed;left:50%;top:30%;transform:translateX(-50%);\
+     z-index:10;width:min(480px,90vw);padding:16px;border-radius:12px;\
+     background:#111827;color:#E5E7EB;border:1px solid #374151;\
+     font:14px system-ui,sans-serif;box-shadow:0 10px 30px #0008";
+const TEXT_STYLE: &str = "box-sizing:border-box;width:100%;height:72px;margin:8px 0;\
+     padding:8px;border-radius:8px;border:1px solid #374151;background:#0F172A;\
+     color:#E5E7EB;font:13px ui-monospace,monospace;resize:none";
+const BUTTON_STYLE: &str = "float:right;padding:6px 14px;border-radius:8px;border:0;\
+     background:#2563EB;color:#fff;font:inherit;cursor:pointer";
+
+/// How `copy` got the text to the user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Copied {
+    /// It's on the clipboard.
+    Clipboard,
+    /// The fallback dialog is showing it, selected.
+    Dialog,
+}
+
+/// `[title](url)`, with brackets in the title and spaces or parentheses in
+/// the URL escaped so the link survives Markdown renderers.
+pub fn markdown_link(title: &str, url: &str) -> String {
+    let mut text = String::with_capacity(title.len());
+    for c in title.trim().chars() {
+        if matches!(c, '[' | ']' | '\\') {
+            text.push('\\');
+        }
+        text.push(c);
+    }
+    let url = url
+        .trim()
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29");
+    format!("[{text}]({url})")
+}
+
+/// Copy `text` to the clipboard, or show it in the fallback dialog when the
+/// browser won't allow that. `done` says which happened.
+pub fn copy(text: &str, done: impl FnOnce(Copied) + 'static) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let navigator = window.navigator();
+    // `navigator.clipboard` is missing entirely on insecure origins.
+    let available = js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard"))
+        .is_ok_and(|c| !c.is_undefined());
+    if !available {
+        show_dialog(text);
+        done(Copied::Dialog);
+        return;
+    }
+
+    let written = navigator.clipboard().write_text(text);
+    let text = text.to_string();
+    wasm_bindgen_futures::spawn_local(async move {
+        match JsFuture::from(written).await {
+            Ok(_) => done(Copied::Clipboard),
+            Err(e) => {
+                log::warn!("clipboard: {e:?}");
+                show_dialog(&text);
+                done(Copied::Dialog);
+            }
+        }
+    });
+}
+
+fn close_dialog() {
+    if let Some(dialog) = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(DIALOG_ID))
+    {
+        dialog.remove();
+    }
+    // Dropped after the handler that closed the dialog returns.
+    drop(LISTENERS.take());
+}
+
+fn show_dialog(text: &str) {
+    close_dialog();
+    if let Err(e) = build_dialog(text) {
+        log::warn!("copy dialog: {e:?}");
+    }
+}
+
+fn build_dialog(text: &str) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+    let body = document
+        .body()
+        .ok_or_else(|| JsValue::from_str("no body"))?;
+
+    let dialog = document.create_element("div")?;
+    dialog.set_id(DIALOG_ID);
+    dialog.set_attribute("role", "dialog")?;
+    dialog.set_attribute("style", DIALOG_STYLE)?;
+
+    let label = document.create_element("div")?;
+    label.set_text_content(Some(
+        "The browser didn't allow copying. Press Ctrl+C (⌘C on a Mac) to copy:",
+    ));
+    let area: web_sys::HtmlTextAreaElement = document.create_element("textarea")?.dyn_into()?;
+    area.set_value(text);
+    area.set_read_only(true);
+    area.set_attribute("style", TEXT_STYLE)?;
+    let close = document.create_element("button")?;
+    close.set_text_content(Some("Close"));
+    close.set_attribute("style", BUTTON_STYLE)?;
+
+    dialog.append_child(&label)?;
+    dialog.append_child(&area)?;
+    dialog.append_child(&close)?;
+    body.append_child(&dialog)?;
+    area.focus()?;
+    area.select();
+
+    let on_close = Listener::new(|_| close_dialog());
+    close.add_event_listener_with_callback("click", on_close.as_ref().unchecked_ref())?;
+
+    let on_key = Listener::new(|ev: web_sys::Event| {
+        if ev
+            .dyn_ref::<web_sys::KeyboardEvent>()
+            .is_some_and(|key| key.key() == "Escape")
+        {
+            close_dialog();
+        }
+        // Keys typed here (Ctrl+C above all) are not for the canvas.
+        ev.stop_propagation();
+    });
+    dialog.add_event_listener_with_callback("keydown", on_key.as_ref().unchecked_ref())?;
+    LISTENERS.set(vec![on_close, on_key]);
+    Ok(())
+}
@@ -0,0 +1,94 @@
+//! Central command registry.
+//!
+//! Every feature adds its actions here: `app::app` registers the bookmark,
+//! search, appearance and data commands inline, and
+//! `widgets::register_commands` adds each widget's `Widget::commands`. The
+//! command palette lists and searches whatever was registered for the
+//! current frame.
+
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub struct Command {
+    /// Grouping shown next to the title, e.g. "Search" or "Widgets".
+    pub section: &'static str,
+    pub title: String,
+    pub run: Rc<dyn Fn()>,
+}
+
+#[derive(Clone, Default)]
+pub struct CommandRegistry {
+    commands: Vec<Command>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(
+        &mut self,
+        section: &'static str,
+        title: impl Into<String>,
+        run: impl Fn() + 'static,
+    ) {
+        self.commands.push(Command {
+            section,
+            title: title.into(),
+            run: Rc::new(run),
+        });
+    }
+
+    /// Commands matching `query`, best first. An empty query lists everything
+    /// in registration order.
+    pub fn search(&self, query: &str) -> Vec<Command> {
+        let query = query.trim();
+        if query.is_empty() {
+            return self.commands.clone();
+        }
+        let mut hits: Vec<(i32, usize)> = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| {
+                let title = fuzzy_score(query, &c.title);
+                let full = fuzzy_score(query, &format!("{} {}", c.section, c.title));
+                // Matching the title alone ranks above needing the section name.
+                title.map(|s| s + 8).max(full).map(|s| (s, i))
+            })
+            .collect();
+        hits.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        hits.into_iter()
+            .map(|(_, i)| self.commands[i].clone())
+            .collect()
+    }
+}
+
+/// Subsequence match, case-insensitive. Consecutive letters and letters at
+/// word starts score higher; gaps cost a little. `None` when some query
+/// character can't be matched in order.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let cand: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev: Option<usize> = None;
+
+    for q in query.chars().flat_map(char::to_lowercase) {
+        if q.is_whitespace() {
+            continue;
+        }
+        let found = (pos..cand.len()).find(|&i| cand[i] == q)?;
+        let word_start = found == 0 || !cand[found - 1].is_alphanumeric();
+        score += match prev {
+            Some(p) if p + 1 == found => 6,
+            _ if word_start => 4,
+            _ => 1,
+        };
+        if let Some(p) = prev {
+            score -= ((found - p - 1) as i32).min(3);
+        }
+        prev = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
@@ -0,0 +1,414 @@
+//! Right-click menu on bookmark tiles.
+//!
+//! Shown through the app's `OverlayHandle`, like the command palette, and owns
+//! the keyboard while open. The runner doesn't report right clicks, so a
+//! `contextmenu` listener hit-tests the tile rects recorded by `links`; touch
+//! browsers send the same event for a long press. The Menu key and Shift+F10
+//! open the menu for the tile under the mouse, or the first tile.
+
+#![allow(non_snake_case)]
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use repose_core::{CursorIcon, PaddingValues, prelude::*};
+use repose_ui::overlay::OverlayHandle;
+use repose_ui::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::closure::Closure;
+
+use crate::links::{self, LinkTarget};
+use crate::storage::Bookmark;
+
+const WIDTH: f32 = 220.0;
+const ROW_HEIGHT: f32 = 34.0;
+
+/// What the app should do with the bookmark the menu was opened for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MenuAction {
+    Open(LinkTarget),
+    Edit,
+    /// Add a copy under another URL, chosen in the edit dialog.
+    Duplicate,
+    CopyLink,
+    CopyMarkdown,
+    MoveTo(String),
+    TogglePin,
+    Delete,
+}
+
+#[derive(Clone)]
+enum Item {
+    Run(MenuAction),
+    /// Switch to the list of groups.
+    ChooseGroup,
+}
+
+#[derive(Clone)]
+struct Target {
+    bookmark: Bookmark,
+    /// Where the menu opens, in px.
+    x: f32,
+    y: f32,
+}
+
+type Handler = Rc<dyn Fn(&Bookmark, MenuAction)>;
+
+pub struct BookmarkMenu {
+    overlay: OverlayHandle,
+    bookmarks: RefCell<Vec<Bookmark>>,
+    handler: RefCell<Option<Handler>>,
+    target: Signal<Option<Target>>,
+    selected: Signal<usize>,
+    /// Showing groups to move to rather than the actions.
+    choosing_group: Signal<bool>,
+    /// New group name typed while choosing a group.
+    typed: Signal<String>,
+    open_id: Cell<Option<u64>>,
+}
+
+impl BookmarkMenu {
+    pub fn new(overlay: OverlayHandle) -> Self {
+        Self {
+            overlay,
+            bookmarks: RefCell::new(Vec::new()),
+            handler: RefCell::new(None),
+            target: signal(None),
+            selected: signal(0),
+            choosing_group: signal(false),
+            typed: signal(String::new()),
+            open_id: Cell::new(None),
+        }
+    }
+
+    /// The bookmarks tiles can be opened for and what runs the chosen action;
+    /// set once per frame.
+    pub fn set_context(
+        &self,
+        bookmarks: Vec<Bookmark>,
+        handler: impl Fn(&Bookmark, MenuAction) + 'static,
+    ) {
+        *self.bookmarks.borrow_mut() = bookmarks;
+        *self.handler.borrow_mut() = Some(Rc::new(handler));
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open_id.get().is_some()
+    }
+
+    /// Open the menu for the bookmark with `url` at `x, y` (px).
+    fn open(self: &Rc<Self>, url: &str, x: f32, y: f32) {
+        let Some(bookmark) = self
+            .bookmarks
+            .borrow()
+            .iter()
+            .find(|b| b.url == url)
+            .cloned()
+        else {
+            return;
+        };
+        self.close();
+        self.target.set(Some(Target { bookmark, x, y }));
+        self.selected.set(0);
+        self.choosing_group.set(false);
+        self.typed.set(String::new());
+        let this = self.clone();
+        let id = self.overlay.show_builder(Rc::new(move || this.view()));
+        self.open_id.set(Some(id));
+    }
+
+    pub fn close(&self) {
+        if let Some(id) = self.open_id.take() {
+            self.overlay.dismiss(id);
+        }
+    }
+
+    fn items(&self, bm: &Bookmark) -> Vec<(String, Item)> {
+        if !self.choosing_group.get() {
+            return vec![
+                (
+                    "Open in new tab".into(),
+                    Item::Run(MenuAction::Open(LinkTarget::NewTab)),
+                ),
+                (
+                    "Open in same tab".into(),
+                    Item::Run(MenuAction::Open(LinkTarget::SameTab)),
+                ),
+                ("Edit…".into(), Item::Run(MenuAction::Edit)),
+                ("Duplicate…".into(), Item::Run(MenuAction::Duplicate)),
+                ("Copy link".into(), Item::Run(MenuAction::CopyLink)),
+                (
+                    "Copy as Markdown".into(),
+                    Item::Run(MenuAction::CopyMarkdown),
+                ),
+                ("Move to group…".into(), Item::ChooseGroup),
+                (
+                    if bm.pinned { "Unpin" } else { "Pin to top" }.into(),
+                    Item::Run(MenuAction::TogglePin),
+                ),
+                ("Delete".into(), Item::Run(MenuAction::Delete)),
+            ];
+        }
+
+        let typed = self.typed.get().trim().to_string();
+        let mut items = Vec::new();
+        let mut groups: Vec<String> = Vec::new();
+        for b in self.bookmarks.borrow().iter() {
+            if b.group != bm.group && !groups.contains(&b.group) {
+                groups.push(b.group.clone());
+            }
+        }
+        if !typed.is_empty() && !groups.contains(&typed) && typed != bm.group {
+            items.push((
+                format!("New group “{typed}”"),
+                Item::Run(MenuAction::MoveTo(typed.clone())),
+            ));
+        }
+        for g in groups {
+            let label = if g.is_empty() {
+                "Bookmarks".to_string()
+            } else {
+                g.clone()
+            };
+            items.push((label, Item::Run(MenuAction::MoveTo(g))));
+        }
+        items
+    }
+
+    fn choose(&self, item: Item) {
+        match item {
+            Item::ChooseGroup => {
+                self.choosing_group.set(true);
+                self.selected.set(0);
+            }
+            Item::Run(action) => {
+                let target = self.target.get();
+                let handler = self.handler.borrow().clone();
+                self.close();
+                if let (Some(t), Some(handler)) = (target, handler) {
+                    handler(&t.bookmark, action);
+                }
+            }
+        }
+    }
+
+    /// Handle a key press; returns whether the menu consumed it.
+    fn on_key(self: &Rc<Self>, ev: &web_sys::KeyboardEvent) -> bool {
+        let key = ev.key();
+        if !self.is_open() {
+            if key == "ContextMenu" || (ev.shift_key() && key == "F10") {
+                return self.open_from_keyboard();
+            }
+            return false;
+        }
+        let Some(target) = self.target.get() else {
+            return false;
+        };
+        let items = self.items(&target.bookmark);
+        let chord = ev.ctrl_key() || ev.meta_key() || ev.alt_key();
+
+        match key.as_str() {
+            "Escape" if self.choosing_group.get() => {
+                self.choosing_group.set(false);
+                self.typed.set(String::new());
+                self.selected.set(0);
+            }
+            "Escape" | "ContextMenu" => self.close(),
+            "Enter" => {
+                if let Some((_, item)) = items.get(self.selected.get()) {
+                    self.choose(item.clone());
+                }
+            }
+            "ArrowDown" | "ArrowUp" => {
+                let n = items.len().max(1);
+                let step = if key == "ArrowDown" { 1 } else { n - 1 };
+                self.selected.update(|s| *s = (*s + step) % n);
+            }
+            "Backspace" if self.choosing_group.get() => {
+                self.typed.update(|t| {
+                    t.pop();
+                });
+                self.selected.set(0);
+            }
+            _ if self.choosing_group.get() && !chord && key.chars().count() == 1 => {
+                self.typed.update(|t| t.push_str(&key));
+                self.selected.set(0);
+            }
+            _ if chord => return false,
+            _ => {}
+        }
+        true
+    }
+
+    fn open_from_keyboard(self: &Rc<Self>) -> bool {
+        let hovered =
+            links::pointer().and_then(|(x, y)| links::tile_at(x, y).map(|tile| (tile, (x, y))));
+        let (url, x, y) = match hovered {
+            Some(((_, url), (x, y))) => (url, x, y),
+            None => match links::first_tile() {
+                Some((rect, url)) => (url, rect.x, rect.y + rect.h),
+                None => return false,
+            },
+        };
+        self.open(&url, x, y);
+        self.is_open()
+    }
+
+    fn view(self: &Rc<Self>) -> View {
+        let Some(target) = self.target.get() else {
+            return Box(Modifier::new());
+        };
+        let items = self.items(&target.bookmark);
+        let selected = self.selected.get().min(items.len().saturating_sub(1));
+        let choosing = self.choosing_group.get();
+
+        // Events report px; the overlay is laid out in dp.
+        let scale = repose_core::locals::density().scale * repose_core::locals::ui_scale().0;
+        let scale = if scale > 0.0 { scale } else { 1.0 };
+        let (vw, vh) = web_sys::window()
+            .map(|w| {
+                let ratio = w.device_pixel_ratio() as f32;
+                let size = |v: Result<wasm_bindgen::JsValue, _>| {
+                    v.ok().and_then(|v| v.as_f64()).unwrap_or(0.0) as f32 * ratio / scale
+                };
+                (size(w.inner_width()), size(w.inner_height()))
+            })
+            .unwrap_or((0.0, 0.0));
+        let rows = items.len().max(1) + usize::from(choosing);
+        let height = rows as f32 * ROW_HEIGHT + 12.0;
+        let x = (target.x / scale).min(vw - WIDTH - 8.0).max(8.0);
+        let y = (target.y / scale).min(vh - height - 8.0).max(8.0);
+
+        let mut rows: Vec<View> = Vec::new();
+        if choosing {
+            let typed = self.typed.get();
+            rows.push(
+                Text(if typed.is_empty() {
+                    "Move to… (type a new group)".to_string()
+                } else {
+                    format!("{typed}▏")
+                })
+                .size(12.0)
+                .single_line()
+                .color(Color::from_hex("#6B7280"))
+                .modifier(Modifier::new().fill_max_width().padding(10.0)),
+            );
+        }
+        if items.is_empty() {
+            rows.push(
+                Text("No other groups")
+                    .size(13.0)
+                    .color(Color::from_hex("#6B7280"))
+                    .modifier(Modifier::new().padding(10.0)),
+            );
+        }
+        rows.extend(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(i, (label, item))| MenuRow(&label, item, i == selected, self.clone())),
+        );
+
+        Stack(Modifier::new().fill_max_size()).child((
+            Box(Modifier::new()
+                .fill_max_size()
+                .clickable()
+                .on_pointer_down({
+                    let this = self.clone();
+                    move |_| this.close()
+                })),
+            Box(Modifier::new()
+                .absolute()
+                .offset(Some(x), Some(y), None, None)
+                .width(WIDTH)
+                .background(theme().surface)
+                .border(1.0, theme().outline, 10.0)
+                .clip_rounded(10.0)
+                .padding(6.0)
+                // Swallow clicks so they don't reach the scrim.
+                .clickable())
+            .child(Column(Modifier::new().fill_max_width()).child(rows)),
+        ))
+    }
+}
+
+fn MenuRow(label: &str, item: Item, selected: bool, menu: Rc<BookmarkMenu>) -> View {
+    let p = theme().primary;
+    let destructive = matches!(item, Item::Run(MenuAction::Delete));
+    Row(Modifier::new()
+        .fill_max_width()
+        .height(ROW_HEIGHT)
+        .align_items(AlignItems::Center)
+        .padding_values(PaddingValues {
+            left: 10.0,
+            right: 10.0,
+            ..Default::default()
+        })
+        .background(if selected {
+            Color(p.0, p.1, p.2, 48)
+        } else {
+            Color(0, 0, 0, 0)
+        })
+        .clip_rounded(6.0)
+        .clickable()
+        .on_pointer_down(move |_| menu.choose(item.clone()))
+        .cursor(CursorIcon::Pointer))
+    .child(
+        Text(label)
+            .size(14.0)
+            .single_line()
+            .overflow_ellipsize()
+            .color(if destructive {
+                Color::from_hex("#EF4444")
+            } else {
+                theme().on_surface
+            }),
+    )
+}
+
+/// Open the menu on right click, long press, the Menu key and Shift+F10, and
+/// route keys to it while open. Call once.
+pub fn install_listeners(menu: Rc<BookmarkMenu>) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+
+    let on_context = Closure::<dyn Fn(web_sys::MouseEvent)>::new({
+        let menu = menu.clone();
+        move |ev: web_sys::MouseEvent| {
+            // The Menu key opened it already; keep the browser's menu away.
+            if menu.is_open() {
+                ev.prevent_default();
+                return;
+            }
+            let scale = web_sys::window().map_or(1.0, |w| w.device_pixel_ratio()) as f32;
+            let (x, y) = (ev.client_x() as f32 * scale, ev.client_y() as f32 * scale);
+            if let Some((_, url)) = links::tile_at(x, y) {
+                ev.prevent_default();
+                menu.open(&url, x, y);
+            }
+        }
+    });
+    let _ = window.add_event_listener_with_callback_and_bool(
+        "contextmenu",
+        on_context.as_ref().unchecked_ref(),
+        true,
+    );
+    // Lives as long as the page.
+    on_context.forget();
+
+    let on_key =
+        Closure::<dyn Fn(web_sys::KeyboardEvent)>::new(move |ev: web_sys::KeyboardEvent| {
+            if menu.on_key(&ev) {
+                ev.prevent_default();
+                ev.stop_propagation();
+            }
+        });
+    // Capture phase, so this runs before the canvas sees the key.
+    let _ = window.add_event_listener_with_callback_and_bool(
+        "keydown",
+        on_key.as_ref().unchecked_ref(),
+        true,
+    );
+    on_key.forget();
+}
@@ -0,0 +1,472 @@
+//! Passphrase encryption for export files and synced data.
+//!
+//! A key is derived from the passphrase with Argon2id and the data sealed
+//! with XChaCha20-Poly1305, both in pure Rust so they run in wasm. Sealed
+//! data travels as an `Envelope`, a JSON object that names its format
+//! version and key derivation settings, so later versions can change either
+//! and still read what older ones wrote.
+//!
+//! Encryption protects what leaves the browser, not what is in it. The sync
+//! passphrase is kept in local storage with the server's token, so passes
+//! run without asking for it; the export passphrase lasts until the page is
+//! closed. Both are entered in `secret`'s masked dialog and never shown.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::prelude::*;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+const FORMAT: &str = "startpage-encrypted";
+
+/// Envelope version written; older ones stay readable.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CryptoError {
+    /// Written by a newer version of the app.
+    UnsupportedVersion(u32),
+    UnsupportedKdf(String),
+    /// The passphrase is not the one the data was sealed with, or the data
+    /// was altered; the cipher can't tell which.
+    WrongPassphrase,
+    /// Not an envelope, or a damaged one.
+    Malformed(String),
+    /// The browser gave no random numbers for a salt or nonce.
+    NoRandom,
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::UnsupportedVersion(v) => write!(
+                f,
+                "encrypted with a newer version of Startpage (format {v}); update to read it"
+            ),
+            CryptoError::UnsupportedKdf(name) => {
+                write!(f, "encrypted with an unknown key derivation ({name})")
+            }
+            CryptoError::WrongPassphrase => {
+                write!(f, "wrong passphrase, or the encrypted data is damaged")
+            }
+            CryptoError::Malformed(why) => write!(f, "damaged encrypted data: {why}"),
+            CryptoError::NoRandom => write!(f, "the browser has no secure random numbers"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+/// Argon2id settings, stored in every envelope.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Kdf {
+    pub name: String,
+    /// Memory in KiB.
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for Kdf {
+    /// OWASP's minimum for Argon2id: 19 MiB, two passes.
+    fn default() -> Self {
+        Self {
+            name: "argon2id".to_string(),
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+impl Kdf {
+    fn params(&self) -> Result<Params, CryptoError> {
+        if self.name != "argon2id" {
+            return Err(CryptoError::UnsupportedKdf(self.name.clone()));
+        }
+        // An envelope must not make the page allocate or spin without bound.
+        if self.m_cost > 256 * 1024 || self.t_cost > 16 || self.p_cost > 4 {
+            return Err(CryptoError::Malformed(
+                "key derivation settings out of range".to_string(),
+            ));
+        }
+        Params::new(self.m_cost, self.t_cost, self.p_cost, Some(32))
+            .map_err(|e| CryptoError::Malformed(e.to_string()))
+    }
+}
+
+/// Sealed data. Binary fields are base64.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope {
+    /// Always `startpage-encrypted`.
+    pub format: String,
+    pub version: u32,
+    pub kdf: Kdf,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl Envelope {
+    /// Read an envelope, refusing versions and settings this build can't open.
+    pub fn from_value(value: Value) -> Result<Self, CryptoError> {
+        if value.get("format").and_then(Value::as_str) != Some(FORMAT) {
+            return Err(CryptoError::Malformed("not encrypted data".to_string()));
+        }
+        match value.get("version").and_then(Value::as_u64) {
+            Some(v) if v > ENVELOPE_VERSION as u64 => {
+                return Err(CryptoError::UnsupportedVersion(v as u32));
+            }
+            Some(_) => {}
+            None => return Err(CryptoError::Malformed("no version".to_string())),
+        }
+        let envelope: Envelope =
+            serde_json::from_value(value).map_err(|e| CryptoError::Malformed(e.to_string()))?;
+        envelope.kdf.params()?;
+        Ok(envelope)
+    }
+
+    /// Everything but the ciphertext, authenticated along with it.
+    fn header(&self) -> String {
+        let k = &self.kdf;
+        format!(
+            "{}/{}/{}/{}/{}/{}/{}",
+            self.format, self.version, k.name, k.m_cost, k.t_cost, k.p_cost, self.salt
+        )
+    }
+}
+
+/// Whether `text` is an envelope rather than plain data.
+pub fn is_encrypted(text: &str) -> bool {
+    serde_json::from_str::<Value>(text)
+        .is_ok_and(|v| v.get("format").and_then(Value::as_str) == Some(FORMAT))
+}
+
+/// A key derived from a passphrase, with the salt and settings it came from.
+pub struct Key {
+    cipher: XChaCha20Poly1305,
+    /// Keys `id_hash`; separate from the cipher key.
+    ids: [u8; 32],
+    salt: Vec<u8>,
+    kdf: Kdf,
+}
+
+impl Key {
+    pub fn derive(passphrase: &str, salt: &[u8], kdf: &Kdf) -> Result<Self, CryptoError> {
+        let mut master = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, kdf.params()?)
+            .hash_password_into(passphrase.as_bytes(), salt, &mut master)
+            .map_err(|e| CryptoError::Malformed(e.to_string()))?;
+        let cipher_key = subkey(&master, "startpage cipher");
+        Ok(Self {
+            cipher: XChaCha20Poly1305::new(&cipher_key.into()),
+            ids: subkey(&master, "startpage ids"),
+            salt: salt.to_vec(),
+            kdf: kdf.clone(),
+        })
+    }
+
+    pub fn seal(&self, plaintext: &str) -> Result<Envelope, CryptoError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::fill(&mut nonce).map_err(|_| CryptoError::NoRandom)?;
+        let mut envelope = Envelope {
+            format: FORMAT.to_string(),
+            version: ENVELOPE_VERSION,
+            kdf: self.kdf.clone(),
+            salt: BASE64_STANDARD.encode(&self.salt),
+            nonce: BASE64_STANDARD.encode(nonce),
+            ciphertext: String::new(),
+        };
+        let header = envelope.header();
+        let sealed = self
+            .cipher
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: header.as_bytes(),
+                },
+            )
+            .map_err(|_| CryptoError::Malformed("too large to encrypt".to_string()))?;
+        envelope.ciphertext = BASE64_STANDARD.encode(sealed);
+        Ok(envelope)
+    }
+
+    /// Open an envelope sealed with this key's salt and settings.
+    pub fn open(&self, envelope: &Envelope) -> Result<String, CryptoError> {
+        let nonce = decode(&envelope.nonce)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(CryptoError::Malformed("bad nonce".to_string()));
+        }
+        let plain = self
+            .cipher
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &decode(&envelope.ciphertext)?,
+                    aad: envelope.header().as_bytes(),
+                },
+            )
+            .map_err(|_| CryptoError::WrongPassphrase)?;
+        String::from_utf8(plain).map_err(|_| CryptoError::WrongPassphrase)
+    }
+
+    /// Stable keyed hash of `id`, hex; hides names from the server while
+    /// every device with the passphrase computes the same one.
+    pub fn id_hash(&self, id: &str) -> String {
+        hmac(&self.ids, id)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
+fn subkey(master: &[u8; 32], purpose: &str) -> [u8; 32] {
+    hmac(master, purpose)
+}
+
+fn hmac(key: &[u8; 32], message: &str) -> [u8; 32] {
+    // HMAC pads keys with zeros to the block size; doing it here picks the
+    // infallible constructor.
+    let mut block = [0u8; 64];
+    block[..32].copy_from_slice(key);
+    let mut mac = <Hmac<Sha256> as KeyInit>::new(&block.into());
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+fn decode(b64: &str) -> Result<Vec<u8>, CryptoError> {
+    BASE64_STANDARD
+        .decode(b64)
+        .map_err(|e| CryptoError::Malformed(e.to_string()))
+}
+
+/// Salt shared by every device syncing with the same `place` (server or file
+/// URL), so they derive the same key and the same `id_hash`es.
+pub fn shared_salt(place: &str) -> Vec<u8> {
+    let mut hash = Sha256::new();
+    hash.update(b"startpage sync salt\0");
+    hash.update(place.as_bytes());
+    hash.finalize()[..SALT_LEN].to_vec()
+}
+
+/// Derived keys, kept so a passphrase is only stretched once per salt.
+#[derive(Default)]
+pub struct Keyring {
+    keys: RefCell<Vec<(String, Rc<Key>)>>,
+}
+
+impl Keyring {
+    pub fn key(&self, passphrase: &str, salt: &[u8], kdf: &Kdf) -> Result<Rc<Key>, CryptoError> {
+        if let Some((_, key)) = self
+            .keys
+            .borrow()
+            .iter()
+            .find(|(p, k)| p == passphrase && k.salt == salt && k.kdf == *kdf)
+        {
+            return Ok(key.clone());
+        }
+        let key = Rc::new(Key::derive(passphrase, salt, kdf)?);
+        let mut keys = self.keys.borrow_mut();
+        // Old passphrases and other servers' salts are rarely needed again.
+        if keys.len() >= 4 {
+            keys.remove(0);
+        }
+        keys.push((passphrase.to_string(), key.clone()));
+        Ok(key)
+    }
+
+    /// Open `envelope` with the key for its own salt and settings.
+    pub fn open(&self, passphrase: &str, envelope: &Envelope) -> Result<String, CryptoError> {
+        let salt = decode(&envelope.salt)?;
+        self.key(passphrase, &salt, &envelope.kdf)?.open(envelope)
+    }
+}
+
+/// Seal `plaintext` under a fresh salt, as envelope JSON; for export files.
+pub fn encrypt(passphrase: &str, plaintext: &str) -> Result<String, CryptoError> {
+    let mut salt = [0u8; SALT_LEN];
+    getrandom::fill(&mut salt).map_err(|_| CryptoError::NoRandom)?;
+    let envelope = Key::derive(passphrase, &salt, &Kdf::default())?.seal(plaintext)?;
+    serde_json::to_string_pretty(&envelope).map_err(|e| CryptoError::Malformed(e.to_string()))
+}
+
+/// Open envelope JSON made by `encrypt`.
+pub fn decrypt(passphrase: &str, text: &str) -> Result<String, CryptoError> {
+    let value: Value =
+        serde_json::from_str(text).map_err(|e| CryptoError::Malformed(e.to_string()))?;
+    let envelope = Envelope::from_value(value)?;
+    Keyring::default().open(passphrase, &envelope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap settings, so tests don't spend seconds in Argon2.
+    fn quick() -> Kdf {
+        Kdf {
+            m_cost: 64,
+            t_cost: 1,
+            ..Kdf::default()
+        }
+    }
+
+    fn key(passphrase: &str) -> Key {
+        Key::derive(passphrase, &[7; SALT_LEN], &quick()).expect("key")
+    }
+
+    #[test]
+    fn encrypt_then_decrypt_round_trips() {
+        let text = encrypt("correct horse", "{\"version\":1}").expect("encrypt");
+        assert!(is_encrypted(&text));
+        assert!(!text.contains("version\\\":1"));
+        assert_eq!(
+            decrypt("correct horse", &text).expect("decrypt"),
+            "{\"version\":1}"
+        );
+
+        let sealed = key("pw").seal("hello").expect("seal");
+        assert_eq!(key("pw").open(&sealed).as_deref(), Ok("hello"));
+        // A fresh nonce every time.
+        assert_ne!(key("pw").seal("hello").expect("seal"), sealed);
+    }
+
+    #[test]
+    fn wrong_passphrase_is_reported() {
+        let sealed = key("pw").seal("hello").expect("seal");
+        assert_eq!(
+            key("other").open(&sealed),
+            Err(CryptoError::WrongPassphrase)
+        );
+
+        let text = encrypt("correct horse", "hello").expect("encrypt");
+        let err = decrypt("battery staple", &text).unwrap_err();
+        assert_eq!(err, CryptoError::WrongPassphrase);
+        assert_eq!(
+            err.to_string(),
+            "wrong passphrase, or the encrypted data is damaged"
+        );
+    }
+
+    #[test]
+    fn newer_envelopes_are_refused() {
+        let mut value = serde_json::to_value(key("pw").seal("hello").expect("seal")).expect("json");
+        value["version"] = (ENVELOPE_VERSION + 1).into();
+        let err = Envelope::from_value(value.clone()).unwrap_err();
+        assert_eq!(err, CryptoError::UnsupportedVersion(ENVELOPE_VERSION + 1));
+        assert_eq!(
+            decrypt("pw", &value.to_string()).unwrap_err().to_string(),
+            "encrypted with a newer version of Startpage (format 2); update to read it"
+        );
+    }
+
+    #[test]
+    fn tampering_fails_to_open() {
+        let key = key("pw");
+        let sealed = key.seal("hello").expect("seal");
+        let tampered: [fn(&mut Envelope); 5] = [
+            |e| e.kdf.t_cost = 2,
+            |e| e.kdf.m_cost = 128,
+            |e| e.salt = BASE64_STANDARD.encode([8; SALT_LEN]),
+            |e| e.format = "startpage-encrypted-x".to_string(),
+            |e| {
+                let mut bytes = decode(&e.ciphertext).expect("base64");
+                bytes[0] ^= 1;
+                e.ciphertext = BASE64_STANDARD.encode(bytes);
+            },
+        ];
+        for (i, tamper) in tampered.iter().enumerate() {
+            let mut envelope = sealed.clone();
+            tamper(&mut envelope);
+            // Same key, so only the authenticated header or the ciphertext
+            // can make it fail.
+            assert_eq!(
+                key.open(&envelope),
+                Err(CryptoError::WrongPassphrase),
+                "{i}"
+            );
+        }
+
+        let mut short_nonce = sealed.clone();
+        short_nonce.nonce = BASE64_STANDARD.encode([0; 12]);
+        assert!(matches!(
+            key.open(&short_nonce),
+            Err(CryptoError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn kdf_settings_are_checked_before_deriving() {
+        let cases = [
+            (
+                Kdf {
+                    m_cost: 1 << 30,
+                    ..quick()
+                },
+                "out of range",
+            ),
+            (
+                Kdf {
+                    t_cost: 1000,
+                    ..quick()
+                },
+                "out of range",
+            ),
+            (
+                Kdf {
+                    p_cost: 64,
+                    ..quick()
+                },
+                "out of range",
+            ),
+            (
+                Kdf {
+                    name: "scrypt".to_string(),
+                    ..quick()
+                },
+                "unknown key derivation",
+            ),
+        ];
+        let sealed = key("pw").seal("hello").expect("seal");
+        for (kdf, why) in cases {
+            let err = Key::derive("pw", &[7; SALT_LEN], &kdf)
+                .err()
+                .expect("refused");
+            assert!(err.to_string().contains(why), "{kdf:?}: {err}");
+
+            let mut value = serde_json::to_value(&sealed).expect("json");
+            value["kdf"] = serde_json::to_value(&kdf).expect("json");
+            let err = Envelope::from_value(value).unwrap_err();
+            assert!(err.to_string().contains(why), "{kdf:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn plain_exports_are_not_taken_for_envelopes() {
+        let cases = [
+            ("{\"version\":1,\"bookmarks\":[]}", false),
+            ("{\"format\":\"something-else\"}", false),
+            ("not json at all", false),
+            ("", false),
+            ("{\"format\":\"startpage-encrypted\"}", true),
+        ];
+        for (text, encrypted) in cases {
+            assert_eq!(is_encrypted(text), encrypted, "{text}");
+        }
+        assert_eq!(
+            decrypt("pw", "{\"version\":1,\"bookmarks\":[]}"),
+            Err(CryptoError::Malformed("not encrypted data".to_string()))
+        );
+    }
+}
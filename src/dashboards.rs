@@ -0,0 +1,201 @@
+//! Named dashboards.
+//!
+//! Each dashboard has its own bookmarks, tile styles, widget layout and widget
+//! configs. Their storage keys are suffixed with `@<id>` (see
+//! `storage::scoped`); the first dashboard, `home`, keeps the plain keys so data
+//! from before dashboards existed stays where it was.
+
+use std::rc::Rc;
+
+use repose_core::Signal;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use wasm_bindgen::closure::Closure;
+
+use crate::deeplink;
+use crate::storage::{self, ThemeChoice};
+
+const KEY: &str = "startpage.dashboards.v1";
+const ACTIVE_KEY: &str = "startpage.dashboards.active.v1";
+
+pub const HOME: &str = "home";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dashboard {
+    /// Stable slug, used in storage keys and in the URL hash (`#work`).
+    pub id: String,
+    pub name: String,
+    /// Overrides the theme from settings while this dashboard is shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<ThemeChoice>,
+}
+
+impl Dashboard {
+    pub fn home() -> Self {
+        Self {
+            id: HOME.to_string(),
+            name: "Home".to_string(),
+            theme: None,
+        }
+    }
+}
+
+/// The saved dashboards; always contains `home`, first.
+pub fn load() -> Vec<Dashboard> {
+    let mut list: Vec<Dashboard> = storage::load_json(KEY);
+    list.retain(|d| !d.id.is_empty());
+    match list.iter().position(|d| d.id == HOME) {
+        Some(0) => {}
+        Some(i) => {
+            let home = list.remove(i);
+            list.insert(0, home);
+        }
+        None => list.insert(0, Dashboard::home()),
+    }
+    list
+}
+
+pub fn save(list: &[Dashboard]) {
+    storage::save_json(KEY, list)
+}
+
+pub fn load_active() -> String {
+    storage::load_json(ACTIVE_KEY)
+}
+
+pub fn save_active(id: &str) {
+    storage::save_json(ACTIVE_KEY, id)
+}
+
+/// Lowercase ASCII letters, digits and dashes.
+pub fn slug(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.ends_with('-') && !out.is_empty() {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_string()
+}
+
+/// A slug for `name` that no dashboard in `list` uses yet.
+pub fn unique_id(list: &[Dashboard], name: &str) -> String {
+    let base = match slug(name) {
+        s if s.is_empty() => "dashboard".to_string(),
+        s => s,
+    };
+    let mut id = base.clone();
+    let mut n = 2;
+    while list.iter().any(|d| d.id == id) {
+        id = format!("{base}-{n}");
+        n += 1;
+    }
+    id
+}
+
+/// The dashboard named by a URL hash such as `#work` or `#work/News`, if it exists.
+pub fn from_hash(list: &[Dashboard], hash: &str) -> Option<String> {
+    let id = deeplink::parse_hash(hash).dashboard?;
+    list.iter().find(|d| d.id == id).map(|d| d.id.clone())
+}
+
+pub fn current_hash() -> String {
+    web_sys::window()
+        .and_then(|w| w.location().hash().ok())
+        .unwrap_or_default()
+}
+
+/// Dashboard to show on startup: the URL hash wins, then the last one used.
+pub fn initial(list: &[Dashboard]) -> String {
+    from_hash(list, &current_hash())
+        .or_else(|| {
+            let last = load_active();
+            list.iter().any(|d| d.id == last).then_some(last)
+        })
+        .unwrap_or_else(|| HOME.to_string())
+}
+
+/// Show dashboard `id`: remember it for the next visit and put it in the URL hash.
+pub fn select(active: &Signal<String>, id: &str) {
+    if active.get() != id {
+        active.set(id.to_string());
+    }
+    save_active(id);
+    // Keep a `#id/group` hash that already points here.
+    if let Some(w) = web_sys::window()
+        && deeplink::parse_hash(&current_hash()).dashboard.as_deref() != Some(id)
+    {
+        let _ = w.location().set_hash(id);
+    }
+}
+
+/// Add a dashboard called `name` and return its id.
+pub fn add(list: &Signal<Vec<Dashboard>>, name: &str) -> String {
+    let id = unique_id(&list.get(), name);
+    list.update(|l| {
+        l.push(Dashboard {
+            id: id.clone(),
+            name: name.trim().to_string(),
+            theme: None,
+        })
+    });
+    save(&list.get());
+    id
+}
+
+pub fn update(list: &Signal<Vec<Dashboard>>, id: &str, f: impl FnOnce(&mut Dashboard)) {
+    list.update(|l| {
+        if let Some(d) = l.iter_mut().find(|d| d.id == id) {
+            f(d);
+        }
+    });
+    save(&list.get());
+}
+
+/// Delete dashboard `id` and everything stored for it. `home` can't be deleted.
+pub fn remove(list: &Signal<Vec<Dashboard>>, active: &Signal<String>, id: &str) {
+    if id == HOME {
+        return;
+    }
+    list.update(|l| l.retain(|d| d.id != id));
+    save(&list.get());
+    storage::remove_dashboard_data(id);
+    if active.get() == id {
+        select(active, HOME);
+    }
+}
+
+/// Switch dashboards with Alt+1…9. Call once.
+pub fn install_keyboard(list: Rc<Signal<Vec<Dashboard>>>, active: Rc<Signal<String>>) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let on_key =
+        Closure::<dyn Fn(web_sys::KeyboardEvent)>::new(move |ev: web_sys::KeyboardEvent| {
+            if !ev.alt_key() || ev.ctrl_key() || ev.meta_key() || ev.shift_key() {
+                return;
+            }
+            // `code` rather than `key`: with Alt held, macOS reports a symbol as the key.
+            let Some(n) = ev
+                .code()
+                .strip_prefix("Digit")
+                .and_then(|d| d.parse::<usize>().ok())
+            else {
+                return;
+            };
+            if let Some(d) = n.checked_sub(1).and_then(|i| list.get().get(i).cloned()) {
+                select(&active, &d.id);
+                ev.prevent_default();
+                ev.stop_propagation();
+            }
+        });
+    let _ = window.add_event_listener_with_callback_and_bool(
+        "keydown",
+        on_key.as_ref().unchecked_ref(),
+        true,
+    );
+    // Lives as long as the page.
+    on_key.forget();
+}
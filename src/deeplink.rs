@@ -0,0 +1,114 @@
+//! Deep links into app state, read from `window.location`.
+//!
+//! - `?q=term` pre-fills the search box; `&go=1` searches right away, so the
+//!   page works as a browser's custom search URL.
+//! - `?engine=brave` searches with Brave for this page load; the saved default
+//!   engine is left alone.
+//! - `#dashboard` shows a dashboard; `#dashboard/group` also scrolls to a group.
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen::closure::Closure;
+
+use crate::settings::SearchEngine;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeepLink {
+    pub query: Option<String>,
+    /// Search for `query` immediately instead of only pre-filling it.
+    pub go: bool,
+    pub engine: Option<SearchEngine>,
+    pub target: HashTarget,
+}
+
+/// Where the URL hash points.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashTarget {
+    pub dashboard: Option<String>,
+    pub group: Option<String>,
+}
+
+/// Percent-decode a query or hash component; `+` is a space.
+fn decode(raw: &str) -> String {
+    let raw = raw.replace('+', " ");
+    urlencoding::decode(&raw)
+        .map(|s| s.into_owned())
+        .unwrap_or(raw)
+}
+
+/// Parse `location.search` and `location.hash`, with or without their `?`/`#`.
+pub fn parse(search: &str, hash: &str) -> DeepLink {
+    let mut link = DeepLink {
+        target: parse_hash(hash),
+        ..DeepLink::default()
+    };
+    for pair in search.trim_start_matches('?').split('&') {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = decode(value);
+        match name {
+            "q" if !value.trim().is_empty() => link.query = Some(value),
+            "go" => link.go = matches!(value.as_str(), "1" | "true" | ""),
+            "engine" => link.engine = SearchEngine::from_name(&value),
+            _ => {}
+        }
+    }
+    link
+}
+
+pub fn parse_hash(hash: &str) -> HashTarget {
+    let hash = hash.trim_start_matches('#');
+    let (dashboard, group) = match hash.split_once('/') {
+        Some((d, g)) => (d, Some(decode(g))),
+        None => (hash, None),
+    };
+    HashTarget {
+        dashboard: (!dashboard.is_empty()).then(|| decode(dashboard)),
+        group,
+    }
+}
+
+pub fn current() -> DeepLink {
+    let Some(location) = web_sys::window().map(|w| w.location()) else {
+        return DeepLink::default();
+    };
+    parse(
+        &location.search().unwrap_or_default(),
+        &location.hash().unwrap_or_default(),
+    )
+}
+
+/// Absolute URL of the page without query or hash.
+pub fn page_url() -> String {
+    let Some(location) = web_sys::window().map(|w| w.location()) else {
+        return String::new();
+    };
+    format!(
+        "{}{}",
+        location.origin().unwrap_or_default(),
+        location.pathname().unwrap_or_default()
+    )
+}
+
+/// Leave the page for `url` without adding a history entry, so Back skips
+/// the start page that only forwarded the search.
+pub fn forward_to(url: &str) {
+    if let Some(w) = web_sys::window() {
+        let _ = w.location().replace(url);
+    }
+}
+
+/// Call `f` whenever the URL hash changes. Call once.
+pub fn install_hash_listener(f: impl Fn(HashTarget) + 'static) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let listener = Closure::<dyn Fn()>::new(move || {
+        let hash = web_sys::window()
+            .and_then(|w| w.location().hash().ok())
+            .unwrap_or_default();
+        f(parse_hash(&hash))
+    });
+    let _ =
+        window.add_event_listener_with_callback("hashchange", listener.as_ref().unchecked_ref());
+    // Lives as long as the page.
+    listener.forget();
+}
@@ -0,0 +1,414 @@
+//! RSS 2.0 / Atom parsing and feed refresh state.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use repose_core::{Signal, signal};
+use serde::{Deserialize, Serialize};
+
+use crate::net::{HttpClient, HttpRequest};
+use crate::storage;
+
+const CACHE_KEY: &str = "startpage.feeds.cache.v1";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedItem {
+    /// Stable identity for read/unread: guid/id, else link, else title.
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParsedFeed {
+    pub title: String,
+    pub items: Vec<FeedItem>,
+}
+
+/// Parse an RSS 2.0 or Atom document.
+pub fn parse_feed(xml: &str) -> anyhow::Result<ParsedFeed> {
+    // Text isn't trimmed per event: entity references split text into several
+    // events, and trimming each would eat the spaces around them.
+    let mut reader = Reader::from_str(xml);
+
+    let mut feed = ParsedFeed::default();
+    let mut root: Option<String> = None;
+    let mut path: Vec<String> = Vec::new();
+    let mut item: Option<FeedItem> = None;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                let name = local_name(&e);
+                if root.is_none() {
+                    root = Some(name.clone());
+                }
+                if name == "item" || name == "entry" {
+                    item = Some(FeedItem::default());
+                }
+                if name == "link"
+                    && let Some(it) = item.as_mut()
+                    && let Some(href) = atom_link(&e)
+                {
+                    it.link = href;
+                }
+                path.push(name);
+                text.clear();
+            }
+            Event::Empty(e) => {
+                // Atom: <link rel="alternate" href="…"/>
+                if local_name(&e) == "link"
+                    && let Some(it) = item.as_mut()
+                    && let Some(href) = atom_link(&e)
+                {
+                    it.link = href;
+                }
+            }
+            Event::Text(t) => text.push_str(&t.decode()?),
+            Event::CData(t) => text.push_str(&t.decode()?),
+            Event::GeneralRef(r) => {
+                let name = r.decode()?;
+                let entity = format!("&{name};");
+                match quick_xml::escape::unescape(&entity) {
+                    Ok(s) => text.push_str(&s),
+                    Err(_) if name == "nbsp" => text.push(' '),
+                    Err(_) => text.push_str(&entity),
+                }
+            }
+            Event::End(_) => {
+                let name = path.pop().unwrap_or_default();
+                let parent = path.last().map(String::as_str).unwrap_or("");
+                let value = text.trim().to_string();
+                text.clear();
+
+                if name == "item" || name == "entry" {
+                    if let Some(mut it) = item.take() {
+                        if it.id.is_empty() {
+                            it.id = if it.link.is_empty() {
+                                it.title.clone()
+                            } else {
+                                it.link.clone()
+                            };
+                        }
+                        if !it.id.is_empty() {
+                            feed.items.push(it);
+                        }
+                    }
+                    continue;
+                }
+
+                if let Some(it) = item.as_mut() {
+                    if parent != "item" && parent != "entry" {
+                        continue;
+                    }
+                    match name.as_str() {
+                        "title" => it.title = value,
+                        "link" if it.link.is_empty() => it.link = value,
+                        "guid" | "id" => it.id = value,
+                        "pubDate" | "published" | "updated" | "date"
+                            if it.published.is_none() && !value.is_empty() =>
+                        {
+                            it.published = Some(value);
+                        }
+                        _ => {}
+                    }
+                } else if name == "title"
+                    && (parent == "channel" || parent == "feed")
+                    && feed.title.is_empty()
+                {
+                    feed.title = value;
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    match root.as_deref() {
+        Some("rss") | Some("feed") | Some("RDF") => Ok(feed),
+        Some(other) => anyhow::bail!("not an RSS or Atom feed (root element <{other}>)"),
+        None => anyhow::bail!("empty document"),
+    }
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+/// `href` of an Atom `<link>`, ignoring non-alternate relations.
+fn atom_link(e: &BytesStart) -> Option<String> {
+    let mut href = None;
+    let mut rel = None;
+    for attr in e.attributes().flatten() {
+        let value = attr.unescape_value().ok()?.into_owned();
+        match attr.key.local_name().as_ref() {
+            b"href" => href = Some(value),
+            b"rel" => rel = Some(value),
+            _ => {}
+        }
+    }
+    match rel.as_deref() {
+        None | Some("alternate") => href,
+        _ => None,
+    }
+}
+
+/// Last fetch result for one feed URL.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedCache {
+    pub title: String,
+    pub items: Vec<FeedItem>,
+    /// Unix seconds of the last attempt, successful or not.
+    pub fetched_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Fetches feeds when they are due and keeps the results in a signal and in storage.
+pub struct FeedStore {
+    http: Rc<dyn HttpClient>,
+    /// Storage key of the dashboard this store was created for.
+    key: String,
+    pub cache: Signal<HashMap<String, FeedCache>>,
+    in_flight: Rc<RefCell<HashSet<String>>>,
+}
+
+impl FeedStore {
+    pub fn new(http: Rc<dyn HttpClient>) -> Self {
+        let key = storage::scoped(CACHE_KEY);
+        Self {
+            http,
+            cache: signal(storage::load_json(&key)),
+            key,
+            in_flight: Rc::new(RefCell::new(HashSet::new())),
+        }
+    }
+
+    /// Start a fetch for every URL whose cache is older than `refresh_secs`.
+    /// `proxy`, when set, is prefixed to the URL-encoded feed URL.
+    pub fn refresh_due(&self, urls: &[String], refresh_secs: u64, proxy: &str, now: u64) {
+        let cache = self.cache.get();
+        for url in urls {
+            let due = cache
+                .get(url)
+                .is_none_or(|c| now.saturating_sub(c.fetched_at) >= refresh_secs);
+            if due {
+                self.fetch(url, proxy, now);
+            }
+        }
+    }
+
+    pub fn fetch(&self, url: &str, proxy: &str, now: u64) {
+        if !self.in_flight.borrow_mut().insert(url.to_string()) {
+            return;
+        }
+        let target = if proxy.trim().is_empty() {
+            url.to_string()
+        } else {
+            format!("{}{}", proxy.trim(), urlencoding::encode(url))
+        };
+
+        let cache = self.cache.clone();
+        let in_flight = self.in_flight.clone();
+        let key = self.key.clone();
+        let url = url.to_string();
+        self.http.send(
+            HttpRequest::get(target),
+            Box::new(move |result| {
+                in_flight.borrow_mut().remove(&url);
+                let parsed = result.and_then(|resp| {
+                    if !resp.ok() {
+                        anyhow::bail!("HTTP {}", resp.status);
+                    }
+                    parse_feed(&resp.body)
+                });
+                cache.update(|c| {
+                    let entry = c.entry(url).or_default();
+                    entry.fetched_at = now;
+                    match parsed {
+                        Ok(feed) => {
+                            entry.title = feed.title;
+                            entry.items = feed.items;
+                            entry.error = None;
+                        }
+                        // Keep the last good items around when a refresh fails.
+                        Err(e) => entry.error = Some(e.to_string()),
+                    }
+                });
+                storage::save_json(&key, &cache.get());
+            }),
+        );
+    }
+
+    /// Drop cached results for feeds that are no longer subscribed.
+    pub fn retain(&self, urls: &[String]) {
+        self.cache.update(|c| c.retain(|k, _| urls.contains(k)));
+        storage::save_json(&self.key, &self.cache.get());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::{StubHttp, response};
+
+    const RSS: &str = include_str!("../fixtures/feed-rss2.xml");
+    const ATOM: &str = include_str!("../fixtures/feed-atom.xml");
+    const MALFORMED: &str = include_str!("../fixtures/feed-malformed.xml");
+
+    const URL: &str = "https://news.example.com/feed.xml";
+
+    #[test]
+    fn parses_rss() {
+        let feed = parse_feed(RSS).unwrap();
+        assert_eq!(feed.title, "Example News");
+        assert_eq!(
+            feed.items,
+            vec![
+                FeedItem {
+                    id: "news-1001".to_string(),
+                    title: "Rust & WebAssembly ship together".to_string(),
+                    link: "https://news.example.com/rust-wasm".to_string(),
+                    published: Some("Mon, 06 May 2024 09:30:00 GMT".to_string()),
+                },
+                FeedItem {
+                    id: "https://news.example.com/cdata".to_string(),
+                    title: "Markup <b>in</b> CDATA".to_string(),
+                    link: "https://news.example.com/cdata".to_string(),
+                    published: Some("2024-05-05T18:00:00Z".to_string()),
+                },
+                FeedItem {
+                    id: "No link, no guid".to_string(),
+                    title: "No link, no guid".to_string(),
+                    link: String::new(),
+                    published: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_atom() {
+        let feed = parse_feed(ATOM).unwrap();
+        assert_eq!(feed.title, "Example Blog");
+        assert_eq!(feed.items.len(), 2);
+        let second = &feed.items[0];
+        assert_eq!(second.id, "tag:blog.example.com,2024:2");
+        // rel="self" is skipped for the alternate link.
+        assert_eq!(second.link, "https://blog.example.com/posts/2");
+        assert_eq!(second.published.as_deref(), Some("2024-05-06T10:00:00Z"));
+        let first = &feed.items[1];
+        assert_eq!(first.link, "https://blog.example.com/posts/1");
+        assert_eq!(first.published.as_deref(), Some("2024-05-01T08:00:00Z"));
+    }
+
+    #[test]
+    fn rejects_malformed_and_foreign_documents() {
+        assert!(parse_feed(MALFORMED).is_err());
+        assert_eq!(parse_feed("").unwrap_err().to_string(), "empty document");
+        assert_eq!(
+            parse_feed("<html><title>Hi</title></html>")
+                .unwrap_err()
+                .to_string(),
+            "not an RSS or Atom feed (root element <html>)"
+        );
+    }
+
+    #[test]
+    fn refresh_stores_items_and_skips_fresh_feeds() {
+        let http = StubHttp::new(|_| Ok(response(200, RSS)));
+        let store = FeedStore::new(http.clone());
+        store.refresh_due(&[URL.to_string()], 600, "", 1_000);
+
+        let cached = store.cache.get()[URL].clone();
+        assert_eq!(cached.title, "Example News");
+        assert_eq!(cached.items.len(), 3);
+        assert_eq!(cached.fetched_at, 1_000);
+        assert_eq!(cached.error, None);
+        // A new store starts from what was saved.
+        assert_eq!(FeedStore::new(http.clone()).cache.get()[URL], cached);
+
+        store.refresh_due(&[URL.to_string()], 600, "", 1_599);
+        assert_eq!(http.sent().len(), 1);
+        store.refresh_due(&[URL.to_string()], 600, "", 1_600);
+        assert_eq!(http.sent().len(), 2);
+    }
+
+    #[test]
+    fn failed_refresh_keeps_last_items() {
+        let http = StubHttp::new(|_| Ok(response(200, RSS)));
+        let store = FeedStore::new(http);
+        store.fetch(URL, "", 1_000);
+
+        let failing = StubHttp::new(|_| Ok(response(503, "")));
+        let store = FeedStore::new(failing);
+        store.fetch(URL, "", 2_000);
+        let cached = store.cache.get()[URL].clone();
+        assert_eq!(cached.error.as_deref(), Some("HTTP 503"));
+        assert_eq!(cached.items.len(), 3);
+        assert_eq!(cached.fetched_at, 2_000);
+
+        let broken = StubHttp::new(|_| Ok(response(200, MALFORMED)));
+        let store = FeedStore::new(broken);
+        store.fetch(URL, "", 3_000);
+        let cached = store.cache.get()[URL].clone();
+        assert!(cached.error.is_some());
+        assert_eq!(cached.items.len(), 3);
+
+        let ok = StubHttp::new(|_| Ok(response(200, ATOM)));
+        let store = FeedStore::new(ok);
+        store.fetch(URL, "", 4_000);
+        let cached = store.cache.get()[URL].clone();
+        assert_eq!(cached.error, None);
+        assert_eq!(cached.title, "Example Blog");
+    }
+
+    #[test]
+    fn fetch_goes_through_proxy_once_at_a_time() {
+        let http = StubHttp::holding(|_| Ok(response(200, RSS)));
+        let store = FeedStore::new(http.clone());
+        store.fetch(URL, " https://proxy.example/?url= ", 1_000);
+        store.fetch(URL, " https://proxy.example/?url= ", 1_000);
+        assert_eq!(http.held(), 1);
+        assert_eq!(
+            http.sent()[0].url,
+            "https://proxy.example/?url=https%3A%2F%2Fnews.example.com%2Ffeed.xml"
+        );
+        http.release_all();
+        assert_eq!(store.cache.get()[URL].items.len(), 3);
+    }
+
+    #[test]
+    fn each_dashboard_keeps_its_own_cache() {
+        let http = StubHttp::new(|_| Ok(response(200, RSS)));
+        storage::set_dashboard("work");
+        FeedStore::new(http.clone()).fetch(URL, "", 1_000);
+
+        storage::set_dashboard(crate::dashboards::HOME);
+        let home = FeedStore::new(http.clone());
+        assert!(home.cache.get().is_empty());
+        home.retain(&[]);
+
+        storage::set_dashboard("work");
+        assert!(FeedStore::new(http.clone()).cache.get().contains_key(URL));
+        storage::remove_dashboard_data("work");
+        assert!(FeedStore::new(http).cache.get().is_empty());
+    }
+
+    #[test]
+    fn retain_drops_unsubscribed_feeds() {
+        let http = StubHttp::new(|_| Ok(response(200, RSS)));
+        let store = FeedStore::new(http.clone());
+        store.fetch(URL, "", 1_000);
+        store.fetch("https://other.example/feed", "", 1_000);
+        store.retain(&[URL.to_string()]);
+        assert_eq!(store.cache.get().len(), 1);
+        assert_eq!(FeedStore::new(http).cache.get().len(), 1);
+    }
+}
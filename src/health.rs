@@ -0,0 +1,399 @@
+//! Optional per-bookmark health checks.
+//!
+//! A bookmark with a `HealthCheck` is probed every `interval_secs`. Each probe
+//! is classified as a pass, a mismatch or a failure, and `HealthState::next`
+//! folds that into the status shown on the tile.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use repose_core::{Signal, signal};
+use serde::{Deserialize, Serialize};
+
+use crate::net::{HttpClient, HttpRequest, HttpResponse};
+use crate::storage::{self, Bookmark};
+
+const STATE_KEY: &str = "startpage.health.v1";
+
+/// Consecutive failures before a check is reported as down; fewer show as degraded.
+pub const DOWN_AFTER: u32 = 2;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    /// URL to probe; empty means the bookmark's own URL.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    pub interval_secs: u32,
+    /// Expected status code; `None` accepts any 2xx.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expect_status: Option<u16>,
+    /// Text the response body must contain; empty skips the body check.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub expect_body: String,
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            interval_secs: 60,
+            expect_status: None,
+            expect_body: String::new(),
+        }
+    }
+}
+
+impl HealthCheck {
+    pub fn target<'a>(&'a self, bookmark_url: &'a str) -> &'a str {
+        if self.url.trim().is_empty() {
+            bookmark_url
+        } else {
+            self.url.trim()
+        }
+    }
+}
+
+/// What a single probe found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    /// Reachable, but not what the check expects.
+    Mismatch(String),
+    /// Unreachable or a server error.
+    Fail(String),
+}
+
+pub fn classify(check: &HealthCheck, result: &anyhow::Result<HttpResponse>) -> Outcome {
+    let resp = match result {
+        Ok(resp) => resp,
+        Err(e) => return Outcome::Fail(e.to_string()),
+    };
+    if resp.status >= 500 {
+        return Outcome::Fail(format!("HTTP {}", resp.status));
+    }
+    match check.expect_status {
+        Some(want) if resp.status != want => {
+            return Outcome::Mismatch(format!("HTTP {}, expected {want}", resp.status));
+        }
+        None if !resp.ok() => return Outcome::Mismatch(format!("HTTP {}", resp.status)),
+        _ => {}
+    }
+    let needle = check.expect_body.trim();
+    if !needle.is_empty() && !resp.body.contains(needle) {
+        return Outcome::Mismatch(format!("body lacks \"{needle}\""));
+    }
+    Outcome::Pass
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HealthStatus {
+    #[default]
+    Unknown,
+    Up,
+    Degraded,
+    Down,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthState {
+    pub status: HealthStatus,
+    /// Unix seconds of the last completed probe.
+    pub checked_at: u64,
+    /// Why the last probe did not pass; empty when it did.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+    /// Consecutive failed probes.
+    #[serde(default)]
+    pub failures: u32,
+}
+
+impl HealthState {
+    pub fn next(&self, outcome: Outcome, now: u64) -> HealthState {
+        let (status, detail, failures) = match outcome {
+            Outcome::Pass => (HealthStatus::Up, String::new(), 0),
+            Outcome::Mismatch(d) => (HealthStatus::Degraded, d, 0),
+            Outcome::Fail(d) => {
+                let failures = self.failures + 1;
+                // A single blip shows amber; only repeated failures go red.
+                let status = if failures >= DOWN_AFTER {
+                    HealthStatus::Down
+                } else {
+                    HealthStatus::Degraded
+                };
+                (status, d, failures)
+            }
+        };
+        HealthState {
+            status,
+            checked_at: now,
+            detail,
+            failures,
+        }
+    }
+
+    pub fn due(&self, check: &HealthCheck, now: u64) -> bool {
+        self.status == HealthStatus::Unknown
+            || now.saturating_sub(self.checked_at) >= u64::from(check.interval_secs.max(5))
+    }
+}
+
+/// Probes checked bookmarks when due and keeps the results, keyed by bookmark URL.
+pub struct HealthMonitor {
+    http: Rc<dyn HttpClient>,
+    /// Storage key of the dashboard this monitor was created for.
+    key: String,
+    pub state: Signal<HashMap<String, HealthState>>,
+    in_flight: Rc<RefCell<HashSet<String>>>,
+}
+
+impl HealthMonitor {
+    pub fn new(http: Rc<dyn HttpClient>) -> Self {
+        let key = storage::scoped(STATE_KEY);
+        Self {
+            http,
+            state: signal(storage::load_json(&key)),
+            key,
+            in_flight: Rc::new(RefCell::new(HashSet::new())),
+        }
+    }
+
+    /// Probe every checked bookmark that is due, and forget state for the rest.
+    pub fn tick(&self, bookmarks: &[Bookmark], now: u64) {
+        let state = self.state.get();
+        let stale = state.keys().any(|url| {
+            !bookmarks
+                .iter()
+                .any(|b| &b.url == url && b.health.is_some())
+        });
+        if stale {
+            self.state.update(|s| {
+                s.retain(|url, _| {
+                    bookmarks
+                        .iter()
+                        .any(|b| &b.url == url && b.health.is_some())
+                })
+            });
+            storage::save_json(&self.key, &self.state.get());
+        }
+
+        for bm in bookmarks {
+            let Some(check) = &bm.health else { continue };
+            if state.get(&bm.url).is_none_or(|s| s.due(check, now)) {
+                self.probe(bm, now);
+            }
+        }
+    }
+
+    /// Probe `bm` now, whether or not it is due.
+    pub fn probe(&self, bm: &Bookmark, now: u64) {
+        let Some(check) = bm.health.clone() else {
+            return;
+        };
+        if !self.in_flight.borrow_mut().insert(bm.url.clone()) {
+            return;
+        }
+
+        let state = self.state.clone();
+        let in_flight = self.in_flight.clone();
+        let url = bm.url.clone();
+        let key = self.key.clone();
+        self.http.send(
+            HttpRequest::get(check.target(&bm.url)),
+            Box::new(move |result| {
+                in_flight.borrow_mut().remove(&url);
+                let outcome = classify(&check, &result);
+                state.update(|s| {
+                    let entry = s.entry(url).or_default();
+                    *entry = entry.next(outcome, now);
+                });
+                storage::save_json(&key, &state.get());
+            }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::net::{StubHttp, response};
+
+    fn checked(url: &str, check: HealthCheck) -> Bookmark {
+        Bookmark {
+            title: url.to_string(),
+            url: url.to_string(),
+            group: String::new(),
+            health: Some(check),
+            open_in: None,
+            tags: Vec::new(),
+            pinned: false,
+        }
+    }
+
+    /// A server whose status the test sets; 0 drops the connection.
+    fn server() -> (Rc<StubHttp>, Rc<Cell<u16>>) {
+        let status = Rc::new(Cell::new(200));
+        let http = StubHttp::new({
+            let status = status.clone();
+            move |_| match status.get() {
+                0 => anyhow::bail!("connection refused"),
+                s => Ok(response(s, "status: all good")),
+            }
+        });
+        (http, status)
+    }
+
+    #[test]
+    fn classifies_responses() {
+        let any = HealthCheck::default();
+        let strict = HealthCheck {
+            expect_status: Some(204),
+            expect_body: "ready".to_string(),
+            ..HealthCheck::default()
+        };
+        let cases: &[(&HealthCheck, anyhow::Result<HttpResponse>, Outcome)] = &[
+            (&any, Ok(response(200, "")), Outcome::Pass),
+            (
+                &any,
+                Ok(response(503, "")),
+                Outcome::Fail("HTTP 503".into()),
+            ),
+            (
+                &any,
+                Ok(response(404, "")),
+                Outcome::Mismatch("HTTP 404".into()),
+            ),
+            (
+                &any,
+                Err(anyhow::anyhow!("timed out")),
+                Outcome::Fail("timed out".into()),
+            ),
+            (
+                &strict,
+                Ok(response(200, "ready")),
+                Outcome::Mismatch("HTTP 200, expected 204".into()),
+            ),
+            (
+                &strict,
+                Ok(response(204, "starting")),
+                Outcome::Mismatch("body lacks \"ready\"".into()),
+            ),
+            (&strict, Ok(response(204, "ready")), Outcome::Pass),
+        ];
+        for (check, result, want) in cases {
+            assert_eq!(classify(check, result), *want, "{result:?}");
+        }
+    }
+
+    #[test]
+    fn goes_down_after_repeated_failures_and_recovers() {
+        let (http, status) = server();
+        let monitor = HealthMonitor::new(http);
+        let bm = checked("https://app.example", HealthCheck::default());
+        let at = |monitor: &HealthMonitor| monitor.state.get()[&bm.url].clone();
+
+        monitor.probe(&bm, 100);
+        assert_eq!(at(&monitor).status, HealthStatus::Up);
+
+        status.set(0);
+        for n in 1..DOWN_AFTER {
+            monitor.probe(&bm, 100 + u64::from(n));
+            assert_eq!(at(&monitor).status, HealthStatus::Degraded);
+            assert_eq!(at(&monitor).failures, n);
+        }
+        monitor.probe(&bm, 200);
+        let down = at(&monitor);
+        assert_eq!(down.status, HealthStatus::Down);
+        assert_eq!(down.detail, "connection refused");
+        assert_eq!(down.checked_at, 200);
+
+        // A mismatch is degraded, but it is an answer: the failure count restarts.
+        status.set(404);
+        monitor.probe(&bm, 300);
+        assert_eq!(at(&monitor).status, HealthStatus::Degraded);
+        assert_eq!(at(&monitor).failures, 0);
+
+        status.set(200);
+        monitor.probe(&bm, 400);
+        assert_eq!(
+            at(&monitor),
+            HealthState {
+                status: HealthStatus::Up,
+                checked_at: 400,
+                detail: String::new(),
+                failures: 0,
+            }
+        );
+        // Results are kept for the next page load.
+        assert_eq!(
+            HealthMonitor::new(server().0).state.get()[&bm.url].status,
+            HealthStatus::Up
+        );
+    }
+
+    #[test]
+    fn due_after_interval() {
+        let check = HealthCheck {
+            interval_secs: 60,
+            ..HealthCheck::default()
+        };
+        assert!(HealthState::default().due(&check, 0));
+        let state = HealthState::default().next(Outcome::Pass, 1_000);
+        assert!(!state.due(&check, 1_059));
+        assert!(state.due(&check, 1_060));
+        // Intervals below five seconds are stretched to five.
+        let eager = HealthCheck {
+            interval_secs: 1,
+            ..HealthCheck::default()
+        };
+        assert!(!state.due(&eager, 1_004));
+        assert!(state.due(&eager, 1_005));
+    }
+
+    #[test]
+    fn tick_probes_due_checks_and_forgets_removed_ones() {
+        let (http, _) = server();
+        let monitor = HealthMonitor::new(http.clone());
+        let own = checked("https://a.example", HealthCheck::default());
+        let other = checked(
+            "https://b.example",
+            HealthCheck {
+                url: " https://b.example/healthz ".to_string(),
+                interval_secs: 300,
+                ..HealthCheck::default()
+            },
+        );
+        let unchecked = Bookmark {
+            health: None,
+            ..checked("https://c.example", HealthCheck::default())
+        };
+        let all = [own.clone(), other.clone(), unchecked];
+
+        monitor.tick(&all, 1_000);
+        let urls: Vec<String> = http.sent().into_iter().map(|r| r.url).collect();
+        assert_eq!(urls, ["https://a.example", "https://b.example/healthz"]);
+
+        monitor.tick(&all, 1_059);
+        assert_eq!(http.sent().len(), 2);
+        monitor.tick(&all, 1_060);
+        assert_eq!(http.sent().len(), 3);
+        assert_eq!(http.sent()[2].url, "https://a.example");
+
+        monitor.tick(&[own], 1_061);
+        assert!(!monitor.state.get().contains_key(&other.url));
+    }
+
+    #[test]
+    fn one_probe_per_bookmark_at_a_time() {
+        let http = StubHttp::holding(|_| Ok(response(200, "")));
+        let monitor = HealthMonitor::new(http.clone());
+        let bm = checked("https://a.example", HealthCheck::default());
+        monitor.probe(&bm, 1);
+        monitor.tick(std::slice::from_ref(&bm), 2);
+        assert_eq!(http.held(), 1);
+        http.release_all();
+        assert_eq!(monitor.state.get()[&bm.url].status, HealthStatus::Up);
+    }
+}
@@ -1,10 +1,42 @@
+// Natively only tests are built, and the app they don't reach is dead code.
+#![cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+
 mod app;
+mod clipboard;
+mod commands;
+mod context_menu;
+mod crypto;
+mod dashboards;
+mod deeplink;
+mod feed;
+mod health;
+mod linkcheck;
+mod links;
+mod metadata;
+mod net;
+// `description` is only used by build.rs, which includes this module on its own.
+#[allow(dead_code)]
+mod opensearch;
+mod palette;
+mod paste;
+mod secret;
+mod selection;
+mod settings;
 mod storage;
+mod sync;
+mod urls;
+mod weather;
+mod widgets;
 
+#[cfg(target_arch = "wasm32")]
 use repose_ui::overlay::SnackbarController;
+#[cfg(target_arch = "wasm32")]
 use std::rc::Rc;
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
+// Tests run natively, where the web platform doesn't exist.
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen(start)]
 pub fn wasm_start() -> Result<(), JsValue> {
     console_error_panic_hook::set_once();
@@ -18,6 +50,6 @@ pub fn wasm_start() -> Result<(), JsValue> {
     repose_platform::web::run_web_app_with_snackbar(
         |s, _rc| app::app(s),
         opts,
-        Some(Rc::new(|ms| SnackbarController::tick_for_frame(ms))),
+        Some(Rc::new(SnackbarController::tick_for_frame)),
     )
 }
@@ -0,0 +1,446 @@
+//! The "check links" job: probe every bookmark once and report the ones that
+//! are gone or have moved.
+//!
+//! `HttpProbe` tries HEAD, falls back to GET where HEAD is refused, and uses a
+//! no-cors GET to tell a CORS-blocked site from a dead one. At most
+//! `CONCURRENCY` probes are in flight at a time.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use repose_core::{Signal, signal};
+use serde::{Deserialize, Serialize};
+
+use crate::net::{HttpClient, HttpRequest, HttpResponse};
+use crate::storage::{self, Bookmark};
+
+const REPORT_KEY: &str = "startpage.linkcheck.v1";
+
+/// Probes in flight at once.
+pub const CONCURRENCY: usize = 4;
+
+/// What probing a link found.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkStatus {
+    Ok(u16),
+    /// Answered after redirects that ended at another URL.
+    Redirected {
+        status: u16,
+        to: String,
+    },
+    /// Not found, gone, or the host can't be reached at all.
+    Dead(String),
+    /// Reachable but answering with an error, which may pass or need a login.
+    Error(u16),
+    /// Reachable, but the server doesn't let the page read its answer (no CORS).
+    Opaque,
+}
+
+impl LinkStatus {
+    pub fn label(&self) -> String {
+        match self {
+            LinkStatus::Ok(status) => format!("OK ({status})"),
+            LinkStatus::Redirected { status, to } => format!("{status} at {to}"),
+            LinkStatus::Dead(why) => format!("dead: {why}"),
+            LinkStatus::Error(status) => format!("HTTP {status}"),
+            LinkStatus::Opaque => "reachable, status hidden by the site".to_string(),
+        }
+    }
+}
+
+/// Status of `url` from a readable response.
+pub fn classify(url: &str, resp: &HttpResponse) -> LinkStatus {
+    match resp.status {
+        404 | 410 => LinkStatus::Dead(format!("HTTP {}", resp.status)),
+        status if !resp.ok() => LinkStatus::Error(status),
+        status if resp.redirected && !same_url(&resp.url, url) => LinkStatus::Redirected {
+            status,
+            to: resp.url.clone(),
+        },
+        status => LinkStatus::Ok(status),
+    }
+}
+
+/// Equal apart from a trailing slash, which servers add or drop freely.
+fn same_url(a: &str, b: &str) -> bool {
+    a.is_empty() || a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+pub type ProbeCallback = Box<dyn FnOnce(LinkStatus)>;
+
+pub trait LinkProbe {
+    /// Probe `url`; `done` is called exactly once.
+    fn probe(&self, url: &str, done: ProbeCallback);
+}
+
+/// Probes with a HEAD request, falling back to GET for servers that refuse
+/// HEAD. When the browser blocks the answer, a `no-cors` request tells a
+/// site that is up but doesn't allow CORS from one that is unreachable.
+pub struct HttpProbe {
+    http: Rc<dyn HttpClient>,
+}
+
+impl HttpProbe {
+    pub fn new(http: Rc<dyn HttpClient>) -> Self {
+        Self { http }
+    }
+}
+
+impl LinkProbe for HttpProbe {
+    fn probe(&self, url: &str, done: ProbeCallback) {
+        let http = self.http.clone();
+        let url = url.to_string();
+        self.http.send(
+            HttpRequest::head(&url),
+            Box::new(move |result| match result {
+                Ok(resp) if matches!(resp.status, 405 | 501) => {
+                    let target = url.clone();
+                    http.send(
+                        HttpRequest::get(&url),
+                        Box::new(move |result| match result {
+                            Ok(resp) => done(classify(&target, &resp)),
+                            Err(e) => done(LinkStatus::Dead(format!("unreachable ({e})"))),
+                        }),
+                    );
+                }
+                Ok(resp) => done(classify(&url, &resp)),
+                Err(_) => http.send(
+                    HttpRequest {
+                        no_cors: true,
+                        ..HttpRequest::get(&url)
+                    },
+                    Box::new(move |result| match result {
+                        Ok(_) => done(LinkStatus::Opaque),
+                        Err(e) => done(LinkStatus::Dead(format!("unreachable ({e})"))),
+                    }),
+                ),
+            }),
+        );
+    }
+}
+
+/// Results of the last run, in the order the answers came.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckReport {
+    /// Unix seconds the run started.
+    pub started_at: u64,
+    pub total: usize,
+    pub running: bool,
+    pub results: Vec<(String, LinkStatus)>,
+}
+
+impl CheckReport {
+    pub fn dead(&self) -> Vec<String> {
+        self.results
+            .iter()
+            .filter(|(_, s)| matches!(s, LinkStatus::Dead(_)))
+            .map(|(u, _)| u.clone())
+            .collect()
+    }
+
+    /// `(from, to)` for every link that redirected.
+    pub fn redirects(&self) -> Vec<(String, String)> {
+        self.results
+            .iter()
+            .filter_map(|(u, s)| match s {
+                LinkStatus::Redirected { to, .. } => Some((u.clone(), to.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Drop the results for `urls`, once they have been dealt with.
+    pub fn forget(&mut self, urls: &[String]) {
+        self.results.retain(|(u, _)| !urls.contains(u));
+    }
+}
+
+/// Point bookmarks at the URLs they redirect to. A redirect to a URL that
+/// another bookmark already has is skipped. Returns the `(from, to)` pairs
+/// applied.
+pub fn apply_redirects(
+    bookmarks: &mut [Bookmark],
+    redirects: &[(String, String)],
+) -> Vec<(String, String)> {
+    let mut applied = Vec::new();
+    for (from, to) in redirects {
+        if bookmarks.iter().any(|bm| &bm.url == to) {
+            continue;
+        }
+        if let Some(bm) = bookmarks.iter_mut().find(|bm| &bm.url == from) {
+            bm.url = to.clone();
+            applied.push((from.clone(), to.clone()));
+        }
+    }
+    applied
+}
+
+/// Runs the check over a list of URLs and keeps the report, per dashboard.
+#[derive(Clone)]
+pub struct LinkChecker {
+    probe: Rc<dyn LinkProbe>,
+    /// Storage key of the dashboard this checker was created for.
+    key: String,
+    pub report: Signal<CheckReport>,
+    queue: Rc<RefCell<VecDeque<String>>>,
+    in_flight: Rc<Cell<usize>>,
+    /// Bumped by every start and cancel, so answers from an older run are dropped.
+    run: Rc<Cell<u64>>,
+}
+
+impl LinkChecker {
+    pub fn new(probe: Rc<dyn LinkProbe>) -> Self {
+        let key = storage::scoped(REPORT_KEY);
+        let mut report: CheckReport = storage::load_json(&key);
+        // A run cut short by a reload doesn't resume.
+        report.running = false;
+        Self {
+            probe,
+            report: signal(report),
+            key,
+            queue: Rc::new(RefCell::new(VecDeque::new())),
+            in_flight: Rc::new(Cell::new(0)),
+            run: Rc::new(Cell::new(0)),
+        }
+    }
+
+    /// Check every URL in `urls`, replacing the last report.
+    pub fn start(&self, urls: Vec<String>, now: u64) {
+        self.run.set(self.run.get() + 1);
+        self.in_flight.set(0);
+        self.report.set(CheckReport {
+            started_at: now,
+            total: urls.len(),
+            running: true,
+            results: Vec::new(),
+        });
+        *self.queue.borrow_mut() = urls.into();
+        self.pump();
+        self.finish_if_done();
+    }
+
+    /// Stop the run, keeping what has been found so far.
+    pub fn cancel(&self) {
+        self.run.set(self.run.get() + 1);
+        self.queue.borrow_mut().clear();
+        self.in_flight.set(0);
+        self.report.update(|r| r.running = false);
+        storage::save_json(&self.key, &self.report.get());
+    }
+
+    /// Change the saved report, e.g. after acting on it.
+    pub fn edit(&self, f: impl FnOnce(&mut CheckReport)) {
+        self.report.update(f);
+        storage::save_json(&self.key, &self.report.get());
+    }
+
+    fn pump(&self) {
+        while self.in_flight.get() < CONCURRENCY {
+            let Some(url) = self.queue.borrow_mut().pop_front() else {
+                return;
+            };
+            self.in_flight.set(self.in_flight.get() + 1);
+            let this = self.clone();
+            let run = self.run.get();
+            let target = url.clone();
+            self.probe.probe(
+                &url,
+                Box::new(move |status| {
+                    if this.run.get() != run {
+                        return;
+                    }
+                    this.in_flight.set(this.in_flight.get() - 1);
+                    this.report.update(|r| r.results.push((target, status)));
+                    this.pump();
+                    this.finish_if_done();
+                }),
+            );
+        }
+    }
+
+    fn finish_if_done(&self) {
+        if self.in_flight.get() > 0 || !self.queue.borrow().is_empty() || !self.report.get().running
+        {
+            return;
+        }
+        self.report.update(|r| r.running = false);
+        storage::save_json(&self.key, &self.report.get());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::{StubHttp, response};
+
+    const URL: &str = "https://example.com/docs";
+
+    /// Probe `URL` against `handler`; the status and the requests it took.
+    fn probe(
+        handler: impl Fn(&HttpRequest) -> anyhow::Result<HttpResponse> + 'static,
+    ) -> (LinkStatus, Vec<String>) {
+        let http = StubHttp::new(handler);
+        let got = Rc::new(RefCell::new(None));
+        HttpProbe::new(http.clone()).probe(URL, {
+            let got = got.clone();
+            Box::new(move |status| *got.borrow_mut() = Some(status))
+        });
+        let requests = http
+            .sent()
+            .into_iter()
+            .map(|r| format!("{}{}", r.method, if r.no_cors { " no-cors" } else { "" }))
+            .collect();
+        let status = got.borrow_mut().take().expect("probe answered");
+        (status, requests)
+    }
+
+    fn redirected_to(url: &str) -> HttpResponse {
+        HttpResponse {
+            redirected: true,
+            url: url.to_string(),
+            ..response(200, "")
+        }
+    }
+
+    #[test]
+    fn classifies_responses() {
+        let cases = [
+            (response(200, ""), LinkStatus::Ok(200)),
+            (response(204, ""), LinkStatus::Ok(204)),
+            (response(404, ""), LinkStatus::Dead("HTTP 404".into())),
+            (response(410, ""), LinkStatus::Dead("HTTP 410".into())),
+            (response(403, ""), LinkStatus::Error(403)),
+            (response(500, ""), LinkStatus::Error(500)),
+            (
+                redirected_to("https://example.com/guide"),
+                LinkStatus::Redirected {
+                    status: 200,
+                    to: "https://example.com/guide".into(),
+                },
+            ),
+            // Only a trailing slash changed.
+            (
+                redirected_to("https://example.com/docs/"),
+                LinkStatus::Ok(200),
+            ),
+            // Browsers may hide the final URL.
+            (redirected_to(""), LinkStatus::Ok(200)),
+        ];
+        for (resp, want) in cases {
+            assert_eq!(classify(URL, &resp), want, "{resp:?}");
+        }
+    }
+
+    #[test]
+    fn falls_back_to_get_when_head_is_refused() {
+        for refused in [405, 501] {
+            let (status, requests) = probe(move |req| {
+                Ok(match req.method.as_str() {
+                    "HEAD" => response(refused, ""),
+                    _ => response(200, ""),
+                })
+            });
+            assert_eq!(status, LinkStatus::Ok(200));
+            assert_eq!(requests, ["HEAD", "GET"]);
+        }
+
+        let (status, requests) = probe(|req| match req.method.as_str() {
+            "HEAD" => Ok(response(405, "")),
+            _ => anyhow::bail!("connection reset"),
+        });
+        assert_eq!(
+            status,
+            LinkStatus::Dead("unreachable (connection reset)".into())
+        );
+        assert_eq!(requests, ["HEAD", "GET"]);
+    }
+
+    #[test]
+    fn answered_head_is_final() {
+        let (status, requests) = probe(|_| Ok(response(410, "")));
+        assert_eq!(status, LinkStatus::Dead("HTTP 410".into()));
+        assert_eq!(requests, ["HEAD"]);
+
+        let (status, _) = probe(|_| Ok(redirected_to("https://docs.example.com/")));
+        assert_eq!(
+            status,
+            LinkStatus::Redirected {
+                status: 200,
+                to: "https://docs.example.com/".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn transport_errors_split_into_opaque_and_dead() {
+        // Blocked by CORS, but a no-cors request gets through: the site is up.
+        let (status, requests) = probe(|req| {
+            if req.no_cors {
+                Ok(response(0, ""))
+            } else {
+                anyhow::bail!("CORS")
+            }
+        });
+        assert_eq!(status, LinkStatus::Opaque);
+        assert_eq!(requests, ["HEAD", "GET no-cors"]);
+
+        let (status, requests) = probe(|_| anyhow::bail!("name not resolved"));
+        assert_eq!(
+            status,
+            LinkStatus::Dead("unreachable (name not resolved)".into())
+        );
+        assert_eq!(requests, ["HEAD", "GET no-cors"]);
+    }
+
+    #[test]
+    fn keeps_at_most_concurrency_probes_in_flight() {
+        let http = StubHttp::holding(|req| {
+            Ok(if req.url.ends_with("/gone") {
+                response(404, "")
+            } else {
+                response(200, "")
+            })
+        });
+        let checker = LinkChecker::new(Rc::new(HttpProbe::new(http.clone())));
+        let mut urls: Vec<String> = (0..9).map(|i| format!("https://e{i}.example/")).collect();
+        urls.push("https://e9.example/gone".to_string());
+        checker.start(urls, 100);
+
+        assert_eq!(http.held(), CONCURRENCY);
+        while http.release_one() {
+            assert!(http.held() <= CONCURRENCY);
+        }
+        let report = checker.report.get();
+        assert!(!report.running);
+        assert_eq!(report.total, 10);
+        assert_eq!(report.results.len(), 10);
+        assert_eq!(report.dead(), ["https://e9.example/gone"]);
+        // The report survives a reload.
+        assert_eq!(
+            LinkChecker::new(Rc::new(HttpProbe::new(http))).report.get(),
+            report
+        );
+    }
+
+    #[test]
+    fn cancel_drops_late_answers() {
+        let http = StubHttp::holding(|_| Ok(response(200, "")));
+        let checker = LinkChecker::new(Rc::new(HttpProbe::new(http.clone())));
+        checker.start(vec![URL.to_string(), "https://b.example/".to_string()], 100);
+        checker.cancel();
+        http.release_all();
+        let report = checker.report.get();
+        assert!(!report.running);
+        assert!(report.results.is_empty());
+    }
+
+    #[test]
+    fn empty_run_finishes_at_once() {
+        let checker = LinkChecker::new(Rc::new(HttpProbe::new(StubHttp::new(|_| {
+            Ok(response(200, ""))
+        }))));
+        checker.start(Vec::new(), 100);
+        assert!(!checker.report.get().running);
+    }
+}
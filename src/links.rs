@@ -0,0 +1,187 @@
+//! How links open: same tab, new tab or new window.
+//!
+//! The runner only reports left-button presses, so middle clicks are caught
+//! with a DOM listener and hit-tested against the tile rects recorded while
+//! painting (`begin_frame` / `register`); the context menu uses the same
+//! rects for right clicks. The runner also doesn't say which modifiers
+//! were held when a text field submits, so the listener remembers them for
+//! the last Enter press.
+
+use std::cell::{Cell, RefCell};
+
+use repose_core::{Modifiers, Rect};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkTarget {
+    SameTab,
+    #[default]
+    NewTab,
+    NewWindow,
+}
+
+impl LinkTarget {
+    pub const ALL: [LinkTarget; 3] = [
+        LinkTarget::SameTab,
+        LinkTarget::NewTab,
+        LinkTarget::NewWindow,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LinkTarget::SameTab => "Same tab",
+            LinkTarget::NewTab => "New tab",
+            LinkTarget::NewWindow => "New window",
+        }
+    }
+}
+
+/// What a click with `modifiers` held asks for: Ctrl/⌘ for a new tab, Shift
+/// for a new window. `None` leaves it to the bookmark or the settings.
+pub fn for_click(modifiers: Modifiers) -> Option<LinkTarget> {
+    if modifiers.ctrl || modifiers.meta || modifiers.command {
+        Some(LinkTarget::NewTab)
+    } else if modifiers.shift {
+        Some(LinkTarget::NewWindow)
+    } else {
+        None
+    }
+}
+
+/// Open `url` as `target`. Returns `false` when the browser refused to open a
+/// tab or window, usually because of a popup blocker.
+pub fn open(url: &str, target: LinkTarget) -> bool {
+    let Some(w) = web_sys::window() else {
+        return false;
+    };
+    let opened = match target {
+        LinkTarget::SameTab => return w.location().set_href(url).is_ok(),
+        LinkTarget::NewTab => w.open_with_url_and_target(url, "_blank"),
+        LinkTarget::NewWindow => w.open_with_url_and_target_and_features(url, "_blank", "popup"),
+    };
+    match opened {
+        Ok(Some(child)) => {
+            // Same effect as rel="noopener": the page can't script us back.
+            let _ = child.set_opener(&JsValue::NULL);
+            true
+        }
+        _ => false,
+    }
+}
+
+thread_local! {
+    /// Tiles painted in the last frame, topmost last.
+    static TILES: RefCell<Vec<(Rect, String)>> = const { RefCell::new(Vec::new()) };
+    /// Whether tiles may take middle clicks; off while a dialog or drawer covers them.
+    static ACCEPTING: Cell<bool> = const { Cell::new(true) };
+    static ENTER_MODIFIERS: Cell<Modifiers> = Cell::new(Modifiers::default());
+    /// Last mouse position (px).
+    static POINTER: Cell<Option<(f32, f32)>> = const { Cell::new(None) };
+}
+
+/// Forget last frame's tiles. Call before the tiles are painted.
+pub fn begin_frame(accepting: bool) {
+    ACCEPTING.with(|a| a.set(accepting));
+    TILES.with(|t| t.borrow_mut().clear());
+}
+
+/// Record a tile's painted rect (px) so a middle or right click on it finds `url`.
+pub fn register(rect: Rect, url: &str) {
+    if ACCEPTING.with(Cell::get) {
+        TILES.with(|t| t.borrow_mut().push((rect, url.to_string())));
+    }
+}
+
+/// The tile painted at `x, y` (px) in the last frame, with its rect.
+pub fn tile_at(x: f32, y: f32) -> Option<(Rect, String)> {
+    TILES.with(|t| {
+        t.borrow()
+            .iter()
+            .rev()
+            .find(|(r, _)| x >= r.x && x < r.x + r.w && y >= r.y && y < r.y + r.h)
+            .cloned()
+    })
+}
+
+/// The first tile painted in the last frame.
+pub fn first_tile() -> Option<(Rect, String)> {
+    TILES.with(|t| t.borrow().first().cloned())
+}
+
+/// Where the mouse was last seen, in px.
+pub fn pointer() -> Option<(f32, f32)> {
+    POINTER.with(Cell::get)
+}
+
+/// Modifiers held during the last Enter press, for text field submissions.
+pub fn enter_modifiers() -> Modifiers {
+    ENTER_MODIFIERS.with(Cell::get)
+}
+
+/// Route middle clicks on tiles to `on_middle_click` and track the mouse
+/// and Enter modifiers. Call once.
+pub fn install_listeners(on_middle_click: impl Fn(&str) + 'static) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+
+    let on_mouse = Closure::<dyn Fn(web_sys::MouseEvent)>::new(move |ev: web_sys::MouseEvent| {
+        if ev.button() != 1 {
+            return;
+        }
+        let scale = web_sys::window().map_or(1.0, |w| w.device_pixel_ratio()) as f32;
+        if let Some((_, url)) = tile_at(ev.client_x() as f32 * scale, ev.client_y() as f32 * scale)
+        {
+            // Also stops the browser's middle-click autoscroll.
+            ev.prevent_default();
+            on_middle_click(&url);
+        }
+    });
+    let _ = window.add_event_listener_with_callback_and_bool(
+        "mousedown",
+        on_mouse.as_ref().unchecked_ref(),
+        true,
+    );
+    // Lives as long as the page.
+    on_mouse.forget();
+
+    let on_move = Closure::<dyn Fn(web_sys::MouseEvent)>::new(move |ev: web_sys::MouseEvent| {
+        let scale = web_sys::window().map_or(1.0, |w| w.device_pixel_ratio()) as f32;
+        POINTER.with(|p| {
+            p.set(Some((
+                ev.client_x() as f32 * scale,
+                ev.client_y() as f32 * scale,
+            )))
+        });
+    });
+    let _ = window.add_event_listener_with_callback_and_bool(
+        "mousemove",
+        on_move.as_ref().unchecked_ref(),
+        true,
+    );
+    on_move.forget();
+
+    let on_key =
+        Closure::<dyn Fn(web_sys::KeyboardEvent)>::new(move |ev: web_sys::KeyboardEvent| {
+            if ev.key() == "Enter" {
+                let (ctrl, meta) = (ev.ctrl_key(), ev.meta_key());
+                ENTER_MODIFIERS.with(|m| {
+                    m.set(Modifiers {
+                        shift: ev.shift_key(),
+                        ctrl,
+                        alt: ev.alt_key(),
+                        meta,
+                        command: ctrl || meta,
+                    })
+                });
+            }
+        });
+    let _ = window.add_event_listener_with_callback_and_bool(
+        "keydown",
+        on_key.as_ref().unchecked_ref(),
+        true,
+    );
+    on_key.forget();
+}
@@ -0,0 +1,255 @@
+//! Page titles for new bookmarks.
+//!
+//! A bookmark added without a title gets one derived from its URL right away;
+//! a `MetadataFetcher` then tries to replace it with the page's `<title>`.
+//! Browsers only let the app read other sites' pages through CORS, so the
+//! lookup can go through a proxy: a same-origin endpoint or one configured in
+//! the settings.
+//!
+//! Favicons are not looked up. Tiles are drawn on a canvas, which needs the
+//! icon's bytes, and those are as off limits without CORS as the page.
+
+use std::rc::Rc;
+
+use anyhow::bail;
+use serde::Deserialize;
+
+use crate::net::{HttpClient, HttpRequest, HttpResponse};
+use crate::settings::Settings;
+
+/// Longest title kept from a page; some sites stuff keywords into it.
+const MAX_TITLE_CHARS: usize = 120;
+
+/// What a page says about itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PageMeta {
+    pub title: Option<String>,
+}
+
+pub type MetaCallback = Box<dyn FnOnce(anyhow::Result<PageMeta>)>;
+
+pub trait MetadataFetcher {
+    /// Look up `url`; `done` is called exactly once.
+    fn fetch(&self, url: &str, done: MetaCallback);
+}
+
+/// Reads the page over HTTP, directly or through a proxy.
+///
+/// The proxy gets the URL-encoded page URL appended, like the feed widget's,
+/// and may answer with the page itself or with JSON `{"title"}`.
+pub struct HttpFetcher {
+    http: Rc<dyn HttpClient>,
+    proxy: String,
+}
+
+impl HttpFetcher {
+    pub fn new(http: Rc<dyn HttpClient>, proxy: &str) -> Self {
+        Self {
+            http,
+            proxy: proxy.trim().to_string(),
+        }
+    }
+}
+
+impl MetadataFetcher for HttpFetcher {
+    fn fetch(&self, url: &str, done: MetaCallback) {
+        let target = if self.proxy.is_empty() {
+            url.to_string()
+        } else {
+            format!("{}{}", self.proxy, urlencoding::encode(url))
+        };
+        self.http.send(
+            HttpRequest::get(target),
+            Box::new(move |result| done(result.and_then(|resp| parse_response(&resp)))),
+        );
+    }
+}
+
+/// The configured fetcher, or `None` when title lookups are turned off.
+pub fn fetcher(settings: &Settings, http: Rc<dyn HttpClient>) -> Option<Rc<dyn MetadataFetcher>> {
+    settings.lookup_titles.then(|| {
+        Rc::new(HttpFetcher::new(http, &settings.metadata_proxy)) as Rc<dyn MetadataFetcher>
+    })
+}
+
+#[derive(Deserialize)]
+struct ProxyMeta {
+    #[serde(default)]
+    title: Option<String>,
+}
+
+pub fn parse_response(resp: &HttpResponse) -> anyhow::Result<PageMeta> {
+    if !resp.ok() {
+        bail!("HTTP {}", resp.status);
+    }
+    let json = resp
+        .header("content-type")
+        .is_some_and(|v| v.contains("json"));
+    if !json {
+        return Ok(parse_html(&resp.body));
+    }
+    let meta: ProxyMeta = serde_json::from_str(&resp.body)?;
+    Ok(PageMeta {
+        title: meta.title.as_deref().and_then(clean_text),
+    })
+}
+
+/// The title from a page's HTML, or its `og:title`.
+pub fn parse_html(html: &str) -> PageMeta {
+    // ASCII lowercasing keeps byte offsets, so matches index into `html` too.
+    let lower = html.to_ascii_lowercase();
+
+    let title = element_text(html, &lower, "title").or_else(|| {
+        tags(html, &lower, "meta")
+            .find(|attrs| {
+                attr(attrs, "property").is_some_and(|p| p.eq_ignore_ascii_case("og:title"))
+            })
+            .and_then(|attrs| attr(&attrs, "content").and_then(|c| clean_text(&c)))
+    });
+
+    PageMeta { title }
+}
+
+/// Text inside the first `<name>` element.
+fn element_text(html: &str, lower: &str, name: &str) -> Option<String> {
+    let start = find_tag(lower, name, 0)?;
+    let open_end = start + lower[start..].find('>')? + 1;
+    let close = open_end + lower[open_end..].find(&format!("</{name}"))?;
+    clean_text(&html[open_end..close])
+}
+
+/// Byte offset of the next `<name` tag (not `<names`) at or after `from`.
+fn find_tag(lower: &str, name: &str, from: usize) -> Option<usize> {
+    let needle = format!("<{name}");
+    let mut at = from;
+    while let Some(i) = lower[at..].find(&needle) {
+        let start = at + i;
+        let after = lower[start + needle.len()..].chars().next();
+        if after.is_none_or(|c| c.is_ascii_whitespace() || c == '>' || c == '/') {
+            return Some(start);
+        }
+        at = start + needle.len();
+    }
+    None
+}
+
+/// Attributes of every `<name …>` tag, names lowercased.
+fn tags<'a>(
+    html: &'a str,
+    lower: &'a str,
+    name: &'a str,
+) -> impl Iterator<Item = Vec<(String, String)>> + 'a {
+    let mut at = 0;
+    std::iter::from_fn(move || {
+        let start = find_tag(lower, name, at)?;
+        let body_start = start + name.len() + 1;
+        let end = lower[body_start..]
+            .find('>')
+            .map_or(lower.len(), |i| body_start + i);
+        at = end;
+        Some(attributes(&html[body_start..end]))
+    })
+}
+
+/// `name="value"`, `name='value'`, `name=value` and bare `name` pairs.
+fn attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut rest = tag.trim_start_matches('/');
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+        let name_len = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        if name_len == 0 {
+            break;
+        }
+        let name = rest[..name_len].to_ascii_lowercase();
+        rest = rest[name_len..].trim_start();
+        let Some(after_eq) = rest.strip_prefix('=') else {
+            attrs.push((name, String::new()));
+            continue;
+        };
+        let after_eq = after_eq.trim_start();
+        let (value, tail) = match after_eq.chars().next() {
+            Some(q @ ('"' | '\'')) => {
+                let inner = &after_eq[1..];
+                let end = inner.find(q).unwrap_or(inner.len());
+                (&inner[..end], inner.get(end + 1..).unwrap_or(""))
+            }
+            _ => {
+                let end = after_eq
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .unwrap_or(after_eq.len());
+                (&after_eq[..end], &after_eq[end..])
+            }
+        };
+        attrs.push((name, decode_entities(value)));
+        rest = tail;
+    }
+    attrs
+}
+
+fn attr(attrs: &[(String, String)], name: &str) -> Option<String> {
+    attrs
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.clone())
+}
+
+/// Entities decoded, whitespace collapsed and long text cut. `None` when blank.
+fn clean_text(raw: &str) -> Option<String> {
+    let text = decode_entities(raw)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().count() <= MAX_TITLE_CHARS {
+        return Some(text);
+    }
+    let cut: String = text.chars().take(MAX_TITLE_CHARS - 1).collect();
+    Some(format!("{}…", cut.trim_end()))
+}
+
+/// The named and numeric entities that show up in titles.
+fn decode_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "ndash" => Some('–'),
+                "mdash" => Some('—'),
+                "hellip" => Some('…'),
+                num => num
+                    .strip_prefix("#x")
+                    .or_else(|| num.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| num.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            }?;
+            Some((c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
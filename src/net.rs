@@ -0,0 +1,233 @@
+//! HTTP behind a trait.
+//!
+//! Features that talk to the network take an `Rc<dyn HttpClient>`, so their
+//! state handling can be driven by an in-memory client instead of `fetch`.
+//! Requests complete through a callback; on the web this runs on the next
+//! microtask, after the response body has been read.
+
+#[cfg(test)]
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use anyhow::anyhow;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    /// Send in `no-cors` mode. Servers that don't allow CORS still answer,
+    /// but the response is opaque: status 0, no headers, no body.
+    pub no_cors: bool,
+}
+
+impl HttpRequest {
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            method: "GET".to_string(),
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn head(url: impl Into<String>) -> Self {
+        Self {
+            method: "HEAD".to_string(),
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// Final URL after redirects.
+    pub url: String,
+    pub redirected: bool,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn ok(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Value of header `name`, matched case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+pub type HttpCallback = Box<dyn FnOnce(anyhow::Result<HttpResponse>)>;
+
+pub trait HttpClient {
+    /// Start `req`; `done` is called exactly once with the response or a transport error.
+    /// Non-2xx statuses are responses, not errors.
+    fn send(&self, req: HttpRequest, done: HttpCallback);
+}
+
+#[cfg(test)]
+type StubHandler = Box<dyn Fn(&HttpRequest) -> anyhow::Result<HttpResponse>>;
+
+/// Answers from a handler instead of the network, for tests. While holding,
+/// requests wait for `release` so a test can look at what is in flight.
+#[cfg(test)]
+pub struct StubHttp {
+    handler: StubHandler,
+    sent: RefCell<Vec<HttpRequest>>,
+    hold: Cell<bool>,
+    held: RefCell<Vec<(HttpRequest, HttpCallback)>>,
+}
+
+#[cfg(test)]
+impl StubHttp {
+    pub fn new(
+        handler: impl Fn(&HttpRequest) -> anyhow::Result<HttpResponse> + 'static,
+    ) -> Rc<Self> {
+        Rc::new(Self {
+            handler: Box::new(handler),
+            sent: RefCell::default(),
+            hold: Cell::new(false),
+            held: RefCell::default(),
+        })
+    }
+
+    pub fn holding(
+        handler: impl Fn(&HttpRequest) -> anyhow::Result<HttpResponse> + 'static,
+    ) -> Rc<Self> {
+        let stub = Self::new(handler);
+        stub.hold.set(true);
+        stub
+    }
+
+    /// Every request so far, oldest first.
+    pub fn sent(&self) -> Vec<HttpRequest> {
+        self.sent.borrow().clone()
+    }
+
+    pub fn held(&self) -> usize {
+        self.held.borrow().len()
+    }
+
+    /// Answer the oldest held request; false when none was waiting.
+    pub fn release_one(&self) -> bool {
+        let next = {
+            let mut held = self.held.borrow_mut();
+            (!held.is_empty()).then(|| held.remove(0))
+        };
+        match next {
+            Some((req, done)) => {
+                done((self.handler)(&req));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Answer held requests, including ones sent meanwhile, until none is left.
+    pub fn release_all(&self) {
+        while self.release_one() {}
+    }
+}
+
+#[cfg(test)]
+impl HttpClient for StubHttp {
+    fn send(&self, req: HttpRequest, done: HttpCallback) {
+        self.sent.borrow_mut().push(req.clone());
+        if self.hold.get() {
+            self.held.borrow_mut().push((req, done));
+        } else {
+            done((self.handler)(&req));
+        }
+    }
+}
+
+/// A response with `status` and `body`, for stubs.
+#[cfg(test)]
+pub fn response(status: u16, body: &str) -> HttpResponse {
+    HttpResponse {
+        status,
+        body: body.to_string(),
+        ..HttpResponse::default()
+    }
+}
+
+/// `window.fetch`.
+pub struct BrowserHttp;
+
+impl HttpClient for BrowserHttp {
+    fn send(&self, req: HttpRequest, done: HttpCallback) {
+        wasm_bindgen_futures::spawn_local(async move {
+            done(fetch(req).await);
+        });
+    }
+}
+
+pub fn browser() -> Rc<dyn HttpClient> {
+    Rc::new(BrowserHttp)
+}
+
+async fn fetch(req: HttpRequest) -> anyhow::Result<HttpResponse> {
+    let window = web_sys::window().ok_or_else(|| anyhow!("no window"))?;
+
+    let init = web_sys::RequestInit::new();
+    init.set_method(&req.method);
+    if req.no_cors {
+        init.set_mode(web_sys::RequestMode::NoCors);
+    }
+    if let Some(body) = &req.body {
+        init.set_body(&JsValue::from_str(body));
+    }
+    let request = web_sys::Request::new_with_str_and_init(&req.url, &init).map_err(js_err)?;
+    for (name, value) in &req.headers {
+        request.headers().set(name, value).map_err(js_err)?;
+    }
+
+    let resp: web_sys::Response = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(js_err)?
+        .dyn_into()
+        .map_err(js_err)?;
+
+    let mut headers = Vec::new();
+    if let Ok(Some(iter)) = js_sys::try_iter(&resp.headers()) {
+        for pair in iter.flatten() {
+            let pair: js_sys::Array = pair.unchecked_into();
+            if let (Some(k), Some(v)) = (pair.get(0).as_string(), pair.get(1).as_string()) {
+                headers.push((k, v));
+            }
+        }
+    }
+
+    let body = JsFuture::from(resp.text().map_err(js_err)?)
+        .await
+        .map_err(js_err)?
+        .as_string()
+        .unwrap_or_default();
+
+    Ok(HttpResponse {
+        status: resp.status(),
+        url: resp.url(),
+        redirected: resp.redirected(),
+        headers,
+        body,
+    })
+}
+
+fn js_err(v: JsValue) -> anyhow::Error {
+    anyhow!(
+        v.as_string()
+            .or_else(|| v
+                .dyn_ref::<js_sys::Error>()
+                .map(|e| String::from(e.message())))
+            .unwrap_or_else(|| "network error".to_string())
+    )
+}
@@ -0,0 +1,95 @@
+//! OpenSearch description, so browsers can add the page as a search engine.
+//!
+//! Searches come back to the page as `?q=…&go=1` (see `deeplink`), which
+//! forwards them to the engine picked in settings. The app has no bangs
+//! (`!w`-style shortcuts to other engines), so the terms go to that one
+//! engine as typed. `build.rs` writes the description to `opensearch.xml`
+//! for the trunk build, so this module must not depend on the rest of the
+//! crate.
+
+pub const SHORT_NAME: &str = "Startpage";
+
+/// `base_url` with a trailing slash, unless it already names an HTML page.
+fn page_url(base_url: &str) -> String {
+    let base = base_url.trim();
+    if base.ends_with('/') || base.ends_with(".html") {
+        base.to_string()
+    } else {
+        format!("{base}/")
+    }
+}
+
+/// Search URL for the page at `base_url`, with `placeholder` where the terms go.
+pub fn search_template(base_url: &str, placeholder: &str) -> String {
+    format!("{}?q={placeholder}&go=1", page_url(base_url))
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// The OpenSearch 1.1 description document for the page at `base_url`,
+/// which must be absolute since browsers don't resolve relative templates.
+pub fn description(base_url: &str) -> String {
+    let template = escape_xml(&search_template(base_url, "{searchTerms}"));
+    let form = escape_xml(&page_url(base_url));
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/" xmlns:moz="http://www.mozilla.org/2006/browser/search/">
+  <ShortName>{SHORT_NAME}</ShortName>
+  <Description>Search with the engine chosen in {SHORT_NAME}, or open a typed URL</Description>
+  <InputEncoding>UTF-8</InputEncoding>
+  <Url type="text/html" method="get" template="{template}"/>
+  <moz:SearchForm>{form}</moz:SearchForm>
+</OpenSearchDescription>
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_follows_the_page_url() {
+        let cases = [
+            ("https://me.example", "https://me.example/?q=%s&go=1"),
+            ("https://me.example/", "https://me.example/?q=%s&go=1"),
+            (
+                "https://me.example/start",
+                "https://me.example/start/?q=%s&go=1",
+            ),
+            (
+                "https://me.example/start/index.html",
+                "https://me.example/start/index.html?q=%s&go=1",
+            ),
+            (
+                " http://127.0.0.1:8080/ ",
+                "http://127.0.0.1:8080/?q=%s&go=1",
+            ),
+        ];
+        for (base, want) in cases {
+            assert_eq!(search_template(base, "%s"), want, "{base}");
+        }
+    }
+
+    #[test]
+    fn description_escapes_and_places_search_terms() {
+        let xml = description("https://me.example/a&b");
+        assert!(xml.contains(r#"template="https://me.example/a&amp;b/?q={searchTerms}&amp;go=1""#));
+        assert!(xml.contains("<moz:SearchForm>https://me.example/a&amp;b/</moz:SearchForm>"));
+        assert!(!xml.contains("a&b"));
+        assert!(xml.contains("<ShortName>Startpage</ShortName>"));
+    }
+}
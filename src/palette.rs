@@ -0,0 +1,271 @@
+//! Ctrl+K command palette.
+//!
+//! Shown through the app's `OverlayHandle`. The runner can't move keyboard
+//! focus into a text field from code, so while the palette is open a
+//! capture-phase `keydown` listener on the window owns the keyboard: it builds
+//! the query, moves the selection and runs the chosen command. Ctrl/⌘+K goes
+//! through the same listener so the browser's own binding never fires.
+
+#![allow(non_snake_case)]
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use repose_core::{CursorIcon, PaddingValues, prelude::*};
+use repose_ui::overlay::OverlayHandle;
+use repose_ui::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::closure::Closure;
+
+use crate::commands::{Command, CommandRegistry};
+
+/// Rows shown at once; the rest are reachable by refining the query.
+const MAX_ROWS: usize = 10;
+
+pub struct CommandPalette {
+    overlay: OverlayHandle,
+    commands: RefCell<CommandRegistry>,
+    query: Signal<String>,
+    selected: Signal<usize>,
+    open_id: Cell<Option<u64>>,
+}
+
+impl CommandPalette {
+    pub fn new(overlay: OverlayHandle) -> Self {
+        Self {
+            overlay,
+            commands: RefCell::new(CommandRegistry::new()),
+            query: signal(String::new()),
+            selected: signal(0),
+            open_id: Cell::new(None),
+        }
+    }
+
+    /// Replace the registered commands; called once per frame.
+    pub fn set_commands(&self, registry: CommandRegistry) {
+        *self.commands.borrow_mut() = registry;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open_id.get().is_some()
+    }
+
+    pub fn open(self: &Rc<Self>) {
+        if self.is_open() {
+            return;
+        }
+        self.query.set(String::new());
+        self.selected.set(0);
+        let this = self.clone();
+        let id = self.overlay.show_builder(Rc::new(move || this.view()));
+        self.open_id.set(Some(id));
+    }
+
+    pub fn close(&self) {
+        if let Some(id) = self.open_id.take() {
+            self.overlay.dismiss(id);
+        }
+    }
+
+    pub fn toggle(self: &Rc<Self>) {
+        if self.is_open() {
+            self.close()
+        } else {
+            self.open()
+        }
+    }
+
+    fn matches(&self) -> Vec<Command> {
+        let mut found = self.commands.borrow().search(&self.query.get());
+        found.truncate(MAX_ROWS);
+        found
+    }
+
+    fn run(&self, cmd: &Command) {
+        self.close();
+        (cmd.run)();
+    }
+
+    fn edit_query(&self, f: impl FnOnce(&mut String)) {
+        self.query.update(f);
+        self.selected.set(0);
+    }
+
+    /// Handle a key press; returns whether the palette consumed it.
+    fn on_key(self: &Rc<Self>, ev: &web_sys::KeyboardEvent) -> bool {
+        let key = ev.key();
+        let chord = ev.ctrl_key() || ev.meta_key();
+        if chord && key.eq_ignore_ascii_case("k") {
+            self.toggle();
+            return true;
+        }
+        if !self.is_open() {
+            return false;
+        }
+
+        match key.as_str() {
+            "Escape" => self.close(),
+            "Enter" => {
+                if let Some(cmd) = self.matches().get(self.selected.get()) {
+                    self.run(cmd);
+                }
+            }
+            "ArrowDown" | "ArrowUp" => {
+                let n = self.matches().len().max(1);
+                let step = if key == "ArrowDown" { 1 } else { n - 1 };
+                self.selected.update(|s| *s = (*s + step) % n);
+            }
+            "Backspace" => self.edit_query(|q| {
+                q.pop();
+            }),
+            _ if !chord && key.chars().count() == 1 => self.edit_query(|q| q.push_str(&key)),
+            // Leave other chords (copy, reload, …) to the browser.
+            _ if chord => return false,
+            _ => {}
+        }
+        true
+    }
+
+    fn view(self: &Rc<Self>) -> View {
+        let query = self.query.get();
+        let matches = self.matches();
+        let selected = self.selected.get().min(matches.len().saturating_sub(1));
+
+        let rows = if matches.is_empty() {
+            vec![
+                Text("No matching commands")
+                    .size(13.0)
+                    .color(Color::from_hex("#6B7280"))
+                    .modifier(Modifier::new().padding(12.0)),
+            ]
+        } else {
+            matches
+                .into_iter()
+                .enumerate()
+                .map(|(i, cmd)| PaletteRow(&cmd, i == selected, self.clone()))
+                .collect()
+        };
+
+        Stack(Modifier::new().fill_max_size()).child((
+            Box(Modifier::new()
+                .fill_max_size()
+                .background(Color::from_hex("#000000AA"))
+                .clickable()
+                .on_pointer_down({
+                    let this = self.clone();
+                    move |_| this.close()
+                })),
+            Column(
+                Modifier::new()
+                    .fill_max_width()
+                    .align_items(AlignItems::Center)
+                    .padding_values(PaddingValues {
+                        top: 96.0,
+                        left: 16.0,
+                        right: 16.0,
+                        ..Default::default()
+                    }),
+            )
+            .child(
+                Box(Modifier::new()
+                    .fill_max_width()
+                    .max_width(560.0)
+                    .background(theme().surface)
+                    .border(1.0, theme().outline, 14.0)
+                    .clip_rounded(14.0)
+                    .padding(8.0)
+                    // Swallow clicks so they don't reach the scrim.
+                    .clickable())
+                .child(
+                    Column(Modifier::new().fill_max_width()).child((
+                        Text(if query.is_empty() {
+                            "Type a command…".to_string()
+                        } else {
+                            format!("{query}▏")
+                        })
+                        .size(16.0)
+                        .single_line()
+                        .color(if query.is_empty() {
+                            Color::from_hex("#6B7280")
+                        } else {
+                            theme().on_surface
+                        })
+                        .modifier(Modifier::new().fill_max_width().padding(12.0)),
+                        Box(Modifier::new()
+                            .fill_max_width()
+                            .height(1.0)
+                            .background(theme().outline)),
+                        Column(
+                            Modifier::new()
+                                .fill_max_width()
+                                .padding_values(PaddingValues {
+                                    top: 6.0,
+                                    ..Default::default()
+                                }),
+                        )
+                        .child(rows),
+                    )),
+                ),
+            ),
+        ))
+    }
+}
+
+fn PaletteRow(cmd: &Command, selected: bool, palette: Rc<CommandPalette>) -> View {
+    let p = theme().primary;
+    Row(Modifier::new()
+        .fill_max_width()
+        .min_height(36.0)
+        .align_items(AlignItems::Center)
+        .padding_values(PaddingValues {
+            left: 12.0,
+            right: 12.0,
+            ..Default::default()
+        })
+        .background(if selected {
+            Color(p.0, p.1, p.2, 48)
+        } else {
+            Color(0, 0, 0, 0)
+        })
+        .clip_rounded(8.0)
+        .clickable()
+        .on_pointer_down({
+            let cmd = cmd.clone();
+            move |_| palette.run(&cmd)
+        })
+        .cursor(CursorIcon::Pointer))
+    .child((
+        Text(cmd.title.clone())
+            .size(14.0)
+            .single_line()
+            .overflow_ellipsize()
+            .color(theme().on_surface)
+            .modifier(Modifier::new().weight(1.0).min_width(0.0)),
+        Text(cmd.section)
+            .size(12.0)
+            .single_line()
+            .color(Color::from_hex("#6B7280")),
+    ))
+}
+
+/// Route window key presses to the palette. Call once.
+pub fn install_keyboard(palette: Rc<CommandPalette>) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let listener =
+        Closure::<dyn Fn(web_sys::KeyboardEvent)>::new(move |ev: web_sys::KeyboardEvent| {
+            if palette.on_key(&ev) {
+                ev.prevent_default();
+                ev.stop_propagation();
+            }
+        });
+    // Capture phase, so this runs before the canvas sees the key.
+    let _ = window.add_event_listener_with_callback_and_bool(
+        "keydown",
+        listener.as_ref().unchecked_ref(),
+        true,
+    );
+    // Lives as long as the page.
+    listener.forget();
+}
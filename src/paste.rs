@@ -0,0 +1,224 @@
+//! Bookmarks from pasted or dropped links.
+//!
+//! Links dragged from another tab are taken anywhere on the page. Ctrl/⌘+V is
+//! only taken when the runner has nothing focused or the mouse is over the
+//! bookmark grid, so a focused text field elsewhere keeps its paste. The
+//! runner swallows the DOM `paste` event, so the text is read with the async
+//! Clipboard API instead.
+
+use std::cell::{Cell, RefCell};
+
+use repose_core::Rect;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::links;
+use crate::urls::{self, Classified, UrlPolicy};
+
+/// Path segments kept in a title made from a URL.
+const TITLE_SEGMENTS: usize = 3;
+
+/// Links found in some pasted or dropped text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Found {
+    pub urls: Vec<String>,
+    /// Links the URL policy turned down.
+    pub rejected: usize,
+}
+
+/// The links in `text`: one per line for `text/uri-list` (where `#` starts a
+/// comment), otherwise every word the search box would open as a URL.
+pub fn find_urls(text: &str, intranet_hosts: &[String], policy: &UrlPolicy) -> Found {
+    let mut found = Found::default();
+    let words = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(str::split_whitespace);
+    for word in words {
+        // Punctuation around a link in running text isn't part of it.
+        let word = word.trim_matches(|c: char| matches!(c, '<' | '>' | '"' | '\'' | ',' | ';'));
+        let Classified::Url(url) = urls::classify(word, intranet_hosts) else {
+            continue;
+        };
+        if policy.check(&url).is_err() {
+            found.rejected += 1;
+        } else if !found.urls.contains(&url) {
+            found.urls.push(url);
+        }
+    }
+    found
+}
+
+/// A readable title for `url`: host without `www.` plus the first few path
+/// segments, e.g. `github.com › rust-lang › rust`.
+pub fn title_for_url(url: &str) -> String {
+    let Some((_, rest)) = url.split_once("://") else {
+        // `mailto:someone@example.com` and friends: drop the scheme.
+        let rest = url.split_once(':').map_or(url, |(_, rest)| rest);
+        return rest.split('?').next().unwrap_or(rest).to_string();
+    };
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let s = urlencoding::decode(s).map_or_else(|_| s.to_string(), |d| d.into_owned());
+            match s.rsplit_once('.') {
+                Some((stem, "html" | "htm" | "php" | "asp" | "aspx")) => stem.to_string(),
+                _ => s,
+            }
+        })
+        .collect();
+    let mut parts = vec![host.to_string()];
+    parts.extend(segments.iter().take(TITLE_SEGMENTS).cloned());
+    if segments.len() > TITLE_SEGMENTS {
+        parts.push("…".to_string());
+    }
+    parts.retain(|p| !p.is_empty());
+    if parts.is_empty() {
+        url.to_string()
+    } else {
+        parts.join(" › ")
+    }
+}
+
+thread_local! {
+    /// Whether pastes and drops are taken; off while a panel or dialog is open.
+    static ACCEPTING: Cell<bool> = const { Cell::new(true) };
+    /// Whether the runner has keyboard focus on something.
+    static FOCUSED: Cell<bool> = const { Cell::new(false) };
+    /// Rects (px) of the bookmark grid, recorded while painting.
+    static GRID: RefCell<Vec<Rect>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Forget last frame's grid. Call before the grid is painted.
+pub fn begin_frame(accepting: bool, focused: bool) {
+    ACCEPTING.with(|a| a.set(accepting));
+    FOCUSED.with(|f| f.set(focused));
+    GRID.with(|g| g.borrow_mut().clear());
+}
+
+/// Record part of the bookmark grid (px), where a paste always makes a bookmark.
+pub fn register_grid(rect: Rect) {
+    GRID.with(|g| g.borrow_mut().push(rect));
+}
+
+fn takes_paste() -> bool {
+    if !ACCEPTING.with(Cell::get) {
+        return false;
+    }
+    let over_grid = links::pointer().is_some_and(|(x, y)| {
+        GRID.with(|g| {
+            g.borrow()
+                .iter()
+                .any(|r| x >= r.x && x < r.x + r.w && y >= r.y && y < r.y + r.h)
+        })
+    });
+    over_grid || !FOCUSED.with(Cell::get)
+}
+
+/// Whether a drag carries links or text rather than files.
+fn has_links(ev: &web_sys::DragEvent) -> bool {
+    ev.data_transfer().is_some_and(|dt| {
+        dt.types()
+            .iter()
+            .filter_map(|t| t.as_string())
+            .any(|t| t == "text/uri-list" || t == "text/plain")
+    })
+}
+
+fn read_clipboard(on_text: impl FnOnce(String) + 'static) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let navigator = window.navigator();
+    // `navigator.clipboard` is missing entirely on insecure origins.
+    let available = js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard"))
+        .is_ok_and(|c| !c.is_undefined());
+    if !available {
+        log::warn!("paste: the clipboard can't be read on this page");
+        return;
+    }
+    let read = navigator.clipboard().read_text();
+    wasm_bindgen_futures::spawn_local(async move {
+        match JsFuture::from(read).await {
+            Ok(text) => on_text(text.as_string().unwrap_or_default()),
+            Err(e) => log::warn!("paste: {e:?}"),
+        }
+    });
+}
+
+/// Hand pasted and dropped text to `on_text`. Call once.
+pub fn install_listeners(on_text: impl Fn(String) + 'static) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let on_text = std::rc::Rc::new(on_text);
+
+    let on_key = Closure::<dyn Fn(web_sys::KeyboardEvent)>::new({
+        let on_text = on_text.clone();
+        move |ev: web_sys::KeyboardEvent| {
+            let chord = ev.ctrl_key() || ev.meta_key();
+            if chord && !ev.shift_key() && ev.key().eq_ignore_ascii_case("v") && takes_paste() {
+                ev.prevent_default();
+                ev.stop_propagation();
+                let on_text = on_text.clone();
+                read_clipboard(move |text| on_text(text));
+            }
+        }
+    });
+    // Capture phase, so this runs before the canvas sees the key.
+    let _ = window.add_event_listener_with_callback_and_bool(
+        "keydown",
+        on_key.as_ref().unchecked_ref(),
+        true,
+    );
+    // Lives as long as the page.
+    on_key.forget();
+
+    // Without this the browser won't allow a drop at all.
+    let on_drag_over = Closure::<dyn Fn(web_sys::DragEvent)>::new(move |ev: web_sys::DragEvent| {
+        if ACCEPTING.with(Cell::get) && has_links(&ev) {
+            ev.prevent_default();
+            if let Some(dt) = ev.data_transfer() {
+                dt.set_drop_effect("copy");
+            }
+        }
+    });
+    let _ = window.add_event_listener_with_callback_and_bool(
+        "dragover",
+        on_drag_over.as_ref().unchecked_ref(),
+        true,
+    );
+    on_drag_over.forget();
+
+    let on_drop = Closure::<dyn Fn(web_sys::DragEvent)>::new(move |ev: web_sys::DragEvent| {
+        // Files are left to the runner.
+        if !ACCEPTING.with(Cell::get) || !has_links(&ev) {
+            return;
+        }
+        let Some(dt) = ev.data_transfer() else {
+            return;
+        };
+        ev.prevent_default();
+        ev.stop_propagation();
+        let text = dt
+            .get_data("text/uri-list")
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+            .or_else(|| dt.get_data("text/plain").ok())
+            .unwrap_or_default();
+        on_text(text);
+    });
+    let _ = window.add_event_listener_with_callback_and_bool(
+        "drop",
+        on_drop.as_ref().unchecked_ref(),
+        true,
+    );
+    on_drop.forget();
+}
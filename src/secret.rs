@@ -0,0 +1,124 @@
+//! Asking for a passphrase or password without showing it.
+//!
+//! The canvas draws text fields in the clear, so secrets are typed into a
+//! small DOM dialog with a password input instead, like the copy fallback in
+//! `clipboard`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+
+const DIALOG_ID: &str = "startpage-secret";
+
+const DIALOG_STYLE: &str = "position:fixed;left:50%;top:30%;transform:translateX(-50%);\
+     z-index:10;width:min(400px,90vw);padding:16px;border-radius:12px;\
+     background:#111827;color:#E5E7EB;border:1px solid #374151;\
+     font:14px system-ui,sans-serif;box-shadow:0 10px 30px #0008";
+const INPUT_STYLE: &str = "box-sizing:border-box;width:100%;height:40px;margin:8px 0 12px;\
+     padding:8px;border-radius:8px;border:1px solid #374151;background:#0F172A;\
+     color:#E5E7EB;font:inherit";
+const BUTTON_STYLE: &str = "float:right;margin-left:8px;padding:6px 14px;border-radius:8px;\
+     border:0;background:#2563EB;color:#fff;font:inherit;cursor:pointer";
+const CANCEL_STYLE: &str = "float:right;padding:6px 14px;border-radius:8px;\
+     border:1px solid #374151;background:transparent;color:#E5E7EB;font:inherit;cursor:pointer";
+
+type Done = Rc<RefCell<Option<Box<dyn FnOnce(String)>>>>;
+
+type Listener = Closure<dyn Fn(web_sys::Event)>;
+
+thread_local! {
+    /// Listeners of the open dialog; dropped when it closes.
+    static LISTENERS: RefCell<Vec<Listener>> = RefCell::default();
+}
+
+/// Ask for a secret under `title`; `done` gets what was entered, unless the
+/// dialog is cancelled. An empty entry is passed on too.
+pub fn ask(title: &str, done: impl FnOnce(String) + 'static) {
+    close_dialog();
+    if let Err(e) = build_dialog(title, Rc::new(RefCell::new(Some(Box::new(done))))) {
+        log::warn!("secret dialog: {e:?}");
+    }
+}
+
+fn close_dialog() {
+    if let Some(dialog) = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(DIALOG_ID))
+    {
+        dialog.remove();
+    }
+    // Dropped after the handler that closed the dialog returns.
+    drop(LISTENERS.take());
+}
+
+fn submit(input: &web_sys::HtmlInputElement, done: &Done) {
+    let value = input.value();
+    // Not left in the page once it's been handed over.
+    input.set_value("");
+    let done = done.borrow_mut().take();
+    close_dialog();
+    if let Some(done) = done {
+        done(value);
+    }
+}
+
+fn build_dialog(title: &str, done: Done) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+    let body = document
+        .body()
+        .ok_or_else(|| JsValue::from_str("no body"))?;
+
+    let dialog = document.create_element("div")?;
+    dialog.set_id(DIALOG_ID);
+    dialog.set_attribute("role", "dialog")?;
+    dialog.set_attribute("style", DIALOG_STYLE)?;
+
+    let label = document.create_element("div")?;
+    label.set_text_content(Some(title));
+    let input: web_sys::HtmlInputElement = document.create_element("input")?.dyn_into()?;
+    input.set_type("password");
+    input.set_attribute("autocomplete", "off")?;
+    input.set_attribute("aria-label", title)?;
+    input.set_attribute("style", INPUT_STYLE)?;
+    let ok = document.create_element("button")?;
+    ok.set_text_content(Some("OK"));
+    ok.set_attribute("style", BUTTON_STYLE)?;
+    let cancel = document.create_element("button")?;
+    cancel.set_text_content(Some("Cancel"));
+    cancel.set_attribute("style", CANCEL_STYLE)?;
+
+    dialog.append_child(&label)?;
+    dialog.append_child(&input)?;
+    dialog.append_child(&ok)?;
+    dialog.append_child(&cancel)?;
+    body.append_child(&dialog)?;
+    input.focus()?;
+
+    let on_ok = Listener::new({
+        let (input, done) = (input.clone(), done.clone());
+        move |_| submit(&input, &done)
+    });
+    ok.add_event_listener_with_callback("click", on_ok.as_ref().unchecked_ref())?;
+
+    let on_cancel = Listener::new(|_| close_dialog());
+    cancel.add_event_listener_with_callback("click", on_cancel.as_ref().unchecked_ref())?;
+
+    let on_key = Listener::new(move |ev: web_sys::Event| {
+        if let Some(key) = ev.dyn_ref::<web_sys::KeyboardEvent>() {
+            match key.key().as_str() {
+                "Enter" => submit(&input, &done),
+                "Escape" => close_dialog(),
+                _ => {}
+            }
+        }
+        // Keys typed here are not for the canvas.
+        ev.stop_propagation();
+    });
+    dialog.add_event_listener_with_callback("keydown", on_key.as_ref().unchecked_ref())?;
+    LISTENERS.set(vec![on_ok, on_cancel, on_key]);
+    Ok(())
+}
@@ -0,0 +1,266 @@
+//! Multi-select on the bookmark grid and the bulk edits applied to a selection.
+//!
+//! Bookmarks are identified by URL, as everywhere else in the app.
+
+use crate::storage::Bookmark;
+
+/// Bookmarks picked in multi-select mode, in the order they were picked.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Selection {
+    urls: Vec<String>,
+}
+
+impl Selection {
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.urls.iter().any(|u| u == url)
+    }
+
+    pub fn toggle(&mut self, url: &str) {
+        match self.urls.iter().position(|u| u == url) {
+            Some(i) => {
+                self.urls.remove(i);
+            }
+            None => self.urls.push(url.to_string()),
+        }
+    }
+
+    /// Select every tile between the last picked one and `url`, in grid
+    /// `order`. With nothing picked yet this just picks `url`.
+    pub fn extend_to(&mut self, order: &[String], url: &str) {
+        let from = self
+            .urls
+            .last()
+            .and_then(|last| order.iter().position(|u| u == last));
+        let to = order.iter().position(|u| u == url);
+        let (Some(from), Some(to)) = (from, to) else {
+            if !self.contains(url) {
+                self.urls.push(url.to_string());
+            }
+            return;
+        };
+        let range: Vec<&String> = if from <= to {
+            order[from..=to].iter().collect()
+        } else {
+            order[to..=from].iter().rev().collect()
+        };
+        for u in range {
+            if !self.contains(u) {
+                self.urls.push(u.clone());
+            }
+        }
+    }
+
+    pub fn select_all(&mut self, order: &[String]) {
+        for u in order {
+            if !self.contains(u) {
+                self.urls.push(u.clone());
+            }
+        }
+    }
+}
+
+/// Bookmarks of `group` in the order their tiles are shown: pinned ones
+/// first, otherwise in list order.
+pub fn group_members<'a>(bookmarks: &'a [Bookmark], group: &str) -> Vec<&'a Bookmark> {
+    let mut members: Vec<&Bookmark> = bookmarks.iter().filter(|bm| bm.group == group).collect();
+    members.sort_by_key(|bm| !bm.pinned);
+    members
+}
+
+/// URLs in the order the grid shows them: groups in first-seen order, then
+/// `group_members` order within each group.
+pub fn grid_order(bookmarks: &[Bookmark]) -> Vec<String> {
+    let mut groups: Vec<&str> = Vec::new();
+    for bm in bookmarks {
+        if !groups.contains(&bm.group.as_str()) {
+            groups.push(&bm.group);
+        }
+    }
+    groups
+        .into_iter()
+        .flat_map(|g| group_members(bookmarks, g))
+        .map(|bm| bm.url.clone())
+        .collect()
+}
+
+/// A bulk delete, kept so it can be undone.
+#[derive(Clone, Debug, Default)]
+pub struct Removed {
+    /// Position each bookmark had, ascending.
+    entries: Vec<(usize, Bookmark)>,
+}
+
+impl Removed {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Remove the bookmarks with `urls` and return them for `restore`.
+pub fn remove(bookmarks: &mut Vec<Bookmark>, urls: &[String]) -> Removed {
+    let mut entries = Vec::new();
+    let mut index = 0;
+    bookmarks.retain(|bm| {
+        let keep = !urls.contains(&bm.url);
+        if !keep {
+            entries.push((index, bm.clone()));
+        }
+        index += 1;
+        keep
+    });
+    Removed { entries }
+}
+
+/// Put removed bookmarks back where they were, skipping URLs that have been
+/// added again since.
+pub fn restore(bookmarks: &mut Vec<Bookmark>, removed: Removed) {
+    for (at, bm) in removed.entries {
+        if bookmarks.iter().any(|b| b.url == bm.url) {
+            continue;
+        }
+        bookmarks.insert(at.min(bookmarks.len()), bm);
+    }
+}
+
+/// Move the bookmarks with `urls` to `group`, after the bookmarks already in it.
+pub fn move_to_group(bookmarks: &mut Vec<Bookmark>, urls: &[String], group: &str) {
+    let (mut moved, rest): (Vec<Bookmark>, Vec<Bookmark>) = std::mem::take(bookmarks)
+        .into_iter()
+        .partition(|bm| urls.contains(&bm.url));
+    *bookmarks = rest;
+    for bm in &mut moved {
+        bm.group = group.to_string();
+    }
+    let at = bookmarks
+        .iter()
+        .rposition(|bm| bm.group == group)
+        .map_or(bookmarks.len(), |i| i + 1);
+    bookmarks.splice(at..at, moved);
+}
+
+/// `raw` as a tag: trimmed, without a leading `#`. `None` when nothing is left.
+pub fn clean_tag(raw: &str) -> Option<String> {
+    let tag = raw.trim().trim_start_matches('#').trim();
+    (!tag.is_empty()).then(|| tag.to_string())
+}
+
+/// Add `tag` to the bookmarks with `urls`, unless they have it in any case.
+pub fn add_tag(bookmarks: &mut [Bookmark], urls: &[String], tag: &str) {
+    for bm in bookmarks.iter_mut().filter(|bm| urls.contains(&bm.url)) {
+        if !bm.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            bm.tags.push(tag.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bookmark titled `name` at `https://<name>/` in `group`.
+    fn bm(name: &str, group: &str) -> Bookmark {
+        Bookmark {
+            title: name.to_string(),
+            url: url(name),
+            group: group.to_string(),
+            health: None,
+            open_in: None,
+            tags: Vec::new(),
+            pinned: false,
+        }
+    }
+
+    fn url(name: &str) -> String {
+        format!("https://{name}/")
+    }
+
+    fn urls(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| url(n)).collect()
+    }
+
+    fn titles(list: &[Bookmark]) -> Vec<&str> {
+        list.iter().map(|bm| bm.title.as_str()).collect()
+    }
+
+    #[test]
+    fn extend_to_walks_backwards_too() {
+        let order = urls(&["a", "b", "c", "d", "e"]);
+        let mut sel = Selection::default();
+        sel.toggle(&url("d"));
+        sel.extend_to(&order, &url("b"));
+        assert_eq!(sel.urls(), urls(&["d", "c", "b"]));
+
+        sel.extend_to(&order, &url("e"));
+        assert_eq!(sel.urls(), urls(&["d", "c", "b", "e"]));
+    }
+
+    #[test]
+    fn extend_to_with_nothing_picked_picks_one() {
+        let order = urls(&["a", "b", "c"]);
+        let mut sel = Selection::default();
+        sel.extend_to(&order, &url("b"));
+        assert_eq!(sel.urls(), urls(&["b"]));
+    }
+
+    #[test]
+    fn restore_puts_bookmarks_back_in_place() {
+        let original = vec![bm("a", ""), bm("b", ""), bm("c", ""), bm("d", "")];
+        let mut list = original.clone();
+        let removed = remove(&mut list, &urls(&["d", "a", "c"]));
+        assert_eq!(removed.len(), 3);
+        assert_eq!(titles(&list), ["b"]);
+
+        restore(&mut list, removed);
+        assert_eq!(list, original);
+    }
+
+    #[test]
+    fn restore_skips_bookmarks_added_again() {
+        let mut list = vec![bm("a", ""), bm("b", ""), bm("c", "")];
+        let removed = remove(&mut list, &urls(&["a", "c"]));
+        let mut again = bm("c", "");
+        again.title = "c again".to_string();
+        list.push(again);
+
+        restore(&mut list, removed);
+        assert_eq!(titles(&list), ["a", "b", "c again"]);
+    }
+
+    #[test]
+    fn moved_bookmarks_follow_the_groups_last_member() {
+        let mut list = vec![
+            bm("a", "work"),
+            bm("b", ""),
+            bm("c", "work"),
+            bm("d", ""),
+            bm("e", "news"),
+        ];
+        move_to_group(&mut list, &urls(&["b", "e"]), "work");
+        assert_eq!(titles(&list), ["a", "c", "b", "e", "d"]);
+        assert!(list[..4].iter().all(|bm| bm.group == "work"));
+
+        move_to_group(&mut list, &urls(&["a"]), "new");
+        assert_eq!(titles(&list), ["c", "b", "e", "d", "a"]);
+        assert_eq!(list[4].group, "new");
+    }
+
+    #[test]
+    fn tags_are_added_once_ignoring_case() {
+        let mut list = vec![bm("a", ""), bm("b", "")];
+        list[0].tags.push("Read".to_string());
+        add_tag(&mut list, &urls(&["a", "b"]), "read");
+        assert_eq!(list[0].tags, ["Read"]);
+        assert_eq!(list[1].tags, ["read"]);
+
+        assert_eq!(clean_tag("  #later "), Some("later".to_string()));
+        assert_eq!(clean_tag(" # "), None);
+    }
+}
@@ -0,0 +1,257 @@
+//! User settings: one persisted model behind the settings drawer.
+
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::links::LinkTarget;
+use crate::storage::{self, ThemeChoice, TileStyle};
+use crate::urls::{self, UrlPolicy};
+
+const KEY: &str = "startpage.settings.v1";
+/// Theme was stored on its own before settings existed.
+const LEGACY_THEME_KEY: &str = "startpage.theme.v1";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SearchEngine {
+    #[default]
+    DuckDuckGo,
+    Google,
+    Brave,
+    /// `Settings::custom_search_url`.
+    Custom,
+}
+
+impl SearchEngine {
+    pub const ALL: [SearchEngine; 4] = [
+        SearchEngine::DuckDuckGo,
+        SearchEngine::Google,
+        SearchEngine::Brave,
+        SearchEngine::Custom,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchEngine::DuckDuckGo => "DuckDuckGo",
+            SearchEngine::Google => "Google",
+            SearchEngine::Brave => "Brave",
+            SearchEngine::Custom => "Custom",
+        }
+    }
+
+    /// Engine by label, ignoring case, spaces and punctuation (`duckduckgo`,
+    /// `Brave`, …). `ddg` is accepted as well.
+    pub fn from_name(name: &str) -> Option<SearchEngine> {
+        let name: String = name
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+        if name == "ddg" {
+            return Some(SearchEngine::DuckDuckGo);
+        }
+        Self::ALL
+            .into_iter()
+            .find(|e| e.label().to_lowercase() == name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub engine: SearchEngine,
+    /// Search URL template for `SearchEngine::Custom`; `%s` is replaced by the query.
+    pub custom_search_url: String,
+    /// Tile style of groups that haven't been switched explicitly.
+    pub default_tile_style: TileStyle,
+    /// Show the URL under the title on list tiles.
+    pub show_tile_urls: bool,
+    /// Minimum width of a list tile in dp; decides how many columns fit.
+    pub tile_min_width: u32,
+    pub theme: ThemeChoice,
+    /// Maximum width of the page content in dp.
+    pub content_width: u32,
+    /// Single-label hosts (`wiki`, `jira`) that the search box opens instead of searching.
+    pub intranet_hosts: Vec<String>,
+    /// Link schemes allowed besides http and https, e.g. `ftp` or `mailto`.
+    pub extra_schemes: Vec<String>,
+    /// Where bookmarks and searches open unless a bookmark or a modifier key says otherwise.
+    pub link_target: LinkTarget,
+    /// Look up the page title of bookmarks added without a title.
+    pub lookup_titles: bool,
+    /// Proxy for title lookups; the URL-encoded page URL is appended to it.
+    /// Empty fetches the page directly, which only works where CORS allows it.
+    pub metadata_proxy: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            engine: SearchEngine::DuckDuckGo,
+            custom_search_url: String::new(),
+            default_tile_style: TileStyle::List,
+            show_tile_urls: true,
+            tile_min_width: 200,
+            theme: ThemeChoice::Midnight,
+            content_width: 900,
+            intranet_hosts: Vec::new(),
+            extra_schemes: Vec::new(),
+            link_target: LinkTarget::NewTab,
+            lookup_titles: true,
+            metadata_proxy: String::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub const TILE_MIN_WIDTH: RangeInclusive<u32> = 160..=320;
+    pub const CONTENT_WIDTH: RangeInclusive<u32> = 600..=1400;
+
+    /// Search URL for `query` with the configured engine.
+    pub fn search_url(&self, query: &str) -> String {
+        let q = urlencoding::encode(query.trim());
+        match self.engine {
+            SearchEngine::DuckDuckGo => format!("https://duckduckgo.com/?q={q}"),
+            SearchEngine::Google => format!("https://www.google.com/search?q={q}"),
+            SearchEngine::Brave => format!("https://search.brave.com/search?q={q}"),
+            SearchEngine::Custom => self.custom_search_url.replace("%s", &q),
+        }
+    }
+
+    /// Every problem with these settings, one message per field.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !Self::TILE_MIN_WIDTH.contains(&self.tile_min_width) {
+            problems.push(format!(
+                "Tile width must be between {} and {}",
+                Self::TILE_MIN_WIDTH.start(),
+                Self::TILE_MIN_WIDTH.end()
+            ));
+        }
+        if !Self::CONTENT_WIDTH.contains(&self.content_width) {
+            problems.push(format!(
+                "Content width must be between {} and {}",
+                Self::CONTENT_WIDTH.start(),
+                Self::CONTENT_WIDTH.end()
+            ));
+        }
+        if (self.engine == SearchEngine::Custom || !self.custom_search_url.is_empty())
+            && let Err(e) = validate_search_template(&self.custom_search_url)
+        {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = validate_proxy(&self.metadata_proxy) {
+            problems.push(e.to_string());
+        }
+        for host in &self.intranet_hosts {
+            if !valid_intranet_host(host) {
+                problems.push(format!("\"{host}\" is not a single-word host name"));
+            }
+        }
+        for scheme in &self.extra_schemes {
+            if !urls::valid_extra_scheme(scheme) {
+                problems.push(format!("\"{scheme}\" can't be allowed as a link scheme"));
+            }
+        }
+        problems
+    }
+
+    /// Clamp numbers into range and drop an unusable custom search URL or
+    /// title lookup proxy, invalid intranet hosts and schemes that can't be
+    /// allowed.
+    pub fn sanitized(mut self) -> Self {
+        let d = Settings::default();
+        self.tile_min_width = self
+            .tile_min_width
+            .clamp(*Self::TILE_MIN_WIDTH.start(), *Self::TILE_MIN_WIDTH.end());
+        self.content_width = self
+            .content_width
+            .clamp(*Self::CONTENT_WIDTH.start(), *Self::CONTENT_WIDTH.end());
+        if validate_search_template(&self.custom_search_url).is_err() {
+            self.custom_search_url = String::new();
+            if self.engine == SearchEngine::Custom {
+                self.engine = d.engine;
+            }
+        }
+        if validate_proxy(&self.metadata_proxy).is_err() {
+            self.metadata_proxy = String::new();
+        }
+        self.intranet_hosts.retain(|h| valid_intranet_host(h));
+        self.extra_schemes.retain(|s| urls::valid_extra_scheme(s));
+        self
+    }
+
+    pub fn url_policy(&self) -> UrlPolicy {
+        UrlPolicy::new(&self.extra_schemes)
+    }
+}
+
+/// A custom search URL must be http(s) and contain `%s` for the query.
+pub fn validate_search_template(url: &str) -> Result<(), &'static str> {
+    let url = url.trim();
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err("Search URL must start with https:// or http://");
+    }
+    if !url.contains("%s") {
+        return Err("Search URL must contain %s where the query goes");
+    }
+    Ok(())
+}
+
+/// A title lookup proxy is empty, a same-origin path or an http(s) URL.
+pub fn validate_proxy(proxy: &str) -> Result<(), &'static str> {
+    let proxy = proxy.trim();
+    if proxy.is_empty()
+        || proxy.starts_with('/')
+        || proxy.starts_with("https://")
+        || proxy.starts_with("http://")
+    {
+        Ok(())
+    } else {
+        Err("Title lookup proxy must start with /, https:// or http://")
+    }
+}
+
+fn valid_intranet_host(host: &str) -> bool {
+    !host.is_empty()
+        && !host.starts_with('-')
+        && !host.ends_with('-')
+        && host.chars().all(|c| c.is_alphanumeric() || c == '-')
+}
+
+/// Entries of a comma- or space-separated list, lowercased, with invalid and
+/// repeated ones dropped.
+fn parse_list(raw: &str, valid: impl Fn(&str) -> bool) -> Vec<String> {
+    let mut items: Vec<String> = Vec::new();
+    for item in raw.split([',', ' ']).map(|h| h.trim().to_lowercase()) {
+        if valid(&item) && !items.contains(&item) {
+            items.push(item);
+        }
+    }
+    items
+}
+
+pub fn parse_host_list(raw: &str) -> Vec<String> {
+    parse_list(raw, valid_intranet_host)
+}
+
+/// Extra link schemes from a list such as `ftp, mailto:`.
+pub fn parse_scheme_list(raw: &str) -> Vec<String> {
+    parse_list(&raw.replace(':', ""), urls::valid_extra_scheme)
+}
+
+pub fn load() -> Settings {
+    let stored: Option<Settings> = storage::load_json(KEY);
+    let settings = stored.unwrap_or_else(|| Settings {
+        theme: storage::load_json(LEGACY_THEME_KEY),
+        ..Settings::default()
+    });
+    for problem in settings.validate() {
+        log::warn!("settings: {problem}; using a valid value instead");
+    }
+    settings.sanitized()
+}
+
+pub fn save(settings: &Settings) {
+    storage::save_json(KEY, settings)
+}
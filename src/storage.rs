@@ -1,32 +1,500 @@
-use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::dashboards::{self, Dashboard};
+use crate::health::HealthCheck;
+use crate::links::LinkTarget;
+use crate::settings::{self, Settings};
+use crate::urls::{Rejection, UrlPolicy};
 
 const KEY: &str = "startpage.bookmarks.v1";
+const TILE_STYLES_KEY: &str = "startpage.tile_styles.v1";
+/// Prefix of every key the app writes.
+const APP_KEY_PREFIX: &str = "startpage.";
+/// Prefix shared by the widget layout and every widget config key.
+const WIDGET_KEY_PREFIX: &str = "startpage.widget";
+
+pub const EXPORT_VERSION: u32 = 1;
+
+thread_local! {
+    /// Dashboard that `scoped` keys belong to; set by the app every frame.
+    static DASHBOARD: RefCell<String> = RefCell::new(dashboards::HOME.to_string());
+}
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Bookmark {
     pub title: String,
     pub url: String,
+    /// Group the bookmark is listed under; empty means the default group.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub group: String,
+    /// Optional periodic status probe, shown as a dot on the tile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<HealthCheck>,
+    /// Where this bookmark opens; `None` follows the setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_in: Option<LinkTarget>,
+    /// Free-form labels, shown on list tiles; the palette opens a tag's bookmarks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Listed before the other bookmarks of its group.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+}
+
+/// How the tiles of a group are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TileStyle {
+    /// Title plus URL in a wide row.
+    #[default]
+    List,
+    /// Large square tile with a monogram and the title underneath.
+    SpeedDial,
 }
 
+/// Color scheme of the whole page.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeChoice {
+    #[default]
+    Midnight,
+    Graphite,
+    Forest,
+}
+
+impl ThemeChoice {
+    pub const ALL: [ThemeChoice; 3] = [
+        ThemeChoice::Midnight,
+        ThemeChoice::Graphite,
+        ThemeChoice::Forest,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeChoice::Midnight => "Midnight",
+            ThemeChoice::Graphite => "Graphite",
+            ThemeChoice::Forest => "Forest",
+        }
+    }
+}
+
+#[cfg(not(test))]
 fn storage() -> Option<web_sys::Storage> {
     web_sys::window()?.local_storage().ok().flatten()
 }
 
-pub fn load_bookmarks() -> Vec<Bookmark> {
+/// Tests run natively, without `localStorage`; each test thread starts with
+/// an empty map in its place.
+#[cfg(test)]
+fn storage() -> Option<MemoryStorage> {
+    Some(MemoryStorage)
+}
+
+#[cfg(test)]
+thread_local! {
+    static MEMORY: RefCell<BTreeMap<String, String>> = RefCell::default();
+}
+
+/// Trade the in-memory storage for `other`, so one test can play two browsers.
+#[cfg(test)]
+pub fn swap_memory(other: &mut BTreeMap<String, String>) {
+    MEMORY.with(|m| std::mem::swap(&mut *m.borrow_mut(), other))
+}
+
+/// The parts of the `web_sys::Storage` API used here.
+#[cfg(test)]
+struct MemoryStorage;
+
+#[cfg(test)]
+impl MemoryStorage {
+    fn get_item(&self, key: &str) -> Result<Option<String>, ()> {
+        Ok(MEMORY.with(|m| m.borrow().get(key).cloned()))
+    }
+
+    fn set_item(&self, key: &str, value: &str) -> Result<(), ()> {
+        MEMORY.with(|m| m.borrow_mut().insert(key.to_string(), value.to_string()));
+        Ok(())
+    }
+
+    fn remove_item(&self, key: &str) -> Result<(), ()> {
+        MEMORY.with(|m| m.borrow_mut().remove(key));
+        Ok(())
+    }
+
+    fn length(&self) -> Result<u32, ()> {
+        Ok(MEMORY.with(|m| m.borrow().len() as u32))
+    }
+
+    fn key(&self, index: u32) -> Result<Option<String>, ()> {
+        Ok(MEMORY.with(|m| m.borrow().keys().nth(index as usize).cloned()))
+    }
+}
+
+/// Read and deserialize `key`, falling back to `T::default()` when missing or malformed.
+pub fn load_json<T: DeserializeOwned + Default>(key: &str) -> T {
     let Some(st) = storage() else {
-        return vec![];
+        return T::default();
     };
-    let Ok(Some(raw)) = st.get_item(KEY) else {
-        return vec![];
+    let Ok(Some(raw)) = st.get_item(key) else {
+        return T::default();
     };
     serde_json::from_str(&raw).unwrap_or_default()
 }
 
+/// Like `load_json`, but a value that can't be read is an error rather than
+/// the default. It is copied to `<key>.unreadable` first, since the next save
+/// would overwrite it.
+pub fn try_load_json<T: DeserializeOwned + Default>(key: &str) -> anyhow::Result<T> {
+    let Some(st) = storage() else {
+        return Ok(T::default());
+    };
+    let Ok(Some(raw)) = st.get_item(key) else {
+        return Ok(T::default());
+    };
+    serde_json::from_str(&raw).map_err(|e| {
+        let backup = format!("{key}.unreadable");
+        let _ = st.set_item(&backup, &raw);
+        anyhow::anyhow!("{key} can't be read ({e}); the stored copy was kept as {backup}")
+    })
+}
+
+pub fn save_json<T: Serialize + ?Sized>(key: &str, value: &T) {
+    let Some(st) = storage() else {
+        return;
+    };
+    if let Ok(raw) = serde_json::to_string(value) {
+        let _ = st.set_item(key, &raw);
+    }
+}
+
+pub fn set_dashboard(id: &str) {
+    DASHBOARD.with(|d| *d.borrow_mut() = id.to_string())
+}
+
+/// `key` for dashboard `id`. The home dashboard uses the bare key.
+pub fn dashboard_key(key: &str, id: &str) -> String {
+    if id == dashboards::HOME {
+        key.to_string()
+    } else {
+        format!("{key}@{id}")
+    }
+}
+
+/// Id of the active dashboard.
+pub fn dashboard() -> String {
+    DASHBOARD.with(|d| d.borrow().clone())
+}
+
+/// `key` for the active dashboard.
+pub fn scoped(key: &str) -> String {
+    DASHBOARD.with(|d| dashboard_key(key, &d.borrow()))
+}
+
+/// Remove every key stored for dashboard `id`; the home dashboard is never removed.
+pub fn remove_dashboard_data(id: &str) {
+    let Some(st) = storage() else {
+        return;
+    };
+    if id == dashboards::HOME {
+        return;
+    }
+    let suffix = format!("@{id}");
+    let len = st.length().unwrap_or(0);
+    let keys: Vec<String> = (0..len)
+        .filter_map(|i| st.key(i).ok().flatten())
+        .filter(|k| k.starts_with(APP_KEY_PREFIX) && k.ends_with(&suffix))
+        .collect();
+    for key in keys {
+        let _ = st.remove_item(&key);
+    }
+}
+
+pub fn load_bookmarks() -> anyhow::Result<Vec<Bookmark>> {
+    try_load_json(&scoped(KEY))
+}
+
 pub fn save_bookmarks(items: &[Bookmark]) {
+    save_json(&scoped(KEY), items)
+}
+
+/// Bookmarks of dashboard `id`, which need not be the active one.
+pub fn load_dashboard_bookmarks(id: &str) -> anyhow::Result<Vec<Bookmark>> {
+    try_load_json(&dashboard_key(KEY, id))
+}
+
+/// Fails when the bookmarks of some dashboard can't be read, before they
+/// would be exported or synced as if there were none.
+pub fn check_bookmarks() -> anyhow::Result<()> {
+    for d in dashboards::load() {
+        load_dashboard_bookmarks(&d.id)?;
+    }
+    Ok(())
+}
+
+/// Save the bookmarks of dashboard `id`, which need not be the active one.
+pub fn save_dashboard_bookmarks(id: &str, items: &[Bookmark]) {
+    save_json(&dashboard_key(KEY, id), items)
+}
+
+/// Tile style per group name. Groups without an entry use `TileStyle::List`.
+pub fn load_tile_styles() -> HashMap<String, TileStyle> {
+    load_json(&scoped(TILE_STYLES_KEY))
+}
+
+pub fn save_tile_styles(styles: &HashMap<String, TileStyle>) {
+    save_json(&scoped(TILE_STYLES_KEY), styles)
+}
+
+pub fn load_dashboard_tile_styles(id: &str) -> HashMap<String, TileStyle> {
+    load_json(&dashboard_key(TILE_STYLES_KEY, id))
+}
+
+pub fn save_dashboard_tile_styles(id: &str, styles: &HashMap<String, TileStyle>) {
+    save_json(&dashboard_key(TILE_STYLES_KEY, id), styles)
+}
+
+/// Everything the app persists, in one file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Export {
+    pub version: u32,
+    /// Bookmarks of the home dashboard.
+    pub bookmarks: Vec<Bookmark>,
+    /// Tile styles of the home dashboard.
+    pub tile_styles: HashMap<String, TileStyle>,
+    /// Widget layout and widget configs (notes included) of every dashboard, keyed by storage key.
+    pub widgets: BTreeMap<String, serde_json::Value>,
+    /// Absent in exports made before settings existed; importing those keeps the current settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Settings>,
+    /// Every dashboard, home first. Absent in exports made before dashboards existed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dashboards: Vec<DashboardExport>,
+}
+
+/// A dashboard and its bookmarks. The home dashboard's bookmarks and tile
+/// styles live in the top-level `Export` fields instead.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DashboardExport {
+    #[serde(flatten)]
+    pub dashboard: Dashboard,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bookmarks: Vec<Bookmark>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tile_styles: HashMap<String, TileStyle>,
+}
+
+/// What applying an import would change, shown before the user confirms.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportPreview {
+    pub new_bookmarks: usize,
+    pub duplicate_bookmarks: usize,
+    pub widget_entries: usize,
+    pub replaces_settings: bool,
+    /// Dashboards in the import that don't exist here yet.
+    pub new_dashboards: usize,
+    /// Bookmarks left out because of their URL, with the reason.
+    pub rejected: Vec<(String, Rejection)>,
+}
+
+pub fn export_all() -> Export {
+    let mut widgets = BTreeMap::new();
+    if let Some(st) = storage() {
+        let len = st.length().unwrap_or(0);
+        for i in 0..len {
+            let Ok(Some(key)) = st.key(i) else {
+                continue;
+            };
+            if !key.starts_with(WIDGET_KEY_PREFIX) {
+                continue;
+            }
+            if let Ok(Some(raw)) = st.get_item(&key)
+                && let Ok(value) = serde_json::from_str(&raw)
+            {
+                widgets.insert(key, value);
+            }
+        }
+    }
+
+    let dashboards = dashboards::load()
+        .into_iter()
+        .map(|dashboard| {
+            let home = dashboard.id == dashboards::HOME;
+            DashboardExport {
+                bookmarks: if home {
+                    Vec::new()
+                } else {
+                    load_json(&dashboard_key(KEY, &dashboard.id))
+                },
+                tile_styles: if home {
+                    HashMap::new()
+                } else {
+                    load_json(&dashboard_key(TILE_STYLES_KEY, &dashboard.id))
+                },
+                dashboard,
+            }
+        })
+        .collect();
+
+    Export {
+        version: EXPORT_VERSION,
+        bookmarks: load_json(KEY),
+        tile_styles: load_json(TILE_STYLES_KEY),
+        widgets,
+        settings: Some(settings::load()),
+        dashboards,
+    }
+}
+
+pub fn parse_export(raw: &str) -> anyhow::Result<Export> {
+    let data: Export = serde_json::from_str(raw)?;
+    if data.version == 0 || data.version > EXPORT_VERSION {
+        anyhow::bail!("unsupported export version {}", data.version);
+    }
+    Ok(data)
+}
+
+/// Bookmarks and tile styles per dashboard id, home included.
+fn dashboard_data(data: &Export) -> Vec<(&str, &[Bookmark], &HashMap<String, TileStyle>)> {
+    let mut out = vec![(dashboards::HOME, &data.bookmarks[..], &data.tile_styles)];
+    for d in &data.dashboards {
+        if d.dashboard.id != dashboards::HOME {
+            out.push((&d.dashboard.id, &d.bookmarks[..], &d.tile_styles));
+        }
+    }
+    out
+}
+
+pub fn preview_import(data: &Export, policy: &UrlPolicy) -> ImportPreview {
+    let existing = dashboards::load();
+    let mut preview = ImportPreview {
+        widget_entries: data
+            .widgets
+            .keys()
+            .filter(|k| k.starts_with(WIDGET_KEY_PREFIX))
+            .count(),
+        replaces_settings: data.settings.is_some(),
+        new_dashboards: data
+            .dashboards
+            .iter()
+            .filter(|d| !existing.iter().any(|e| e.id == d.dashboard.id))
+            .count(),
+        ..ImportPreview::default()
+    };
+    for (id, bookmarks, _) in dashboard_data(data) {
+        let current: Vec<Bookmark> = load_json(&dashboard_key(KEY, id));
+        for bm in bookmarks {
+            if let Err(e) = policy.check(&bm.url) {
+                preview.rejected.push((bm.url.clone(), e));
+            } else if current.iter().any(|c| c.url == bm.url) {
+                preview.duplicate_bookmarks += 1;
+            } else {
+                preview.new_bookmarks += 1;
+            }
+        }
+    }
+    preview
+}
+
+/// Add missing dashboards, merge imported bookmarks into each (skipping URLs
+/// already present or not allowed by `policy`) and replace tile styles,
+/// widget data and settings.
+pub fn apply_import(data: &Export, policy: &UrlPolicy) {
+    let mut list = dashboards::load();
+    for d in &data.dashboards {
+        match list.iter_mut().find(|e| e.id == d.dashboard.id) {
+            Some(existing) => *existing = d.dashboard.clone(),
+            None => list.push(d.dashboard.clone()),
+        }
+    }
+    dashboards::save(&list);
+
+    for (id, bookmarks, tile_styles) in dashboard_data(data) {
+        let key = dashboard_key(KEY, id);
+        let mut merged: Vec<Bookmark> = load_json(&key);
+        for bm in bookmarks {
+            if policy.check(&bm.url).is_ok() && !merged.iter().any(|c| c.url == bm.url) {
+                merged.push(bm.clone());
+            }
+        }
+        save_json(&key, &merged);
+
+        let key = dashboard_key(TILE_STYLES_KEY, id);
+        let mut styles: HashMap<String, TileStyle> = load_json(&key);
+        styles.extend(tile_styles.clone());
+        save_json(&key, &styles);
+    }
+
+    for (key, value) in &data.widgets {
+        if key.starts_with(WIDGET_KEY_PREFIX) {
+            save_json(key, value);
+        }
+    }
+
+    if let Some(s) = &data.settings {
+        settings::save(&s.clone().sanitized());
+    }
+}
+
+/// Make stored data match `data`: dashboards it doesn't have are removed, and
+/// bookmarks, tile styles and widget data are replaced rather than merged.
+/// Bookmarks not allowed by `policy` are left out.
+pub fn replace_all(data: &Export, policy: &UrlPolicy) {
+    let list: Vec<Dashboard> = data
+        .dashboards
+        .iter()
+        .map(|d| d.dashboard.clone())
+        .collect();
+    for d in dashboards::load() {
+        if !list.iter().any(|e| e.id == d.id) {
+            remove_dashboard_data(&d.id);
+        }
+    }
+    dashboards::save(&list);
+
+    for (id, bookmarks, tile_styles) in dashboard_data(data) {
+        let allowed: Vec<Bookmark> = bookmarks
+            .iter()
+            .filter(|bm| policy.check(&bm.url).is_ok())
+            .cloned()
+            .collect();
+        save_json(&dashboard_key(KEY, id), &allowed);
+        save_json(&dashboard_key(TILE_STYLES_KEY, id), tile_styles);
+    }
+
+    if let Some(st) = storage() {
+        let len = st.length().unwrap_or(0);
+        let keys: Vec<String> = (0..len)
+            .filter_map(|i| st.key(i).ok().flatten())
+            .filter(|k| k.starts_with(WIDGET_KEY_PREFIX))
+            .collect();
+        for key in keys {
+            let _ = st.remove_item(&key);
+        }
+    }
+    for (key, value) in &data.widgets {
+        if key.starts_with(WIDGET_KEY_PREFIX) {
+            save_json(key, value);
+        }
+    }
+
+    if let Some(s) = &data.settings {
+        settings::save(&s.clone().sanitized());
+    }
+}
+
+/// Remove everything this app has stored in the browser.
+pub fn clear_all() {
     let Some(st) = storage() else {
         return;
     };
-    if let Ok(raw) = serde_json::to_string(items) {
-        let _ = st.set_item(KEY, &raw);
+    let len = st.length().unwrap_or(0);
+    let keys: Vec<String> = (0..len)
+        .filter_map(|i| st.key(i).ok().flatten())
+        .filter(|k| k.starts_with(APP_KEY_PREFIX))
+        .collect();
+    for key in keys {
+        let _ = st.remove_item(&key);
     }
 }
@@ -0,0 +1,293 @@
+//! Reference servers for both sync backends, kept in memory.
+//!
+//! They implement `HttpClient`, so a `Syncer` can talk to them instead of
+//! `fetch`; with `storage::swap_memory` between passes, two syncers sharing
+//! one server behave like two devices. `MockServer` speaks the record
+//! protocol, and a real server only has to do what its `handle` does.
+//! `MockDav` stands in for a WebDAV server holding the one file.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use base64::prelude::*;
+
+use crate::net::{HttpCallback, HttpClient, HttpRequest, HttpResponse};
+
+use super::protocol::{PullResponse, PushRequest, PushResponse, Record};
+
+#[derive(Default)]
+pub struct MockServer {
+    /// Bearer token every request must carry; `None` accepts anyone.
+    token: Option<String>,
+    state: RefCell<Stored>,
+}
+
+#[derive(Default)]
+struct Stored {
+    /// Sequence number of the last stored record.
+    seq: u64,
+    /// Each record with the sequence number it was stored at.
+    records: BTreeMap<String, (u64, Record)>,
+}
+
+impl MockServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_token(token: &str) -> Self {
+        Self {
+            token: Some(token.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Every stored record, tombstones included.
+    pub fn records(&self) -> Vec<Record> {
+        self.state
+            .borrow()
+            .records
+            .values()
+            .map(|(_, r)| r.clone())
+            .collect()
+    }
+
+    pub fn handle(&self, req: &HttpRequest) -> HttpResponse {
+        if let Some(token) = &self.token {
+            let expected = format!("Bearer {token}");
+            let authorized = req
+                .headers
+                .iter()
+                .any(|(k, v)| k.eq_ignore_ascii_case("authorization") && *v == expected);
+            if !authorized {
+                return respond(401, String::new());
+            }
+        }
+        let (path, query) = req.url.split_once('?').unwrap_or((&req.url, ""));
+        if !path.ends_with("/records") {
+            return respond(404, String::new());
+        }
+        match req.method.as_str() {
+            "GET" => {
+                let since = query
+                    .split('&')
+                    .find_map(|p| p.strip_prefix("since="))
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0);
+                json(&self.pull(since))
+            }
+            "POST" => {
+                let Some(Ok(push)) = req.body.as_deref().map(serde_json::from_str::<PushRequest>)
+                else {
+                    return respond(400, String::new());
+                };
+                json(&self.push(push.records))
+            }
+            _ => respond(405, String::new()),
+        }
+    }
+
+    fn pull(&self, since: u64) -> PullResponse {
+        let state = self.state.borrow();
+        let mut records: Vec<&(u64, Record)> = state
+            .records
+            .values()
+            .filter(|(seq, _)| *seq > since)
+            .collect();
+        records.sort_by_key(|(seq, _)| *seq);
+        PullResponse {
+            cursor: state.seq,
+            records: records.into_iter().map(|(_, r)| r.clone()).collect(),
+        }
+    }
+
+    fn push(&self, records: Vec<Record>) -> PushResponse {
+        let mut state = self.state.borrow_mut();
+        let mut rejected = Vec::new();
+        for r in records {
+            match state.records.get(&r.id) {
+                // Sent again after a lost response; already stored.
+                Some((_, stored)) if *stored == r => continue,
+                Some((_, stored)) if !r.wins_over(stored) => {
+                    rejected.push(stored.clone());
+                    continue;
+                }
+                _ => {}
+            }
+            state.seq += 1;
+            let seq = state.seq;
+            state.records.insert(r.id.clone(), (seq, r));
+        }
+        PushResponse { rejected }
+    }
+}
+
+impl HttpClient for MockServer {
+    fn send(&self, req: HttpRequest, done: HttpCallback) {
+        done(Ok(self.handle(&req)));
+    }
+}
+
+/// A WebDAV server with one file, answering GET and PUT with ETags and
+/// conditional requests the way Nextcloud does.
+#[derive(Default)]
+pub struct MockDav {
+    /// `Authorization` header every request must carry; `None` accepts anyone.
+    auth: Option<String>,
+    /// The file and the number of times it was written, which is its ETag.
+    file: RefCell<Option<(String, u64)>>,
+}
+
+impl MockDav {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_login(user: &str, password: &str) -> Self {
+        let pair = format!("{user}:{password}");
+        Self {
+            auth: Some(format!("Basic {}", BASE64_STANDARD.encode(pair))),
+            ..Self::default()
+        }
+    }
+
+    pub fn file(&self) -> Option<String> {
+        self.file.borrow().as_ref().map(|(body, _)| body.clone())
+    }
+
+    /// Replace the file, as another device would.
+    pub fn write(&self, body: &str) {
+        let mut file = self.file.borrow_mut();
+        let n = file.as_ref().map_or(0, |(_, n)| *n) + 1;
+        *file = Some((body.to_string(), n));
+    }
+
+    pub fn handle(&self, req: &HttpRequest) -> HttpResponse {
+        let header = |name: &str| {
+            req.headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        };
+        if self.auth.is_some() && header("authorization") != self.auth.as_deref() {
+            return respond(401, String::new());
+        }
+        let etag = self.file.borrow().as_ref().map(|(_, n)| format!("\"{n}\""));
+        match req.method.as_str() {
+            "GET" => {
+                let Some((body, _)) = self.file.borrow().clone() else {
+                    return respond(404, String::new());
+                };
+                let etag = etag.unwrap_or_default();
+                if header("if-none-match") == Some(etag.as_str()) {
+                    return respond(304, String::new());
+                }
+                HttpResponse {
+                    status: 200,
+                    headers: vec![("etag".to_string(), etag)],
+                    body,
+                    ..HttpResponse::default()
+                }
+            }
+            "PUT" => {
+                let stale = match (header("if-match"), header("if-none-match")) {
+                    (Some(expected), _) => etag.as_deref() != Some(expected),
+                    (None, Some("*")) => etag.is_some(),
+                    _ => false,
+                };
+                if stale {
+                    return respond(412, String::new());
+                }
+                self.write(req.body.as_deref().unwrap_or_default());
+                let n = self.file.borrow().as_ref().map_or(0, |(_, n)| *n);
+                HttpResponse {
+                    status: if etag.is_some() { 204 } else { 201 },
+                    headers: vec![("etag".to_string(), format!("\"{n}\""))],
+                    ..HttpResponse::default()
+                }
+            }
+            _ => respond(405, String::new()),
+        }
+    }
+}
+
+impl HttpClient for MockDav {
+    fn send(&self, req: HttpRequest, done: HttpCallback) {
+        done(Ok(self.handle(&req)));
+    }
+}
+
+fn respond(status: u16, body: String) -> HttpResponse {
+    HttpResponse {
+        status,
+        headers: vec![("content-type".to_string(), "application/json".to_string())],
+        body,
+        ..HttpResponse::default()
+    }
+}
+
+fn json<T: serde::Serialize>(value: &T) -> HttpResponse {
+    respond(200, serde_json::to_string(value).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put(body: &str, headers: &[(&str, &str)]) -> HttpRequest {
+        HttpRequest {
+            method: "PUT".to_string(),
+            url: "https://dav.example/startpage.json".to_string(),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            body: Some(body.to_string()),
+            ..HttpRequest::default()
+        }
+    }
+
+    fn etag(resp: &HttpResponse) -> Option<&str> {
+        resp.headers
+            .iter()
+            .find(|(k, _)| k == "etag")
+            .map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn dav_writes_are_conditional() {
+        let dav = MockDav::with_login("me", "pw");
+        let login = ("Authorization", "Basic bWU6cHc=");
+        assert_eq!(dav.handle(&put("{}", &[])).status, 401);
+        assert_eq!(MockDav::new().handle(&put("{}", &[])).status, 201);
+
+        let created = dav.handle(&put("one", &[login, ("If-None-Match", "*")]));
+        assert_eq!((created.status, etag(&created)), (201, Some("\"1\"")));
+        assert_eq!(
+            dav.handle(&put("two", &[login, ("If-None-Match", "*")]))
+                .status,
+            412
+        );
+        assert_eq!(
+            dav.handle(&put("two", &[login, ("If-Match", "\"0\"")]))
+                .status,
+            412
+        );
+        let updated = dav.handle(&put("two", &[login, ("If-Match", "\"1\"")]));
+        assert_eq!((updated.status, etag(&updated)), (204, Some("\"2\"")));
+        assert_eq!(dav.file().as_deref(), Some("two"));
+
+        let get = |headers: &[(&str, &str)]| {
+            dav.handle(&HttpRequest {
+                headers: headers
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+                ..HttpRequest::get("https://dav.example/startpage.json")
+            })
+        };
+        let read = get(&[login]);
+        assert_eq!((read.status, read.body.as_str()), (200, "two"));
+        assert_eq!(get(&[login, ("If-None-Match", "\"2\"")]).status, 304);
+    }
+}
@@ -0,0 +1,926 @@
+//! Optional sync with a small self-hosted server (see `docs/sync-protocol.md`).
+//!
+//! Dashboards, bookmarks, tile styles and settings are split into records.
+//! Each pass diffs local data against the records last agreed with the
+//! server (the shadow), pulls what other devices stored since the last pass,
+//! pushes the local edits and applies what came in. Conflicts go to the
+//! later edit; deletes travel as tombstones.
+//!
+//! With the WebDAV backend (`webdav`) the whole state is one JSON file
+//! instead, as stored by Nextcloud and other WebDAV servers.
+//!
+//! The server URL and credentials are kept under their own key rather than in
+//! `Settings`, so exports never carry them.
+
+#[cfg(test)]
+mod mock;
+pub mod protocol;
+pub mod webdav;
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use anyhow::{Context, bail};
+use base64::prelude::*;
+use repose_core::{Signal, signal};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::crypto::{self, CryptoError, Envelope, Kdf, Key, Keyring};
+use crate::dashboards::{self, Dashboard};
+use crate::net::{HttpClient, HttpRequest, HttpResponse};
+use crate::settings::{self, Settings};
+use crate::storage::{self, Bookmark};
+
+use protocol::{PullResponse, PushRequest, PushResponse, Record};
+
+const STATE_KEY: &str = "startpage.sync.v1";
+
+/// Seconds between passes while the page is open.
+pub const INTERVAL_SECS: u64 = 60;
+
+/// Kind of server sync talks to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Backend {
+    /// Records with per-record versions (`docs/sync-protocol.md`).
+    #[default]
+    Records,
+    /// One JSON file on a WebDAV server.
+    WebDav,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 2] = [Backend::Records, Backend::WebDav];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Backend::Records => "Sync server",
+            Backend::WebDav => "WebDAV file",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncState {
+    pub backend: Backend,
+    /// Base URL of the server, or of the file for WebDAV; empty turns sync off.
+    pub url: String,
+    /// WebDAV user name; `token` is then the password.
+    pub user: String,
+    /// Sent as a bearer token, or as the WebDAV password, when set.
+    pub token: String,
+    /// Encrypts what is sent to the server when set (see `crypto`). Stored
+    /// as typed, like `token`, so passes need no prompt.
+    pub passphrase: String,
+    /// Random id of this browser, made on the first pass.
+    pub device: String,
+    /// Server position up to which records have been pulled.
+    pub cursor: u64,
+    /// Highest version seen or issued; new versions are above it.
+    pub clock: u64,
+    /// Unix seconds of the last pass that went through.
+    pub last_synced: u64,
+    /// Records as last agreed with the server, by id.
+    pub shadow: BTreeMap<String, Record>,
+    /// WebDAV: ETag of the file as last read or written.
+    pub etag: String,
+    /// WebDAV: digest of the file as last read or written.
+    pub remote_digest: u64,
+    /// WebDAV: digest of the local data at that point.
+    pub local_digest: u64,
+    /// Bumped by every `configure`; a pass started before it is dropped.
+    pub generation: u64,
+}
+
+/// The parts of `SyncState` the user sets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Target {
+    pub backend: Backend,
+    pub url: String,
+    pub user: String,
+    pub token: String,
+    pub passphrase: String,
+}
+
+impl SyncState {
+    pub fn target(&self) -> Target {
+        Target {
+            backend: self.backend,
+            url: self.url.clone(),
+            user: self.user.clone(),
+            token: self.token.clone(),
+            passphrase: self.passphrase.clone(),
+        }
+    }
+}
+
+pub fn load_state() -> SyncState {
+    storage::load_json(STATE_KEY)
+}
+
+fn save_state(state: &SyncState) {
+    storage::save_json(STATE_KEY, state)
+}
+
+/// A sync server URL is http(s).
+pub fn validate_url(url: &str) -> Result<(), &'static str> {
+    let url = url.trim();
+    if url.starts_with("https://") || url.starts_with("http://") {
+        Ok(())
+    } else {
+        Err("Sync server URL must start with https:// or http://")
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum SyncStatus {
+    #[default]
+    Off,
+    Idle,
+    Syncing,
+    Failed(String),
+    /// Both sides changed; waits for the user to pick (`Syncer::resolve`).
+    Conflict,
+}
+
+impl SyncStatus {
+    /// Short text for the header; `None` while sync is off.
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            SyncStatus::Off => None,
+            SyncStatus::Idle => Some("Synced"),
+            SyncStatus::Syncing => Some("Syncing…"),
+            SyncStatus::Failed(_) => Some("Sync failed"),
+            SyncStatus::Conflict => Some("Sync conflict"),
+        }
+    }
+}
+
+/// Runs sync passes: on load, every `INTERVAL_SECS` and on demand.
+#[derive(Clone)]
+pub struct Syncer {
+    http: Rc<dyn HttpClient>,
+    pub status: Signal<SyncStatus>,
+    /// Bumped whenever a pass changed local data, so views reload it.
+    pub applied: Signal<u64>,
+    /// The server's copy while `status` is `Conflict`.
+    pub conflict: Signal<Option<webdav::Conflict>>,
+    in_flight: Rc<Cell<bool>>,
+    last_attempt: Rc<Cell<u64>>,
+    keys: Rc<Keyring>,
+}
+
+impl Syncer {
+    pub fn new(http: Rc<dyn HttpClient>) -> Self {
+        let on = !load_state().url.is_empty();
+        Self {
+            http,
+            status: signal(if on {
+                SyncStatus::Idle
+            } else {
+                SyncStatus::Off
+            }),
+            applied: signal(0),
+            conflict: signal(None),
+            in_flight: Rc::new(Cell::new(false)),
+            last_attempt: Rc::new(Cell::new(0)),
+            keys: Rc::new(Keyring::default()),
+        }
+    }
+
+    /// Point sync at `target` (an empty URL turns it off). Another server, or
+    /// another passphrase, starts over: everything there is read again, and
+    /// data already here only fills in what the server doesn't have.
+    pub fn configure(&self, target: Target) {
+        let mut state = load_state();
+        let generation = state.generation + 1;
+        let url = target.url.trim().trim_end_matches('/').to_string();
+        if url != state.url
+            || target.backend != state.backend
+            || target.passphrase != state.passphrase
+        {
+            state = SyncState {
+                device: state.device,
+                ..SyncState::default()
+            };
+        }
+        state.backend = target.backend;
+        state.url = url;
+        state.user = target.user.trim().to_string();
+        state.token = target.token.trim().to_string();
+        state.passphrase = target.passphrase;
+        state.generation = generation;
+        self.conflict.set(None);
+        save_state(&state);
+        self.in_flight.set(false);
+        self.last_attempt.set(0);
+        self.status.set(if state.url.is_empty() {
+            SyncStatus::Off
+        } else {
+            SyncStatus::Idle
+        });
+    }
+
+    /// Start a pass when one is due.
+    pub fn tick(&self, now: u64) {
+        if matches!(self.status.get(), SyncStatus::Idle | SyncStatus::Failed(_))
+            && now.saturating_sub(self.last_attempt.get()) >= INTERVAL_SECS
+        {
+            self.sync_now(now);
+        }
+    }
+
+    /// Start a pass now, unless one is running.
+    pub fn sync_now(&self, now: u64) {
+        let mut state = load_state();
+        if state.url.is_empty() {
+            self.status.set(SyncStatus::Off);
+            return;
+        }
+        if self.in_flight.replace(true) {
+            return;
+        }
+        self.last_attempt.set(now);
+        self.status.set(SyncStatus::Syncing);
+        if state.backend == Backend::WebDav {
+            return self.webdav_pass(state, now, true);
+        }
+
+        if state.device.is_empty() {
+            state.device = new_device_id();
+        }
+        let local = match snapshot() {
+            Ok(local) => local,
+            Err(e) => return self.fail(e, &state),
+        };
+        let wire = match self.wire(&state) {
+            Ok(wire) => wire,
+            Err(e) => return self.fail(e.into(), &state),
+        };
+        // Before the first pass nothing here is an edit: the server's copy wins.
+        let version = if state.shadow.is_empty() {
+            0
+        } else {
+            next_version(&mut state.clock)
+        };
+        let mut changes = protocol::local_changes(&state.shadow, &local, &state.device, version);
+
+        let pull = request(
+            &state,
+            HttpRequest::get(format!("{}/records?since={}", state.url, state.cursor)),
+        );
+        let this = self.clone();
+        self.http.send(
+            pull,
+            Box::new(move |result| {
+                if !is_current(&state) {
+                    return;
+                }
+                // The server dropped the history behind our cursor: pull
+                // everything again next pass. The shadow keeps what is known.
+                if matches!(&result, Ok(resp) if resp.status == 410) {
+                    this.in_flight.set(false);
+                    state.cursor = 0;
+                    save_state(&state);
+                    this.last_attempt.set(0);
+                    this.status.set(SyncStatus::Idle);
+                    return;
+                }
+                let pulled = match parse::<PullResponse>(result).and_then(|mut p| {
+                    p.records = wire.open_all(p.records)?;
+                    Ok(p)
+                }) {
+                    Ok(p) => p,
+                    Err(e) => return this.fail(e, &state),
+                };
+                let mut taken =
+                    protocol::merge_pulled(&mut state.shadow, &mut changes, pulled.records);
+                state.cursor = pulled.cursor;
+                if changes.is_empty() {
+                    return this.finish(state, taken, now);
+                }
+
+                let records = match changes.iter().map(|c| wire.seal(c)).collect() {
+                    Ok(records) => records,
+                    Err(e) => return this.fail(anyhow::Error::from(e), &state),
+                };
+                let body = serde_json::to_string(&PushRequest { records }).unwrap_or_default();
+                let push = request(
+                    &state,
+                    HttpRequest {
+                        method: "POST".to_string(),
+                        url: format!("{}/records", state.url),
+                        headers: vec![("content-type".to_string(), "application/json".to_string())],
+                        body: Some(body),
+                        ..HttpRequest::default()
+                    },
+                );
+                let http = this.http.clone();
+                http.send(
+                    push,
+                    Box::new(move |result| {
+                        let rejected = match parse::<PushResponse>(result)
+                            .and_then(|p| wire.open_all(p.rejected))
+                        {
+                            Ok(rejected) => rejected,
+                            Err(e) => return this.fail(e, &state),
+                        };
+                        for c in changes {
+                            state.shadow.insert(c.id.clone(), c);
+                        }
+                        for r in rejected {
+                            if state.shadow.get(&r.id).is_none_or(|s| r.wins_over(s)) {
+                                state.shadow.insert(r.id.clone(), r.clone());
+                                taken.push(r);
+                            }
+                        }
+                        this.finish(state, taken, now);
+                    }),
+                );
+            }),
+        );
+    }
+
+    fn wire(&self, state: &SyncState) -> Result<Wire, CryptoError> {
+        Ok(Wire {
+            key: self.sealing_key(state)?,
+            keys: self.keys.clone(),
+            passphrase: state.passphrase.clone(),
+        })
+    }
+
+    /// Key for sealing what goes to `state`'s server; `None` without a
+    /// passphrase. Derived once per passphrase and server, which takes a
+    /// good part of a second.
+    fn sealing_key(&self, state: &SyncState) -> Result<Option<Rc<Key>>, CryptoError> {
+        if state.passphrase.is_empty() {
+            return Ok(None);
+        }
+        self.keys
+            .key(
+                &state.passphrase,
+                &crypto::shared_salt(&state.url),
+                &Kdf::default(),
+            )
+            .map(Some)
+    }
+
+    fn finish(&self, mut state: SyncState, taken: Vec<Record>, now: u64) {
+        if !is_current(&state) {
+            return;
+        }
+        self.in_flight.set(false);
+        let seen = state.shadow.values().map(|r| r.version).max().unwrap_or(0);
+        state.clock = state.clock.max(seen);
+        state.last_synced = now;
+        save_state(&state);
+        if !taken.is_empty() {
+            apply(&taken);
+            self.applied.update(|n| *n += 1);
+        }
+        self.status.set(SyncStatus::Idle);
+    }
+
+    fn fail(&self, e: anyhow::Error, state: &SyncState) {
+        if !is_current(state) {
+            return;
+        }
+        self.in_flight.set(false);
+        log::warn!("sync: {e:#}");
+        self.status.set(SyncStatus::Failed(format!("{e:#}")));
+    }
+}
+
+/// Turns records into what the server stores and back. With a passphrase,
+/// ids become keyed hashes and the data, id included, is sealed; tombstones
+/// carry a sealed id too.
+#[derive(Clone)]
+struct Wire {
+    key: Option<Rc<Key>>,
+    keys: Rc<Keyring>,
+    passphrase: String,
+}
+
+const SEALED_PREFIX: &str = "sealed/";
+
+impl Wire {
+    fn seal(&self, r: &Record) -> Result<Record, CryptoError> {
+        let Some(key) = &self.key else {
+            return Ok(r.clone());
+        };
+        let inner = serde_json::json!({ "id": r.id, "data": r.data });
+        let envelope = key.seal(&inner.to_string())?;
+        Ok(Record {
+            id: format!("{SEALED_PREFIX}{}", key.id_hash(&r.id)),
+            data: serde_json::to_value(envelope).ok(),
+            ..r.clone()
+        })
+    }
+
+    fn open(&self, r: Record) -> anyhow::Result<Record> {
+        let sealed = r.id.starts_with(SEALED_PREFIX);
+        let Some(key) = &self.key else {
+            if sealed {
+                bail!("the data on the server is encrypted; enter its passphrase");
+            }
+            return Ok(r);
+        };
+        if !sealed {
+            bail!("the server holds unencrypted data; sync to a new location to encrypt");
+        }
+        let envelope = Envelope::from_value(r.data.clone().unwrap_or_default())?;
+        let inner: Value = serde_json::from_str(&self.keys.open(&self.passphrase, &envelope)?)?;
+        let id = inner
+            .get("id")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        // Stops a server from passing one record off as another.
+        if r.id != format!("{SEALED_PREFIX}{}", key.id_hash(&id)) {
+            bail!("a record on the server doesn't match its id");
+        }
+        Ok(Record {
+            id,
+            data: inner.get("data").filter(|d| !d.is_null()).cloned(),
+            ..r
+        })
+    }
+
+    fn open_all(&self, records: Vec<Record>) -> anyhow::Result<Vec<Record>> {
+        records.into_iter().map(|r| self.open(r)).collect()
+    }
+}
+
+/// False once sync was reconfigured or turned off since `state` was loaded.
+/// A pass holding an old state then neither saves it nor touches `in_flight`,
+/// which belongs to the pass started since.
+fn is_current(state: &SyncState) -> bool {
+    load_state().generation == state.generation
+}
+
+fn request(state: &SyncState, mut req: HttpRequest) -> HttpRequest {
+    if let Some(auth) = authorization(state) {
+        req.headers.push(("authorization".to_string(), auth));
+    }
+    req
+}
+
+/// Basic auth with a user name, else the token as a bearer token.
+fn authorization(state: &SyncState) -> Option<String> {
+    if !state.user.is_empty() {
+        let pair = format!("{}:{}", state.user, state.token);
+        Some(format!("Basic {}", BASE64_STANDARD.encode(pair)))
+    } else if !state.token.is_empty() {
+        Some(format!("Bearer {}", state.token))
+    } else {
+        None
+    }
+}
+
+fn parse<T: DeserializeOwned>(result: anyhow::Result<HttpResponse>) -> anyhow::Result<T> {
+    let resp = result?;
+    match resp.status {
+        401 | 403 => bail!("the server refused the token"),
+        status if !(200..300).contains(&status) => bail!("HTTP {status}"),
+        _ => serde_json::from_str(&resp.body).context("unexpected answer from the server"),
+    }
+}
+
+fn new_device_id() -> String {
+    let mut bytes = [0u8; 8];
+    if getrandom::fill(&mut bytes).is_err() {
+        // Only ties between equal versions depend on it.
+        bytes = now_ms().to_le_bytes();
+    }
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// A version above every one seen so far, even with the clock set back.
+fn next_version(clock: &mut u64) -> u64 {
+    *clock = now_ms().max(*clock + 1);
+    *clock
+}
+
+/// Where an item sits in its list, stored in its record as `pos`.
+fn with_pos<T: Serialize>(item: &T, pos: usize) -> Value {
+    let mut value = serde_json::to_value(item).unwrap_or_default();
+    if let Value::Object(fields) = &mut value {
+        fields.insert("pos".to_string(), pos.into());
+    }
+    value
+}
+
+fn pos_of(r: &Record) -> usize {
+    r.data
+        .as_ref()
+        .and_then(|d| d.get("pos"))
+        .and_then(Value::as_u64)
+        .unwrap_or(u64::MAX) as usize
+}
+
+/// Local data as records, by id. Fails rather than report bookmarks that
+/// can't be read as deleted.
+fn snapshot() -> anyhow::Result<BTreeMap<String, Value>> {
+    let mut records = BTreeMap::new();
+    for (pos, d) in dashboards::load().iter().enumerate() {
+        records.insert(format!("dashboard/{}", d.id), with_pos(d, pos));
+        for (pos, bm) in storage::load_dashboard_bookmarks(&d.id)?.iter().enumerate() {
+            records.insert(format!("bookmark/{}/{}", d.id, bm.url), with_pos(bm, pos));
+        }
+        let styles = storage::load_dashboard_tile_styles(&d.id);
+        if !styles.is_empty() {
+            records.insert(
+                format!("tiles/{}", d.id),
+                serde_json::to_value(styles).unwrap_or_default(),
+            );
+        }
+    }
+    records.insert(
+        "settings".to_string(),
+        serde_json::to_value(settings::load()).unwrap_or_default(),
+    );
+    Ok(records)
+}
+
+fn data<T: DeserializeOwned>(r: &Record) -> Option<T> {
+    r.data
+        .clone()
+        .filter(|_| !r.deleted)
+        .and_then(|d| serde_json::from_value(d).ok())
+}
+
+/// Write records taken from the server into local data. Items are put back
+/// at their `pos`, so edits made here during the pass are kept.
+fn apply(records: &[Record]) {
+    let policy = settings::load().url_policy();
+    let mut list = dashboards::load();
+    let mut bookmarks: BTreeMap<String, Vec<Bookmark>> = BTreeMap::new();
+
+    let mut records: Vec<&Record> = records.iter().collect();
+    records.sort_by_key(|r| pos_of(r));
+    for r in records {
+        let pos = pos_of(r);
+        if let Some(id) = r.id.strip_prefix("dashboard/") {
+            list.retain(|d| d.id != id);
+            match data::<Dashboard>(r) {
+                Some(d) => list.insert(pos.min(list.len()), d),
+                None => storage::remove_dashboard_data(id),
+            }
+        } else if let Some((dashboard, url)) =
+            r.id.strip_prefix("bookmark/")
+                .and_then(|rest| rest.split_once('/'))
+        {
+            let v = bookmarks.entry(dashboard.to_string()).or_insert_with(|| {
+                storage::load_dashboard_bookmarks(dashboard).unwrap_or_default()
+            });
+            v.retain(|bm| bm.url != url);
+            match data::<Bookmark>(r) {
+                Some(bm) if policy.check(&bm.url).is_err() => {
+                    log::warn!("sync: not adding {}: link type not allowed", bm.url);
+                }
+                Some(bm) => v.insert(pos.min(v.len()), bm),
+                None => {}
+            }
+        } else if let Some(dashboard) = r.id.strip_prefix("tiles/") {
+            storage::save_dashboard_tile_styles(dashboard, &data(r).unwrap_or_default());
+        } else if r.id == "settings" {
+            if let Some(s) = data::<Settings>(r) {
+                settings::save(&s.sanitized());
+            }
+        } else {
+            log::warn!("sync: skipping unknown record {}", r.id);
+        }
+    }
+
+    dashboards::save(&list);
+    let list = dashboards::load();
+    for (dashboard, v) in bookmarks {
+        // Bookmarks of a dashboard deleted in the same pass went with it.
+        if list.iter().any(|d| d.id == dashboard) {
+            storage::save_dashboard_bookmarks(&dashboard, &v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::dashboards::HOME;
+    use crate::net::StubHttp;
+    use mock::MockServer;
+
+    const URL: &str = "https://sync.example";
+
+    /// A browser: its own storage, and a syncer working on it.
+    pub(super) struct Device {
+        memory: BTreeMap<String, String>,
+        pub(super) syncer: Syncer,
+    }
+
+    impl Device {
+        fn new(name: &str, http: Rc<dyn HttpClient>) -> Self {
+            Self::with_target(
+                name,
+                http,
+                Target {
+                    url: URL.to_string(),
+                    ..Target::default()
+                },
+            )
+        }
+
+        pub(super) fn with_target(name: &str, http: Rc<dyn HttpClient>, target: Target) -> Self {
+            let mut device = Device {
+                memory: BTreeMap::new(),
+                syncer: Syncer::new(http),
+            };
+            device.run(|syncer| {
+                save_state(&SyncState {
+                    device: name.to_string(),
+                    ..SyncState::default()
+                });
+                syncer.configure(target);
+            });
+            device
+        }
+
+        /// Run `f` with this device's storage in place.
+        pub(super) fn run<R>(&mut self, f: impl FnOnce(&Syncer) -> R) -> R {
+            storage::swap_memory(&mut self.memory);
+            let result = f(&self.syncer);
+            storage::swap_memory(&mut self.memory);
+            result
+        }
+
+        pub(super) fn sync(&mut self) -> SyncStatus {
+            self.run(|syncer| {
+                syncer.sync_now(100);
+                syncer.status.get()
+            })
+        }
+
+        pub(super) fn bookmarks(&mut self) -> Vec<Bookmark> {
+            self.run(|_| storage::load_dashboard_bookmarks(HOME).expect("bookmarks"))
+        }
+
+        pub(super) fn save_bookmarks(&mut self, list: &[Bookmark]) {
+            self.run(|_| storage::save_dashboard_bookmarks(HOME, list))
+        }
+
+        pub(super) fn state(&mut self) -> SyncState {
+            self.run(|_| load_state())
+        }
+    }
+
+    pub(super) fn bookmark(title: &str, url: &str) -> Bookmark {
+        Bookmark {
+            title: title.to_string(),
+            url: url.to_string(),
+            group: String::new(),
+            health: None,
+            open_in: None,
+            tags: Vec::new(),
+            pinned: false,
+        }
+    }
+
+    pub(super) fn titles(list: &[Bookmark]) -> Vec<&str> {
+        list.iter().map(|bm| bm.title.as_str()).collect()
+    }
+
+    fn push_request(records: Vec<Record>) -> HttpRequest {
+        HttpRequest {
+            method: "POST".to_string(),
+            url: format!("{URL}/records"),
+            body: Some(serde_json::to_string(&PushRequest { records }).expect("json")),
+            ..HttpRequest::default()
+        }
+    }
+
+    /// An edit another device stored at `version`.
+    fn edit_from_elsewhere(bm: &Bookmark, version: u64) -> Record {
+        Record {
+            id: format!("bookmark/{HOME}/{}", bm.url),
+            version,
+            device: "elsewhere".to_string(),
+            deleted: false,
+            data: Some(with_pos(bm, 0)),
+        }
+    }
+
+    #[test]
+    fn edits_reach_the_other_device() {
+        let server = Rc::new(MockServer::new());
+        let mut a = Device::new("a", server.clone());
+        let mut b = Device::new("b", server.clone());
+
+        a.save_bookmarks(&[bookmark("Docs", "https://docs.example/")]);
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        assert_eq!(b.sync(), SyncStatus::Idle);
+        assert_eq!(titles(&b.bookmarks()), ["Docs"]);
+        assert_eq!(b.syncer.applied.get(), 1);
+
+        b.save_bookmarks(&[bookmark("Manual", "https://docs.example/")]);
+        assert_eq!(b.sync(), SyncStatus::Idle);
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        assert_eq!(titles(&a.bookmarks()), ["Manual"]);
+        assert_eq!(a.state().last_synced, 100);
+    }
+
+    #[test]
+    fn deletes_travel_as_tombstones() {
+        let server = Rc::new(MockServer::new());
+        let mut a = Device::new("a", server.clone());
+        let mut b = Device::new("b", server.clone());
+        let docs = bookmark("Docs", "https://docs.example/");
+        let mail = bookmark("Mail", "https://mail.example/");
+
+        a.save_bookmarks(&[docs.clone(), mail]);
+        a.sync();
+        b.sync();
+        assert_eq!(titles(&b.bookmarks()), ["Docs", "Mail"]);
+
+        a.save_bookmarks(&[docs]);
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        let stored = server
+            .records()
+            .into_iter()
+            .find(|r| r.id == format!("bookmark/{HOME}/https://mail.example/"))
+            .expect("record");
+        assert!(stored.deleted);
+        assert_eq!(stored.data, None);
+
+        assert_eq!(b.sync(), SyncStatus::Idle);
+        assert_eq!(titles(&b.bookmarks()), ["Docs"]);
+    }
+
+    #[test]
+    fn pulled_edits_beat_only_older_local_ones() {
+        let server = Rc::new(MockServer::new());
+        let mut a = Device::new("a", server.clone());
+        let docs = bookmark("Docs", "https://docs.example/");
+        let mail = bookmark("Mail", "https://mail.example/");
+        a.save_bookmarks(&[docs.clone(), mail.clone()]);
+        a.sync();
+
+        // Both edited here; elsewhere, one later and one before this edit.
+        a.save_bookmarks(&[
+            bookmark("Docs here", &docs.url),
+            bookmark("Mail here", &mail.url),
+        ]);
+        let later = now_ms() + 3_600_000;
+        let response = server.handle(&push_request(vec![
+            edit_from_elsewhere(&bookmark("Docs there", &docs.url), later),
+            edit_from_elsewhere(&bookmark("Mail there", &mail.url), 1),
+        ]));
+        assert_eq!(response.status, 200);
+
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        let mut list = a.bookmarks();
+        list.sort_by(|x, y| x.url.cmp(&y.url));
+        assert_eq!(titles(&list), ["Docs there", "Mail here"]);
+        assert!(a.state().clock >= later);
+    }
+
+    #[test]
+    fn rejected_push_takes_the_server_copy() {
+        let server = Rc::new(MockServer::new());
+        // Stored by another device between our pull and our push.
+        let rival = Rc::new(RefCell::new(None::<Record>));
+        let http = StubHttp::new({
+            let (server, rival) = (server.clone(), rival.clone());
+            move |req| {
+                if req.method == "POST"
+                    && let Some(r) = rival.borrow_mut().take()
+                {
+                    server.handle(&push_request(vec![r]));
+                }
+                Ok(server.handle(req))
+            }
+        });
+        let mut a = Device::new("a", http.clone());
+        let docs = bookmark("Docs", "https://docs.example/");
+        a.save_bookmarks(std::slice::from_ref(&docs));
+        a.sync();
+
+        a.save_bookmarks(&[bookmark("Docs here", &docs.url)]);
+        let theirs = edit_from_elsewhere(&bookmark("Docs there", &docs.url), now_ms() + 3_600_000);
+        *rival.borrow_mut() = Some(theirs.clone());
+        let sent = http.sent().len();
+
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        let methods: Vec<String> = http.sent()[sent..]
+            .iter()
+            .map(|r| r.method.clone())
+            .collect();
+        assert_eq!(methods, ["GET", "POST"]);
+        assert_eq!(titles(&a.bookmarks()), ["Docs there"]);
+        assert_eq!(a.state().shadow[&theirs.id], theirs);
+        assert!(server.records().contains(&theirs));
+    }
+
+    #[test]
+    fn gone_cursor_pulls_everything_again() {
+        let server = Rc::new(MockServer::new());
+        let gone = Rc::new(Cell::new(false));
+        let http = StubHttp::new({
+            let (server, gone) = (server.clone(), gone.clone());
+            move |req| {
+                if gone.get() && !req.url.ends_with("since=0") {
+                    return Ok(crate::net::response(410, ""));
+                }
+                Ok(server.handle(req))
+            }
+        });
+        let mut a = Device::new("a", http.clone());
+        a.save_bookmarks(&[bookmark("Docs", "https://docs.example/")]);
+        // The cursor moves past our records when they are pulled back.
+        a.sync();
+        a.sync();
+        let before = a.state();
+        assert!(before.cursor > 0);
+
+        gone.set(true);
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        let after = a.state();
+        assert_eq!(after.cursor, 0);
+        assert_eq!(after.shadow, before.shadow);
+
+        // The next tick doesn't wait out the interval, and reads from the start.
+        a.run(|syncer| syncer.tick(101));
+        let last = http.sent().pop().expect("request");
+        assert!(last.url.ends_with("/records?since=0"), "{}", last.url);
+        assert_eq!(a.state().cursor, before.cursor);
+        assert_eq!(titles(&a.bookmarks()), ["Docs"]);
+    }
+
+    #[test]
+    fn refused_token_fails_the_pass() {
+        let mut a = Device::new("a", Rc::new(MockServer::with_token("secret")));
+        assert_eq!(
+            a.sync(),
+            SyncStatus::Failed("the server refused the token".to_string())
+        );
+
+        a.run(|syncer| {
+            syncer.configure(Target {
+                url: URL.to_string(),
+                token: "secret".to_string(),
+                ..Target::default()
+            })
+        });
+        assert_eq!(a.sync(), SyncStatus::Idle);
+    }
+
+    #[test]
+    fn reconfiguring_drops_the_pass_in_flight() {
+        let server = Rc::new(MockServer::new());
+        let http = StubHttp::holding({
+            let server = server.clone();
+            move |req| Ok(server.handle(req))
+        });
+        let mut a = Device::new("a", http.clone());
+        a.save_bookmarks(&[bookmark("Docs", "https://docs.example/")]);
+        a.run(|syncer| syncer.sync_now(100));
+        assert_eq!(http.held(), 1);
+
+        // A passphrase arrives while the pull is on its way; a pass with it
+        // starts before the old one hears back.
+        a.run(|syncer| {
+            syncer.configure(Target {
+                url: URL.to_string(),
+                passphrase: "correct horse".to_string(),
+                ..Target::default()
+            });
+            syncer.sync_now(101);
+            assert!(http.release_one());
+        });
+        // The old pass pushed nothing and saved nothing, and the new one
+        // still counts as running.
+        assert_eq!(http.sent().len(), 2);
+        let state = a.state();
+        assert_eq!(state.passphrase, "correct horse");
+        assert_eq!(state.last_synced, 0);
+        a.run(|syncer| syncer.sync_now(102));
+        assert_eq!(http.sent().len(), 2);
+
+        a.run(|_| http.release_all());
+        assert_eq!(a.syncer.status.get(), SyncStatus::Idle);
+        assert_eq!(a.state().last_synced, 101);
+        let records = server.records();
+        assert!(!records.is_empty());
+        assert!(records.iter().all(|r| r.id.starts_with(SEALED_PREFIX)));
+    }
+}
@@ -0,0 +1,237 @@
+//! Wire format and merge rules of the sync protocol (see `docs/sync-protocol.md`).
+//!
+//! Shared by the client and the reference server in `mock`, so both resolve
+//! conflicts the same way.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// One synced item: a dashboard, a bookmark, a dashboard's tile styles or the settings.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// `dashboard/<id>`, `bookmark/<dashboard>/<url>`, `tiles/<dashboard>` or `settings`.
+    pub id: String,
+    /// Milliseconds since the epoch of the edit, never lower than a version the
+    /// device has seen. 0 for data a device had before it first synced.
+    pub version: u64,
+    /// Device that made the edit; breaks ties between equal versions.
+    pub device: String,
+    /// A tombstone: the item was deleted.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deleted: bool,
+    /// The item; absent on tombstones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl Record {
+    /// Last write wins: the higher version, then the higher device id.
+    pub fn wins_over(&self, other: &Record) -> bool {
+        (self.version, &self.device) > (other.version, &other.device)
+    }
+}
+
+/// `GET <base>/records?since=<cursor>`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PullResponse {
+    /// Pass as `since` next time.
+    pub cursor: u64,
+    /// Records stored after `since`, oldest first.
+    #[serde(default)]
+    pub records: Vec<Record>,
+}
+
+/// `POST <base>/records`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PushRequest {
+    pub records: Vec<Record>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PushResponse {
+    /// Stored records that beat pushed ones; the client takes these instead.
+    #[serde(default)]
+    pub rejected: Vec<Record>,
+}
+
+/// Records for what changed locally since the last sync: `local` values that
+/// differ from `shadow`, and tombstones for ids `local` no longer has.
+pub fn local_changes(
+    shadow: &BTreeMap<String, Record>,
+    local: &BTreeMap<String, Value>,
+    device: &str,
+    version: u64,
+) -> Vec<Record> {
+    let mut changes = Vec::new();
+    for (id, value) in local {
+        let unchanged = shadow
+            .get(id)
+            .is_some_and(|r| !r.deleted && r.data.as_ref() == Some(value));
+        if !unchanged {
+            changes.push(Record {
+                id: id.clone(),
+                version,
+                device: device.to_string(),
+                deleted: false,
+                data: Some(value.clone()),
+            });
+        }
+    }
+    for (id, r) in shadow {
+        if !r.deleted && !local.contains_key(id) {
+            changes.push(Record {
+                id: id.clone(),
+                version,
+                device: device.to_string(),
+                deleted: true,
+                data: None,
+            });
+        }
+    }
+    changes
+}
+
+/// Fold pulled records into `shadow`. A pulled record beats a pending local
+/// change to the same id only if it wins by `Record::wins_over`; the losing
+/// change is dropped from `changes`. Returns the records taken, which the
+/// caller applies to local data.
+pub fn merge_pulled(
+    shadow: &mut BTreeMap<String, Record>,
+    changes: &mut Vec<Record>,
+    pulled: Vec<Record>,
+) -> Vec<Record> {
+    let mut taken = Vec::new();
+    for r in pulled {
+        if let Some(i) = changes.iter().position(|c| c.id == r.id) {
+            if !r.wins_over(&changes[i]) {
+                continue;
+            }
+            changes.remove(i);
+        } else if shadow.get(&r.id).is_some_and(|s| !r.wins_over(s)) {
+            continue;
+        }
+        shadow.insert(r.id.clone(), r.clone());
+        taken.push(r);
+    }
+    taken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rec(id: &str, version: u64, device: &str, data: Value) -> Record {
+        Record {
+            id: id.to_string(),
+            version,
+            device: device.to_string(),
+            deleted: false,
+            data: Some(data),
+        }
+    }
+
+    fn tombstone(id: &str, version: u64, device: &str) -> Record {
+        Record {
+            deleted: true,
+            data: None,
+            ..rec(id, version, device, Value::Null)
+        }
+    }
+
+    #[test]
+    fn last_write_wins_then_higher_device() {
+        let cases = [
+            ((2, "a"), (1, "b"), true),
+            ((1, "b"), (2, "a"), false),
+            ((5, "b"), (5, "a"), true),
+            ((5, "a"), (5, "b"), false),
+            ((5, "a"), (5, "a"), false),
+        ];
+        for ((v1, d1), (v2, d2), wins) in cases {
+            let one = rec("settings", v1, d1, json!(1));
+            let other = rec("settings", v2, d2, json!(2));
+            assert_eq!(one.wins_over(&other), wins, "{v1}/{d1} over {v2}/{d2}");
+        }
+    }
+
+    #[test]
+    fn local_changes_diff_against_the_shadow() {
+        let shadow: BTreeMap<String, Record> = [
+            rec("same", 1, "b", json!("x")),
+            rec("edited", 1, "b", json!("old")),
+            rec("removed", 1, "b", json!("y")),
+            tombstone("gone", 1, "b"),
+            tombstone("back", 1, "b"),
+        ]
+        .into_iter()
+        .map(|r| (r.id.clone(), r))
+        .collect();
+        let local: BTreeMap<String, Value> = [
+            ("same", json!("x")),
+            ("edited", json!("new")),
+            ("added", json!("z")),
+            ("back", json!("again")),
+        ]
+        .into_iter()
+        .map(|(id, v)| (id.to_string(), v))
+        .collect();
+
+        let changes = local_changes(&shadow, &local, "a", 7);
+        assert_eq!(
+            changes,
+            [
+                rec("added", 7, "a", json!("z")),
+                rec("back", 7, "a", json!("again")),
+                rec("edited", 7, "a", json!("new")),
+                tombstone("removed", 7, "a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_pulled_keeps_the_later_edit() {
+        let mut shadow: BTreeMap<String, Record> = [rec("known", 5, "b", json!("seen"))]
+            .into_iter()
+            .map(|r| (r.id.clone(), r))
+            .collect();
+        let mut changes = vec![
+            rec("beaten", 5, "a", json!("mine")),
+            rec("kept", 5, "a", json!("mine")),
+            rec("tie-lost", 5, "a", json!("mine")),
+            rec("tie-won", 5, "b", json!("mine")),
+        ];
+        let pulled = vec![
+            rec("beaten", 6, "a", json!("theirs")),
+            rec("kept", 4, "z", json!("theirs")),
+            rec("tie-lost", 5, "b", json!("theirs")),
+            rec("tie-won", 5, "a", json!("theirs")),
+            rec("known", 4, "c", json!("older")),
+            tombstone("fresh", 3, "c"),
+        ];
+
+        let taken = merge_pulled(&mut shadow, &mut changes, pulled);
+        assert_eq!(
+            taken,
+            [
+                rec("beaten", 6, "a", json!("theirs")),
+                rec("tie-lost", 5, "b", json!("theirs")),
+                tombstone("fresh", 3, "c"),
+            ]
+        );
+        assert_eq!(
+            changes,
+            [
+                rec("kept", 5, "a", json!("mine")),
+                rec("tie-won", 5, "b", json!("mine")),
+            ]
+        );
+        // What was taken is now agreed; an older copy of a known record is not.
+        assert_eq!(shadow["known"], rec("known", 5, "b", json!("seen")));
+        assert_eq!(shadow["tie-lost"].data, Some(json!("theirs")));
+        assert!(shadow["fresh"].deleted);
+        assert!(!shadow.contains_key("kept"));
+    }
+}
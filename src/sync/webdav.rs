@@ -0,0 +1,632 @@
+//! WebDAV backend: the whole state, as in an export, kept in one JSON file.
+//!
+//! ETags keep a stale copy from overwriting a newer one: the file is only
+//! written with `If-Match` on the version last read, or `If-None-Match: *`
+//! when there was none. Digests of the file and of the local data as last
+//! agreed tell which side changed since. When both did, the pass stops at
+//! `SyncStatus::Conflict` and the user picks a side or merges them.
+//!
+//! With a passphrase the file is an encrypted envelope (see `crypto`). A
+//! plain file is still read, so turning encryption on for an existing file
+//! rewrites it encrypted.
+
+use std::rc::Rc;
+
+use anyhow::{anyhow, bail};
+
+use crate::crypto::{self, Envelope};
+use crate::net::{HttpClient, HttpRequest};
+use crate::settings;
+use crate::storage::{self, Export};
+
+use super::{Backend, SyncState, SyncStatus, Syncer, authorization, is_current};
+
+/// What reading the file found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fetched {
+    /// Same ETag as the copy last read.
+    NotModified,
+    Missing,
+    File {
+        body: String,
+        etag: String,
+    },
+}
+
+/// What writing the file did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Stored {
+    /// Written; `etag` is empty when the server didn't say.
+    Saved { etag: String },
+    /// Refused because the file is not the version expected.
+    Conflict,
+}
+
+/// The version of the file a write may replace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expect {
+    /// No file yet.
+    Missing,
+    /// The file with this ETag.
+    Etag(String),
+    /// Whatever is there; for servers that send no ETags.
+    Any,
+}
+
+impl Expect {
+    fn from_etag(etag: String) -> Self {
+        if etag.is_empty() {
+            Expect::Any
+        } else {
+            Expect::Etag(etag)
+        }
+    }
+}
+
+pub type FetchCallback = Box<dyn FnOnce(anyhow::Result<Fetched>)>;
+pub type StoreCallback = Box<dyn FnOnce(anyhow::Result<Stored>)>;
+
+/// Reads and writes one file on a WebDAV server.
+pub struct DavClient {
+    http: Rc<dyn HttpClient>,
+    url: String,
+    auth: Option<String>,
+}
+
+impl DavClient {
+    pub fn new(http: Rc<dyn HttpClient>, state: &SyncState) -> Self {
+        Self {
+            http,
+            url: state.url.clone(),
+            auth: authorization(state),
+        }
+    }
+
+    fn request(&self, method: &str) -> HttpRequest {
+        let mut req = HttpRequest {
+            method: method.to_string(),
+            url: self.url.clone(),
+            ..HttpRequest::default()
+        };
+        if let Some(auth) = &self.auth {
+            req.headers
+                .push(("authorization".to_string(), auth.clone()));
+        }
+        req
+    }
+
+    /// Read the file, or learn it is unchanged since the copy tagged `etag`.
+    pub fn get(&self, etag: &str, done: FetchCallback) {
+        let mut req = self.request("GET");
+        // Caches in between must not answer with an older copy.
+        req.headers
+            .push(("cache-control".to_string(), "no-cache".to_string()));
+        if !etag.is_empty() {
+            req.headers
+                .push(("if-none-match".to_string(), etag.to_string()));
+        }
+        self.http.send(
+            req,
+            Box::new(move |result| {
+                done(result.and_then(|resp| match resp.status {
+                    304 => Ok(Fetched::NotModified),
+                    404 => Ok(Fetched::Missing),
+                    401 | 403 => bail!("the server refused the user name or password"),
+                    _ if resp.ok() => Ok(Fetched::File {
+                        etag: resp.header("etag").unwrap_or_default().to_string(),
+                        body: resp.body,
+                    }),
+                    status => bail!("HTTP {status}"),
+                }))
+            }),
+        );
+    }
+
+    /// Write `body`, unless the file is no longer the version `expect`ed.
+    pub fn put(&self, body: String, expect: Expect, done: StoreCallback) {
+        let mut req = self.request("PUT");
+        req.headers
+            .push(("content-type".to_string(), "application/json".to_string()));
+        match expect {
+            Expect::Missing => req
+                .headers
+                .push(("if-none-match".to_string(), "*".to_string())),
+            Expect::Etag(etag) => req.headers.push(("if-match".to_string(), etag)),
+            Expect::Any => {}
+        }
+        req.body = Some(body);
+        self.http.send(
+            req,
+            Box::new(move |result| {
+                done(result.and_then(|resp| match resp.status {
+                    412 => Ok(Stored::Conflict),
+                    401 | 403 => bail!("the server refused the user name or password"),
+                    409 => bail!("the folder for the file does not exist"),
+                    _ if resp.ok() => Ok(Stored::Saved {
+                        etag: resp.header("etag").unwrap_or_default().to_string(),
+                    }),
+                    status => bail!("HTTP {status}"),
+                }))
+            }),
+        );
+    }
+}
+
+/// The server's copy, held while the user decides.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub remote: Export,
+    digest: u64,
+    etag: String,
+}
+
+impl Conflict {
+    /// Bookmarks in the server's copy, on every dashboard.
+    pub fn remote_bookmarks(&self) -> usize {
+        self.remote.bookmarks.len()
+            + self
+                .remote
+                .dashboards
+                .iter()
+                .map(|d| d.bookmarks.len())
+                .sum::<usize>()
+    }
+}
+
+/// How to settle a conflict.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    /// Overwrite the server's copy with this browser's data.
+    KeepLocal,
+    /// Replace this browser's data with the server's copy.
+    UseRemote,
+    /// Add the server's bookmarks to the ones here, take its settings and
+    /// widgets, and write the result back.
+    Merge,
+}
+
+/// The local data as the file stores it. Serialized through `Value`, whose
+/// maps are sorted, so unchanged data always gives the same text.
+pub fn local_file() -> String {
+    serde_json::to_value(storage::export_all())
+        .and_then(|v| serde_json::to_string_pretty(&v))
+        .unwrap_or_default()
+}
+
+/// FNV-1a of `text`; tells whether a side changed since the last pass.
+pub fn digest(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl Syncer {
+    pub(super) fn webdav_pass(&self, state: SyncState, now: u64, retry: bool) {
+        let client = DavClient::new(self.http.clone(), &state);
+        let this = self.clone();
+        client.get(
+            &state.etag.clone(),
+            Box::new(move |result| {
+                if !is_current(&state) {
+                    return;
+                }
+                let fetched = match result {
+                    Ok(f) => f,
+                    Err(e) => return this.fail(e, &state),
+                };
+                if let Err(e) = storage::check_bookmarks() {
+                    return this.fail(e, &state);
+                }
+                let local = local_file();
+                let local_changed = digest(&local) != state.local_digest;
+                match fetched {
+                    Fetched::NotModified if !local_changed => this.webdav_done(state, now),
+                    Fetched::NotModified => {
+                        let expect = Expect::from_etag(state.etag.clone());
+                        this.upload(state, local, expect, now, retry);
+                    }
+                    Fetched::Missing => this.upload(state, local, Expect::Missing, now, retry),
+                    Fetched::File { body, etag } => {
+                        let remote_digest = digest(&body);
+                        let remote_changed = remote_digest != state.remote_digest;
+                        if !remote_changed && !local_changed {
+                            let mut state = state;
+                            state.etag = etag;
+                            return this.webdav_done(state, now);
+                        } else if !remote_changed {
+                            return this.upload(state, local, Expect::from_etag(etag), now, retry);
+                        }
+                        let body = match this.open_file(&state, body) {
+                            Ok(body) => body,
+                            Err(e) => return this.fail(e, &state),
+                        };
+                        if !local_changed || body == local {
+                            this.download(state, &body, remote_digest, etag, now);
+                        } else {
+                            match storage::parse_export(&body) {
+                                Ok(remote) => {
+                                    this.in_flight.set(false);
+                                    this.conflict.set(Some(Conflict {
+                                        remote,
+                                        digest: remote_digest,
+                                        etag,
+                                    }));
+                                    this.status.set(SyncStatus::Conflict);
+                                }
+                                Err(e) => this.fail(not_an_export(e), &state),
+                            }
+                        }
+                    }
+                }
+            }),
+        );
+    }
+
+    /// Settle the conflict the last pass stopped at.
+    pub fn resolve(&self, choice: Resolution, now: u64) {
+        let Some(conflict) = self.conflict.get() else {
+            return;
+        };
+        let mut state = super::load_state();
+        if state.backend != Backend::WebDav || state.url.is_empty() {
+            self.conflict.set(None);
+            return;
+        }
+        if self.in_flight.replace(true) {
+            return;
+        }
+        self.conflict.set(None);
+        self.last_attempt.set(now);
+        self.status.set(SyncStatus::Syncing);
+        if let Err(e) = storage::check_bookmarks() {
+            return self.fail(e, &state);
+        }
+        let expect = Expect::from_etag(conflict.etag.clone());
+        match choice {
+            Resolution::KeepLocal => self.upload(state, local_file(), expect, now, true),
+            Resolution::UseRemote => {
+                storage::replace_all(&conflict.remote, &settings::load().url_policy());
+                state.etag = conflict.etag;
+                state.remote_digest = conflict.digest;
+                state.local_digest = digest(&local_file());
+                self.applied.update(|n| *n += 1);
+                self.webdav_done(state, now);
+            }
+            Resolution::Merge => {
+                storage::apply_import(&conflict.remote, &settings::load().url_policy());
+                self.applied.update(|n| *n += 1);
+                self.upload(state, local_file(), expect, now, true);
+            }
+        }
+    }
+
+    /// Write `local`, the local data as `local_file` gives it, sealed when
+    /// there is a passphrase.
+    fn upload(&self, state: SyncState, local: String, expect: Expect, now: u64, retry: bool) {
+        let body = match self.seal_file(&state, &local) {
+            Ok(body) => body,
+            Err(e) => return self.fail(e, &state),
+        };
+        let client = DavClient::new(self.http.clone(), &state);
+        let this = self.clone();
+        let sent = digest(&body);
+        let local = digest(&local);
+        client.put(
+            body,
+            expect,
+            Box::new(move |result| match result {
+                Ok(Stored::Saved { etag }) => {
+                    let mut state = state;
+                    state.etag = etag;
+                    state.remote_digest = sent;
+                    state.local_digest = local;
+                    this.webdav_done(state, now);
+                }
+                // Written by someone else since it was read: read it again.
+                Ok(Stored::Conflict) if retry && is_current(&state) => {
+                    this.webdav_pass(state, now, false)
+                }
+                Ok(Stored::Conflict) => {
+                    this.fail(anyhow!("the file keeps changing on the server"), &state)
+                }
+                Err(e) => this.fail(e, &state),
+            }),
+        );
+    }
+
+    /// Take `body`, the file as opened, whose stored text has `remote_digest`.
+    fn download(
+        &self,
+        mut state: SyncState,
+        body: &str,
+        remote_digest: u64,
+        etag: String,
+        now: u64,
+    ) {
+        let data = match storage::parse_export(body) {
+            Ok(d) => d,
+            Err(e) => return self.fail(not_an_export(e), &state),
+        };
+        storage::replace_all(&data, &settings::load().url_policy());
+        state.etag = etag;
+        state.remote_digest = remote_digest;
+        state.local_digest = digest(&local_file());
+        self.applied.update(|n| *n += 1);
+        self.webdav_done(state, now);
+    }
+
+    fn seal_file(&self, state: &SyncState, local: &str) -> anyhow::Result<String> {
+        let Some(key) = self.sealing_key(state)? else {
+            return Ok(local.to_string());
+        };
+        Ok(serde_json::to_string_pretty(&key.seal(local)?)?)
+    }
+
+    /// The file's text, decrypted when it is sealed.
+    fn open_file(&self, state: &SyncState, body: String) -> anyhow::Result<String> {
+        if !crypto::is_encrypted(&body) {
+            return Ok(body);
+        }
+        if state.passphrase.is_empty() {
+            bail!("the file on the server is encrypted; enter its passphrase");
+        }
+        let envelope = Envelope::from_value(serde_json::from_str(&body)?)?;
+        Ok(self.keys.open(&state.passphrase, &envelope)?)
+    }
+
+    fn webdav_done(&self, mut state: SyncState, now: u64) {
+        if !is_current(&state) {
+            return;
+        }
+        self.in_flight.set(false);
+        state.last_synced = now;
+        super::save_state(&state);
+        self.status.set(SyncStatus::Idle);
+    }
+}
+
+fn not_an_export(e: anyhow::Error) -> anyhow::Error {
+    e.context("the file on the server is not a startpage export")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::super::Target;
+    use super::super::mock::MockDav;
+    use super::super::tests::{Device, bookmark, titles};
+    use super::*;
+    use crate::net::{HttpRequest, StubHttp};
+
+    const URL: &str = "https://dav.example/startpage.json";
+
+    fn target(passphrase: &str) -> Target {
+        Target {
+            backend: Backend::WebDav,
+            url: URL.to_string(),
+            user: "me".to_string(),
+            token: "pw".to_string(),
+            passphrase: passphrase.to_string(),
+        }
+    }
+
+    /// A WebDAV server; `race` is written to the file just before the next
+    /// PUT, as if another device got there first.
+    struct Server {
+        dav: Rc<MockDav>,
+        http: Rc<StubHttp>,
+        race: Rc<RefCell<Option<String>>>,
+    }
+
+    impl Server {
+        fn new() -> Self {
+            let dav = Rc::new(MockDav::with_login("me", "pw"));
+            let race = Rc::new(RefCell::new(None::<String>));
+            let http = StubHttp::new({
+                let (dav, race) = (dav.clone(), race.clone());
+                move |req| {
+                    if req.method == "PUT"
+                        && let Some(body) = race.borrow_mut().take()
+                    {
+                        dav.write(&body);
+                    }
+                    Ok(dav.handle(req))
+                }
+            });
+            Self { dav, http, race }
+        }
+
+        fn device(&self, name: &str, passphrase: &str) -> Device {
+            Device::with_target(name, self.http.clone(), target(passphrase))
+        }
+
+        fn file(&self) -> String {
+            self.dav.file().unwrap_or_default()
+        }
+
+        /// Requests sent from `from` on, as `METHOD condition`.
+        fn sent(&self, from: usize) -> Vec<String> {
+            self.http.sent()[from..].iter().map(describe).collect()
+        }
+    }
+
+    fn describe(req: &HttpRequest) -> String {
+        let condition = req
+            .headers
+            .iter()
+            .find(|(k, _)| k == "if-match" || k == "if-none-match")
+            .map(|(k, v)| format!(" {k}: {v}"))
+            .unwrap_or_default();
+        format!("{}{condition}", req.method)
+    }
+
+    #[test]
+    fn writes_only_over_the_version_read() {
+        let server = Server::new();
+        let mut a = server.device("a", "");
+        a.save_bookmarks(&[bookmark("Docs", "https://docs.example/")]);
+
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        assert_eq!(server.sent(0), ["GET", "PUT if-none-match: *"]);
+        assert!(server.file().contains("Docs"));
+        assert_eq!(a.state().etag, "\"1\"");
+
+        a.save_bookmarks(&[bookmark("Manual", "https://docs.example/")]);
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        assert_eq!(
+            server.sent(2),
+            ["GET if-none-match: \"1\"", "PUT if-match: \"1\""]
+        );
+        assert!(server.file().contains("Manual"));
+
+        // Nothing changed on either side: nothing is written.
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        assert_eq!(server.sent(4), ["GET if-none-match: \"2\""]);
+    }
+
+    /// `a` added Mail and the other device News, both after agreeing on Docs.
+    /// `a`'s write lost the race and the pass stopped at a conflict.
+    fn conflicted() -> (Server, Device) {
+        let server = Server::new();
+        let docs = bookmark("Docs", "https://docs.example/");
+        let mut a = server.device("a", "");
+        let mut b = server.device("b", "");
+        a.save_bookmarks(std::slice::from_ref(&docs));
+        a.sync();
+        b.sync();
+
+        b.save_bookmarks(&[docs.clone(), bookmark("News", "https://news.example/")]);
+        *server.race.borrow_mut() = Some(b.run(|_| local_file()));
+        a.save_bookmarks(&[docs, bookmark("Mail", "https://mail.example/")]);
+        let from = server.http.sent().len();
+
+        assert_eq!(a.sync(), SyncStatus::Conflict);
+        assert_eq!(
+            server.sent(from),
+            [
+                "GET if-none-match: \"1\"",
+                "PUT if-match: \"1\"",
+                "GET if-none-match: \"1\""
+            ]
+        );
+        let conflict = a.syncer.conflict.get().expect("conflict");
+        assert_eq!(conflict.remote_bookmarks(), 2);
+        (server, a)
+    }
+
+    #[test]
+    fn lost_write_retries_then_stops_at_conflict() {
+        let (server, mut a) = conflicted();
+        // The other device's file is untouched, and so is the data here.
+        assert!(server.file().contains("News"));
+        assert_eq!(titles(&a.bookmarks()), ["Docs", "Mail"]);
+    }
+
+    #[test]
+    fn keep_local_overwrites_the_file() {
+        let (server, mut a) = conflicted();
+        let from = server.http.sent().len();
+        a.run(|syncer| syncer.resolve(Resolution::KeepLocal, 200));
+        assert_eq!(a.syncer.status.get(), SyncStatus::Idle);
+        assert_eq!(server.sent(from), ["PUT if-match: \"2\""]);
+        assert!(server.file().contains("Mail") && !server.file().contains("News"));
+        assert_eq!(titles(&a.bookmarks()), ["Docs", "Mail"]);
+    }
+
+    #[test]
+    fn use_remote_replaces_the_data_here() {
+        let (server, mut a) = conflicted();
+        let from = server.http.sent().len();
+        a.run(|syncer| syncer.resolve(Resolution::UseRemote, 200));
+        assert_eq!(a.syncer.status.get(), SyncStatus::Idle);
+        assert!(server.sent(from).is_empty());
+        assert_eq!(titles(&a.bookmarks()), ["Docs", "News"]);
+
+        // Both sides now agree.
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        assert_eq!(server.sent(from), ["GET if-none-match: \"2\""]);
+    }
+
+    #[test]
+    fn merge_writes_both_sides_back() {
+        let (server, mut a) = conflicted();
+        let from = server.http.sent().len();
+        a.run(|syncer| syncer.resolve(Resolution::Merge, 200));
+        assert_eq!(a.syncer.status.get(), SyncStatus::Idle);
+        assert_eq!(server.sent(from), ["PUT if-match: \"2\""]);
+        assert_eq!(titles(&a.bookmarks()), ["Docs", "Mail", "News"]);
+        assert!(server.file().contains("Mail") && server.file().contains("News"));
+    }
+
+    #[test]
+    fn encrypted_file_needs_the_passphrase() {
+        let server = Server::new();
+        let mut a = server.device("a", "correct horse");
+        a.save_bookmarks(&[bookmark("Docs", "https://docs.example/")]);
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        assert!(crypto::is_encrypted(&server.file()));
+        assert!(!server.file().contains("Docs"));
+
+        let mut b = server.device("b", "");
+        assert_eq!(
+            b.sync(),
+            SyncStatus::Failed("the file on the server is encrypted; enter its passphrase".into())
+        );
+        assert!(b.bookmarks().is_empty());
+
+        // With it the file opens; this browser never agreed with it, so
+        // the user picks a side.
+        b.run(|syncer| syncer.configure(target("correct horse")));
+        assert_eq!(b.sync(), SyncStatus::Conflict);
+        b.run(|syncer| syncer.resolve(Resolution::UseRemote, 200));
+        assert_eq!(b.syncer.status.get(), SyncStatus::Idle);
+        assert_eq!(titles(&b.bookmarks()), ["Docs"]);
+    }
+
+    #[test]
+    fn reconfiguring_drops_the_pass_in_flight() {
+        let dav = Rc::new(MockDav::with_login("me", "pw"));
+        let http = StubHttp::holding({
+            let dav = dav.clone();
+            move |req| Ok(dav.handle(req))
+        });
+        let mut a = Device::with_target("a", http.clone(), target(""));
+        a.save_bookmarks(&[bookmark("Docs", "https://docs.example/")]);
+        a.run(|syncer| {
+            syncer.sync_now(100);
+            syncer.configure(target("correct horse"));
+            http.release_all();
+        });
+        // The old pass would have written the file in the clear.
+        assert_eq!(http.sent().len(), 1);
+        assert_eq!(dav.file(), None);
+        assert_eq!(a.state().passphrase, "correct horse");
+
+        a.run(|syncer| {
+            syncer.sync_now(101);
+            http.release_all();
+        });
+        assert_eq!(a.syncer.status.get(), SyncStatus::Idle);
+        assert!(crypto::is_encrypted(&dav.file().unwrap_or_default()));
+    }
+
+    #[test]
+    fn plain_file_is_read_then_rewritten_encrypted() {
+        let server = Server::new();
+        let mut a = server.device("a", "");
+        a.save_bookmarks(&[bookmark("Docs", "https://docs.example/")]);
+        a.sync();
+        assert!(!crypto::is_encrypted(&server.file()));
+
+        a.run(|syncer| syncer.configure(target("correct horse")));
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        assert!(!crypto::is_encrypted(&server.file()));
+        assert_eq!(titles(&a.bookmarks()), ["Docs"]);
+
+        a.save_bookmarks(&[bookmark("Manual", "https://docs.example/")]);
+        assert_eq!(a.sync(), SyncStatus::Idle);
+        assert!(crypto::is_encrypted(&server.file()));
+        assert!(!server.file().contains("Manual"));
+    }
+}
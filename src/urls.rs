@@ -0,0 +1,388 @@
+//! Deciding whether typed text is a URL or a search.
+//!
+//! `classify` accepts explicit schemes, `host:port`, IPv4 and bracketed IPv6
+//! addresses, `localhost`, single-label intranet hosts the user allowlisted,
+//! and dotted names whose last label is a known top-level domain (or that are
+//! followed by a port or a `/`). Everything else is a search, and a leading
+//! `?` forces one.
+//!
+//! `UrlPolicy` decides which of those URLs may be stored or opened at all.
+
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Prefix that makes the rest of the input a search, even if it looks like a URL.
+pub const FORCE_SEARCH: char = '?';
+
+/// Schemes written without `//` that still make the input a URL.
+const OPAQUE_SCHEMES: [&str; 7] = [
+    "about",
+    "mailto",
+    "tel",
+    "data",
+    "javascript",
+    "view-source",
+    "blob",
+];
+
+/// Generic TLDs. `zip` and `mov` are left out: as file names they're far
+/// more common than as domains.
+const GENERIC_TLDS: &str = "com org net edu gov mil int arpa info biz name pro mobi aero asia cat \
+     coop jobs museum tel travel post xxx app dev page blog cloud online site store shop tech \
+     xyz top club live news wiki art design link space website email life world today network \
+     systems tools software digital media studio solutions agency company group team works zone \
+     run chat social video music games photo photos one global guru ninja rocks fun icu vip work \
+     center city host land market money finance bank ventures capital codes community academy \
+     education school university health care love";
+
+/// Country-code TLDs, minus those that are common source-file extensions
+/// (`rs`, `py`, `md`, `sh`, `pl`, `so`, `cc`, `ps`, `mk`, `ml`, `tf`, `sc`).
+/// Such names only open as URLs with a scheme or a trailing `/`.
+const COUNTRY_TLDS: &str = "ac ad ae af ag ai al am ao aq ar as at au aw ax az ba bb bd be bf bg \
+     bh bi bj bm bn bo br bs bt bw by bz ca cd cf cg ch ci ck cl cm cn co cr cu cv cw cx cy cz de \
+     dj dk dm do dz ec ee eg er es et eu fi fj fk fm fo fr ga gd ge gf gg gh gi gl gm gn gp gq gr \
+     gs gt gu gw gy hk hm hn hr ht hu id ie il im in io iq ir is it je jm jo jp ke kg kh ki km kn \
+     kp kr kw ky kz la lb lc li lk lr ls lt lu lv ly ma mc md me mg mh mm mn mo mp mq mr ms mt \
+     mu mv mw mx my mz na nc ne nf ng ni nl no np nr nu nz om pa pe pf pg ph pk pm pn pr pt pw \
+     qa re ro ru rw sa sb sd se sg si sk sl sm sn sr ss st sv sx sy sz tc td tg th tj tk tl tm \
+     tn to tr tt tv tw tz ua ug uk us uy uz va vc ve vg vi vn vu wf ws ye yt za zm zw";
+
+/// What the search box should do with some input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Classified {
+    /// Open this URL, with a scheme added when the input had none.
+    Url(String),
+    /// Search for this text.
+    Search(String),
+}
+
+fn known_tld(label: &str) -> bool {
+    let label = label.to_lowercase();
+    GENERIC_TLDS
+        .split_whitespace()
+        .chain(COUNTRY_TLDS.split_whitespace())
+        .any(|t| t == label)
+}
+
+/// Whether `input` starts with a scheme, e.g. `https://` or `about:`.
+/// `localhost:3000` doesn't: a bare `name:` only counts for `OPAQUE_SCHEMES`.
+fn has_scheme(input: &str) -> bool {
+    let Some((scheme, rest)) = input.split_once(':') else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid
+        && (rest.starts_with("//")
+            || OPAQUE_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()))
+}
+
+fn valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= 63
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+}
+
+fn valid_port(port: &str) -> bool {
+    !port.is_empty()
+        && port.chars().all(|c| c.is_ascii_digit())
+        && port.parse::<u16>().is_ok_and(|p| p > 0)
+}
+
+/// Scheme to add to a bare `host[:port]…`, or `None` when it isn't a host.
+fn host_scheme(authority: &str, has_path: bool, intranet_hosts: &[String]) -> Option<&'static str> {
+    // Bracketed IPv6, optionally with a port.
+    if let Some(rest) = authority.strip_prefix('[') {
+        let (addr, after) = rest.split_once(']')?;
+        addr.parse::<Ipv6Addr>().ok()?;
+        return match after.strip_prefix(':') {
+            None if after.is_empty() => Some("http"),
+            Some(port) if valid_port(port) => Some("http"),
+            _ => None,
+        };
+    }
+
+    let (host, port) = match authority.split_once(':') {
+        Some((host, port)) if valid_port(port) => (host, Some(port)),
+        Some(_) => return None,
+        None => (authority, None),
+    };
+    if host.eq_ignore_ascii_case("localhost") || host.parse::<Ipv4Addr>().is_ok() {
+        return Some("http");
+    }
+    if !host.contains('.') {
+        let listed = intranet_hosts
+            .iter()
+            .any(|h| h.trim().eq_ignore_ascii_case(host));
+        // An explicit port is a strong enough hint on its own (`devbox:8080`).
+        return (valid_label(host) && (listed || port.is_some())).then_some("http");
+    }
+
+    let labels: Vec<&str> = host.split('.').collect();
+    if !labels.iter().all(|l| valid_label(l)) {
+        return None;
+    }
+    let tld = labels[labels.len() - 1];
+    if tld.chars().all(|c| c.is_ascii_digit()) {
+        // Looks numeric but isn't a valid IPv4 address (`v1.2`, `999.1.1.1`).
+        return None;
+    }
+    // A port or a path after an unknown TLD is deliberate enough (`foo.rs/`).
+    (known_tld(tld) || port.is_some() || has_path).then_some("https")
+}
+
+/// Decide whether `input` is a URL to open or text to search for.
+/// `intranet_hosts` are single-label names (`wiki`, `jira`) that open as URLs.
+pub fn classify(input: &str, intranet_hosts: &[String]) -> Classified {
+    let input = input.trim();
+    if let Some(rest) = input.strip_prefix(FORCE_SEARCH) {
+        return Classified::Search(rest.trim().to_string());
+    }
+    if input.is_empty() || input.chars().any(char::is_whitespace) {
+        return Classified::Search(input.to_string());
+    }
+    if has_scheme(input) {
+        return Classified::Url(input.to_string());
+    }
+
+    let split = input.find(['/', '?', '#']).unwrap_or(input.len());
+    let (authority, rest) = input.split_at(split);
+    // `user@host` is almost always an email address being looked up.
+    if authority.contains('@') {
+        return Classified::Search(input.to_string());
+    }
+    match host_scheme(authority, rest.starts_with('/'), intranet_hosts) {
+        Some(scheme) => Classified::Url(format!("{scheme}://{input}")),
+        None => Classified::Search(input.to_string()),
+    }
+}
+
+/// Schemes that are never allowed, whatever the settings say: they run script
+/// or inline content in the context of whatever opens them.
+const BLOCKED_SCHEMES: [&str; 4] = ["javascript", "vbscript", "data", "blob"];
+
+/// Always allowed for bookmarks and navigation.
+const DEFAULT_SCHEMES: [&str; 2] = ["http", "https"];
+
+/// Why `UrlPolicy::check` turned a URL down.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rejection {
+    NoScheme,
+    /// One of `BLOCKED_SCHEMES`.
+    Blocked(String),
+    /// A scheme that isn't enabled in settings.
+    NotAllowed(String),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::NoScheme => write!(f, "not an absolute URL"),
+            Rejection::Blocked(s) => write!(f, "{s}: links are never allowed"),
+            Rejection::NotAllowed(s) => write!(f, "{s}: links are not enabled in settings"),
+        }
+    }
+}
+
+/// The lowercased scheme of `url`, read the way browsers do: leading spaces and
+/// control characters are skipped and tabs or newlines inside are ignored, so
+/// `" java\tscript:"` is `javascript`.
+pub fn scheme_of(url: &str) -> Option<String> {
+    let url = url.trim_start_matches(|c: char| c <= ' ');
+    let (scheme, _) = url.split_once(':')?;
+    let scheme: String = scheme
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+/// Whether `scheme` may be enabled as an extra scheme.
+pub fn valid_extra_scheme(scheme: &str) -> bool {
+    scheme_of(&format!("{scheme}:")).is_some_and(|s| s == scheme)
+        && !BLOCKED_SCHEMES.contains(&scheme)
+        && !DEFAULT_SCHEMES.contains(&scheme)
+}
+
+/// Which URL schemes bookmarks may use and the page may open: http and https,
+/// plus extras enabled in settings (`ftp`, `mailto`, …).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UrlPolicy {
+    extra_schemes: Vec<String>,
+}
+
+impl UrlPolicy {
+    pub fn new(extra_schemes: &[String]) -> Self {
+        Self {
+            extra_schemes: extra_schemes
+                .iter()
+                .map(|s| s.to_ascii_lowercase())
+                .filter(|s| valid_extra_scheme(s))
+                .collect(),
+        }
+    }
+
+    pub fn check(&self, url: &str) -> Result<(), Rejection> {
+        let scheme = scheme_of(url).ok_or(Rejection::NoScheme)?;
+        if BLOCKED_SCHEMES.contains(&scheme.as_str()) {
+            return Err(Rejection::Blocked(scheme));
+        }
+        if DEFAULT_SCHEMES.contains(&scheme.as_str()) || self.extra_schemes.contains(&scheme) {
+            Ok(())
+        } else {
+            Err(Rejection::NotAllowed(scheme))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_table() {
+        const URL: bool = true;
+        const SEARCH: bool = false;
+        let intranet = ["wiki".to_string(), " jira ".to_string()];
+        // (input, allowlist applies, URL or search, what comes out)
+        let cases: &[(&str, bool, bool, &str)] = &[
+            // Dotted names
+            ("example.com", false, URL, "https://example.com"),
+            ("  example.com  ", false, URL, "https://example.com"),
+            ("EXAMPLE.COM", false, URL, "https://EXAMPLE.COM"),
+            (
+                "github.com/rust-lang",
+                false,
+                URL,
+                "https://github.com/rust-lang",
+            ),
+            ("example.com?q=1", false, URL, "https://example.com?q=1"),
+            ("bbc.co.uk", false, URL, "https://bbc.co.uk"),
+            ("example.com:8443", false, URL, "https://example.com:8443"),
+            ("example.com:0", false, SEARCH, "example.com:0"),
+            ("example.com:99999", false, SEARCH, "example.com:99999"),
+            ("-bad.com", false, SEARCH, "-bad.com"),
+            ("v1.2", false, SEARCH, "v1.2"),
+            ("e.g.", false, SEARCH, "e.g."),
+            ("foo.rs", false, SEARCH, "foo.rs"),
+            ("foo.rs/", false, URL, "https://foo.rs/"),
+            ("main.py", false, SEARCH, "main.py"),
+            ("archive.zip", false, SEARCH, "archive.zip"),
+            ("foo.bar:8080", false, URL, "https://foo.bar:8080"),
+            // Local hosts and addresses
+            ("localhost", false, URL, "http://localhost"),
+            ("localhost:3000", false, URL, "http://localhost:3000"),
+            (
+                "localhost:3000/api",
+                false,
+                URL,
+                "http://localhost:3000/api",
+            ),
+            ("192.168.1.1", false, URL, "http://192.168.1.1"),
+            (
+                "192.168.1.1:8080/admin",
+                false,
+                URL,
+                "http://192.168.1.1:8080/admin",
+            ),
+            ("999.1.1.1", false, SEARCH, "999.1.1.1"),
+            ("[::1]", false, URL, "http://[::1]"),
+            ("[::1]:8080", false, URL, "http://[::1]:8080"),
+            ("[::1", false, SEARCH, "[::1"),
+            ("[nope]:80", false, SEARCH, "[nope]:80"),
+            // Explicit schemes
+            ("https://example.com", false, URL, "https://example.com"),
+            ("file:///", false, URL, "file:///"),
+            ("about:blank", false, URL, "about:blank"),
+            ("mailto:me@example.com", false, URL, "mailto:me@example.com"),
+            ("ftp://files.example", false, URL, "ftp://files.example"),
+            ("note:buy milk", false, SEARCH, "note:buy milk"),
+            // Searches
+            ("user@host.com", false, SEARCH, "user@host.com"),
+            ("rust borrow checker", false, SEARCH, "rust borrow checker"),
+            ("", false, SEARCH, ""),
+            ("?example.com", false, SEARCH, "example.com"),
+            ("? localhost:3000 ", false, SEARCH, "localhost:3000"),
+            // Single-label hosts
+            ("wiki", true, URL, "http://wiki"),
+            ("wiki", false, SEARCH, "wiki"),
+            ("JIRA/browse/X-1", true, URL, "http://JIRA/browse/X-1"),
+            ("confluence", true, SEARCH, "confluence"),
+            ("devbox:8080", false, URL, "http://devbox:8080"),
+            ("devbox:http", false, SEARCH, "devbox:http"),
+        ];
+        for &(input, allowlisted, url, want) in cases {
+            let hosts: &[String] = if allowlisted { &intranet } else { &[] };
+            let expected = if url {
+                Classified::Url(want.to_string())
+            } else {
+                Classified::Search(want.to_string())
+            };
+            assert_eq!(classify(input, hosts), expected, "classify({input:?})");
+        }
+    }
+
+    #[test]
+    fn policy_table() {
+        let default = UrlPolicy::default();
+        let extras = UrlPolicy::new(&[
+            "FTP".to_string(),
+            "obsidian".to_string(),
+            "javascript".to_string(),
+            "https".to_string(),
+        ]);
+        let blocked = |s: &str| Err(Rejection::Blocked(s.to_string()));
+        let not_allowed = |s: &str| Err(Rejection::NotAllowed(s.to_string()));
+        let cases: &[(&UrlPolicy, &str, Result<(), Rejection>)] = &[
+            (&default, "https://example.com", Ok(())),
+            (&default, "HTTP://example.com", Ok(())),
+            (&default, "example.com", Err(Rejection::NoScheme)),
+            (&default, "", Err(Rejection::NoScheme)),
+            (&default, "1http://x", Err(Rejection::NoScheme)),
+            (&default, "javascript:alert(1)", blocked("javascript")),
+            (&default, " java\tscript:alert(1)", blocked("javascript")),
+            (&default, "\u{1}JaVaScRiPt:alert(1)", blocked("javascript")),
+            (&default, "java\nscript:alert(1)", blocked("javascript")),
+            (&default, "vbscript:msgbox", blocked("vbscript")),
+            (&default, "data:text/html,<script>", blocked("data")),
+            (&default, "blob:https://example.com/id", blocked("blob")),
+            (&default, "file:///etc/passwd", not_allowed("file")),
+            (&default, "ftp://files.example", not_allowed("ftp")),
+            (&extras, "ftp://files.example", Ok(())),
+            (&extras, "obsidian://open?vault=notes", Ok(())),
+            // Enabling a blocked scheme has no effect.
+            (&extras, "javascript:alert(1)", blocked("javascript")),
+            (&extras, "mailto:me@example.com", not_allowed("mailto")),
+        ];
+        for (policy, url, want) in cases {
+            assert_eq!(policy.check(url), *want, "check({url:?})");
+        }
+    }
+
+    #[test]
+    fn scheme_parsing() {
+        let cases = [
+            (" java\tscript:", Some("javascript")),
+            ("HTTPS://x", Some("https")),
+            ("web+app:x", Some("web+app")),
+            ("no scheme here", None),
+            ("://x", None),
+            ("1ab:x", None),
+        ];
+        for (url, want) in cases {
+            assert_eq!(scheme_of(url).as_deref(), want, "scheme_of({url:?})");
+        }
+        assert!(valid_extra_scheme("ftp"));
+        assert!(!valid_extra_scheme("javascript"));
+        assert!(!valid_extra_scheme("https"));
+        assert!(!valid_extra_scheme("bad scheme"));
+        assert!(!valid_extra_scheme("FTP"));
+    }
+}
@@ -0,0 +1,474 @@
+//! Weather data behind a provider trait, plus a storage cache with a TTL.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use anyhow::Context;
+use repose_core::{Signal, signal};
+use serde::{Deserialize, Serialize};
+
+use crate::net::{HttpClient, HttpRequest};
+use crate::storage;
+
+const CACHE_KEY: &str = "startpage.weather.cache.v1";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Units {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl Units {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Units::Celsius => "°C",
+            Units::Fahrenheit => "°F",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DayForecast {
+    /// `YYYY-MM-DD` in the location's timezone.
+    pub date: String,
+    pub code: u32,
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Forecast {
+    pub temperature: f64,
+    /// WMO weather interpretation code.
+    pub code: u32,
+    pub daily: Vec<DayForecast>,
+}
+
+pub type Done<T> = Box<dyn FnOnce(anyhow::Result<T>)>;
+
+pub trait WeatherProvider {
+    /// Stable id, part of the cache key.
+    fn id(&self) -> &'static str;
+
+    fn forecast(&self, location: &Location, units: Units, done: Done<Forecast>);
+
+    /// Resolve a place name to coordinates.
+    fn geocode(&self, query: &str, done: Done<Location>);
+}
+
+/// <https://open-meteo.com> — free, no API key.
+pub struct OpenMeteo {
+    http: Rc<dyn HttpClient>,
+}
+
+impl OpenMeteo {
+    pub fn new(http: Rc<dyn HttpClient>) -> Self {
+        Self { http }
+    }
+}
+
+impl WeatherProvider for OpenMeteo {
+    fn id(&self) -> &'static str {
+        "open-meteo"
+    }
+
+    fn forecast(&self, location: &Location, units: Units, done: Done<Forecast>) {
+        let unit = match units {
+            Units::Celsius => "celsius",
+            Units::Fahrenheit => "fahrenheit",
+        };
+        let url = format!(
+            "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}\
+             &current=temperature_2m,weather_code\
+             &daily=weather_code,temperature_2m_max,temperature_2m_min\
+             &timezone=auto&forecast_days=4&temperature_unit={unit}",
+            location.latitude, location.longitude
+        );
+        self.http.send(
+            HttpRequest::get(url),
+            Box::new(move |result| {
+                done(result.and_then(|resp| {
+                    if !resp.ok() {
+                        anyhow::bail!("HTTP {}", resp.status);
+                    }
+                    parse_open_meteo(&resp.body)
+                }))
+            }),
+        );
+    }
+
+    fn geocode(&self, query: &str, done: Done<Location>) {
+        let url = format!(
+            "https://geocoding-api.open-meteo.com/v1/search?count=1&name={}",
+            urlencoding::encode(query.trim())
+        );
+        self.http.send(
+            HttpRequest::get(url),
+            Box::new(move |result| {
+                done(result.and_then(|resp| {
+                    if !resp.ok() {
+                        anyhow::bail!("HTTP {}", resp.status);
+                    }
+                    parse_open_meteo_geocoding(&resp.body)
+                }))
+            }),
+        );
+    }
+}
+
+/// Answers from fixed Open-Meteo responses; no network.
+#[cfg(test)]
+pub struct FixtureProvider {
+    forecast: String,
+    geocoding: String,
+}
+
+#[cfg(test)]
+impl FixtureProvider {
+    pub fn new(forecast: impl Into<String>, geocoding: impl Into<String>) -> Self {
+        Self {
+            forecast: forecast.into(),
+            geocoding: geocoding.into(),
+        }
+    }
+
+    /// The sample responses in `fixtures/`.
+    pub fn sample() -> Self {
+        Self::new(
+            include_str!("../fixtures/open-meteo-forecast.json"),
+            include_str!("../fixtures/open-meteo-geocoding.json"),
+        )
+    }
+}
+
+#[cfg(test)]
+impl WeatherProvider for FixtureProvider {
+    fn id(&self) -> &'static str {
+        "fixture"
+    }
+
+    fn forecast(&self, _location: &Location, _units: Units, done: Done<Forecast>) {
+        done(parse_open_meteo(&self.forecast));
+    }
+
+    fn geocode(&self, _query: &str, done: Done<Location>) {
+        done(parse_open_meteo_geocoding(&self.geocoding));
+    }
+}
+
+#[derive(Deserialize)]
+struct OmResponse {
+    current: OmCurrent,
+    daily: OmDaily,
+}
+
+#[derive(Deserialize)]
+struct OmCurrent {
+    temperature_2m: f64,
+    weather_code: u32,
+}
+
+#[derive(Deserialize)]
+struct OmDaily {
+    time: Vec<String>,
+    weather_code: Vec<u32>,
+    temperature_2m_max: Vec<f64>,
+    temperature_2m_min: Vec<f64>,
+}
+
+pub fn parse_open_meteo(json: &str) -> anyhow::Result<Forecast> {
+    let r: OmResponse = serde_json::from_str(json).context("unexpected forecast response")?;
+    let daily = r
+        .daily
+        .time
+        .into_iter()
+        .zip(r.daily.weather_code)
+        .zip(
+            r.daily
+                .temperature_2m_min
+                .into_iter()
+                .zip(r.daily.temperature_2m_max),
+        )
+        .map(|((date, code), (min, max))| DayForecast {
+            date,
+            code,
+            min,
+            max,
+        })
+        .collect();
+    Ok(Forecast {
+        temperature: r.current.temperature_2m,
+        code: r.current.weather_code,
+        daily,
+    })
+}
+
+#[derive(Deserialize)]
+struct OmGeocoding {
+    #[serde(default)]
+    results: Vec<OmPlace>,
+}
+
+#[derive(Deserialize)]
+struct OmPlace {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    #[serde(default)]
+    country: String,
+}
+
+pub fn parse_open_meteo_geocoding(json: &str) -> anyhow::Result<Location> {
+    let r: OmGeocoding = serde_json::from_str(json).context("unexpected geocoding response")?;
+    let place = r.results.into_iter().next().context("no matching place")?;
+    Ok(Location {
+        name: if place.country.is_empty() {
+            place.name
+        } else {
+            format!("{}, {}", place.name, place.country)
+        },
+        latitude: place.latitude,
+        longitude: place.longitude,
+    })
+}
+
+/// Short description and symbol for a WMO weather code.
+pub fn describe(code: u32) -> (&'static str, &'static str) {
+    match code {
+        0 => ("Clear", "☀"),
+        1 => ("Mainly clear", "🌤"),
+        2 => ("Partly cloudy", "⛅"),
+        3 => ("Overcast", "☁"),
+        45 | 48 => ("Fog", "🌫"),
+        51..=57 => ("Drizzle", "🌦"),
+        61..=67 => ("Rain", "🌧"),
+        71..=77 => ("Snow", "🌨"),
+        80..=82 => ("Showers", "🌦"),
+        85 | 86 => ("Snow showers", "🌨"),
+        95..=99 => ("Thunderstorm", "⛈"),
+        _ => ("Unknown", "·"),
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeatherCache {
+    /// Provider, location and units the forecast was fetched for.
+    pub key: String,
+    /// Unix seconds.
+    pub fetched_at: u64,
+    pub forecast: Option<Forecast>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Serves the cached forecast and refetches once it is older than the TTL.
+pub struct WeatherStore {
+    /// Storage key of the dashboard this store was created for.
+    key: String,
+    pub cache: Signal<WeatherCache>,
+    in_flight: Rc<Cell<bool>>,
+}
+
+impl Default for WeatherStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WeatherStore {
+    pub fn new() -> Self {
+        let key = storage::scoped(CACHE_KEY);
+        Self {
+            cache: signal(storage::load_json(&key)),
+            key,
+            in_flight: Rc::new(Cell::new(false)),
+        }
+    }
+
+    pub fn cache_key(provider: &dyn WeatherProvider, location: &Location, units: Units) -> String {
+        format!(
+            "{}:{:.3},{:.3},{}",
+            provider.id(),
+            location.latitude,
+            location.longitude,
+            units.symbol()
+        )
+    }
+
+    pub fn refresh_if_stale(
+        &self,
+        provider: &dyn WeatherProvider,
+        location: &Location,
+        units: Units,
+        ttl_secs: u64,
+        now: u64,
+    ) {
+        let key = Self::cache_key(provider, location, units);
+        let cache = self.cache.get();
+        let fresh = cache.key == key && now.saturating_sub(cache.fetched_at) < ttl_secs;
+        if fresh || self.in_flight.get() {
+            return;
+        }
+
+        self.in_flight.set(true);
+        let store = self.cache.clone();
+        let in_flight = self.in_flight.clone();
+        let storage_key = self.key.clone();
+        provider.forecast(
+            location,
+            units,
+            Box::new(move |result| {
+                in_flight.set(false);
+                store.update(|c| {
+                    // A failed refresh for the same place keeps showing the last forecast.
+                    if c.key != key {
+                        c.forecast = None;
+                    }
+                    c.key = key;
+                    c.fetched_at = now;
+                    match result {
+                        Ok(f) => {
+                            c.forecast = Some(f);
+                            c.error = None;
+                        }
+                        Err(e) => c.error = Some(e.to_string()),
+                    }
+                });
+                storage::save_json(&storage_key, &store.get());
+            }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::{StubHttp, response};
+
+    const FORECAST: &str = include_str!("../fixtures/open-meteo-forecast.json");
+
+    fn berlin() -> Location {
+        Location {
+            name: "Berlin, Germany".to_string(),
+            latitude: 52.52,
+            longitude: 13.41,
+        }
+    }
+
+    #[test]
+    fn parses_fixtures() {
+        let f = parse_open_meteo(FORECAST).unwrap();
+        assert_eq!(f.temperature, 17.4);
+        assert_eq!(f.code, 2);
+        assert_eq!(f.daily.len(), 4);
+        assert_eq!(
+            f.daily[1],
+            DayForecast {
+                date: "2024-05-07".to_string(),
+                code: 61,
+                min: 10.1,
+                max: 15.3,
+            }
+        );
+
+        let place =
+            parse_open_meteo_geocoding(include_str!("../fixtures/open-meteo-geocoding.json"))
+                .unwrap();
+        assert_eq!(place.name, "Berlin, Germany");
+        assert!((place.latitude - 52.52).abs() < 0.01);
+
+        assert!(parse_open_meteo("{}").is_err());
+        assert_eq!(
+            parse_open_meteo_geocoding(r#"{"generationtime_ms":0.1}"#)
+                .unwrap_err()
+                .to_string(),
+            "no matching place"
+        );
+    }
+
+    #[test]
+    fn cached_forecast_expires_after_ttl() {
+        let provider = FixtureProvider::sample();
+        let store = WeatherStore::new();
+        store.refresh_if_stale(&provider, &berlin(), Units::Celsius, 600, 1_000);
+        assert_eq!(store.cache.get().fetched_at, 1_000);
+        assert!(store.cache.get().forecast.is_some());
+
+        store.refresh_if_stale(&provider, &berlin(), Units::Celsius, 600, 1_599);
+        assert_eq!(store.cache.get().fetched_at, 1_000);
+        store.refresh_if_stale(&provider, &berlin(), Units::Celsius, 600, 1_600);
+        assert_eq!(store.cache.get().fetched_at, 1_600);
+
+        // Other units are another forecast, however fresh this one is.
+        store.refresh_if_stale(&provider, &berlin(), Units::Fahrenheit, 600, 1_601);
+        assert_eq!(store.cache.get().fetched_at, 1_601);
+        assert_eq!(
+            store.cache.get().key,
+            WeatherStore::cache_key(&provider, &berlin(), Units::Fahrenheit)
+        );
+
+        // The cache outlives the store.
+        assert_eq!(WeatherStore::new().cache.get(), store.cache.get());
+    }
+
+    #[test]
+    fn each_dashboard_keeps_its_own_forecast() {
+        let provider = FixtureProvider::sample();
+        storage::set_dashboard("travel");
+        let paris = Location {
+            name: "Paris".to_string(),
+            latitude: 48.85,
+            longitude: 2.35,
+        };
+        WeatherStore::new().refresh_if_stale(&provider, &paris, Units::Celsius, 600, 1_000);
+
+        storage::set_dashboard(crate::dashboards::HOME);
+        WeatherStore::new().refresh_if_stale(&provider, &berlin(), Units::Celsius, 600, 1_000);
+
+        storage::set_dashboard("travel");
+        assert_eq!(
+            WeatherStore::new().cache.get().key,
+            WeatherStore::cache_key(&provider, &paris, Units::Celsius)
+        );
+    }
+
+    #[test]
+    fn failed_refresh_keeps_forecast_for_same_place() {
+        let store = WeatherStore::new();
+        store.refresh_if_stale(
+            &FixtureProvider::sample(),
+            &berlin(),
+            Units::Celsius,
+            600,
+            1_000,
+        );
+        let broken = FixtureProvider::new("not json", "");
+        store.refresh_if_stale(&broken, &berlin(), Units::Celsius, 600, 2_000);
+        let cache = store.cache.get();
+        assert_eq!(cache.fetched_at, 2_000);
+        assert!(cache.forecast.is_some());
+        assert_eq!(cache.error.as_deref(), Some("unexpected forecast response"));
+    }
+
+    #[test]
+    fn open_meteo_requests_units() {
+        let http = StubHttp::new(|_| Ok(response(200, FORECAST)));
+        let got = Rc::new(Cell::new(None));
+        OpenMeteo::new(http.clone()).forecast(&berlin(), Units::Fahrenheit, {
+            let got = got.clone();
+            Box::new(move |r| got.set(Some(r.map(|f| f.temperature).ok())))
+        });
+        assert_eq!(got.get(), Some(Some(17.4)));
+        let url = &http.sent()[0].url;
+        assert!(url.contains("latitude=52.52&longitude=13.41"));
+        assert!(url.ends_with("&temperature_unit=fahrenheit"));
+    }
+}
@@ -0,0 +1,177 @@
+use repose_core::prelude::*;
+use repose_ui::*;
+use serde::{Deserialize, Serialize};
+
+use super::{SwitchRow, Widget, WidgetConfig, WidgetKind};
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClockConfig {
+    pub hour24: bool,
+    pub seconds: bool,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            hour24: true,
+            seconds: false,
+        }
+    }
+}
+
+pub struct ClockWidget {
+    config: WidgetConfig<ClockConfig>,
+}
+
+impl ClockWidget {
+    pub fn new() -> Self {
+        Self {
+            config: WidgetConfig::load(WidgetKind::Clock),
+        }
+    }
+}
+
+impl Widget for ClockWidget {
+    fn kind(&self) -> WidgetKind {
+        WidgetKind::Clock
+    }
+
+    fn view(&self) -> View {
+        let now = js_sys::Date::new_0();
+        Text(format_time(
+            now.get_hours(),
+            now.get_minutes(),
+            now.get_seconds(),
+            &self.config.get(),
+        ))
+        .size(44.0)
+        .single_line()
+        .color(theme().on_surface)
+    }
+
+    fn settings(&self) -> View {
+        let cfg = self.config.get();
+        Column(Modifier::new().fill_max_width()).child((
+            SwitchRow("24-hour clock", cfg.hour24, {
+                let config = self.config.clone();
+                move |on| config.update(|c| c.hour24 = on)
+            }),
+            SwitchRow("Show seconds", cfg.seconds, {
+                let config = self.config.clone();
+                move |on| config.update(|c| c.seconds = on)
+            }),
+        ))
+    }
+}
+
+fn format_time(h: u32, m: u32, s: u32, cfg: &ClockConfig) -> String {
+    let (h, suffix) = if cfg.hour24 {
+        (h, "")
+    } else {
+        let h12 = if h.is_multiple_of(12) { 12 } else { h % 12 };
+        (h12, if h < 12 { " AM" } else { " PM" })
+    };
+    if cfg.seconds {
+        format!("{h:02}:{m:02}:{s:02}{suffix}")
+    } else {
+        format!("{h:02}:{m:02}{suffix}")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DateConfig {
+    pub weekday: bool,
+    pub year: bool,
+}
+
+impl Default for DateConfig {
+    fn default() -> Self {
+        Self {
+            weekday: true,
+            year: false,
+        }
+    }
+}
+
+pub struct DateWidget {
+    config: WidgetConfig<DateConfig>,
+}
+
+impl DateWidget {
+    pub fn new() -> Self {
+        Self {
+            config: WidgetConfig::load(WidgetKind::Date),
+        }
+    }
+}
+
+impl Widget for DateWidget {
+    fn kind(&self) -> WidgetKind {
+        WidgetKind::Date
+    }
+
+    fn view(&self) -> View {
+        let now = js_sys::Date::new_0();
+        let cfg = self.config.get();
+
+        let mut out = String::new();
+        if cfg.weekday {
+            out.push_str(WEEKDAYS[now.get_day() as usize % 7]);
+            out.push_str(", ");
+        }
+        out.push_str(&format!(
+            "{} {}",
+            now.get_date(),
+            MONTHS[now.get_month() as usize % 12]
+        ));
+        if cfg.year {
+            out.push_str(&format!(" {}", now.get_full_year()));
+        }
+
+        Text(out)
+            .size(15.0)
+            .single_line()
+            .color(Color::from_hex("#9CA3AF"))
+    }
+
+    fn settings(&self) -> View {
+        let cfg = self.config.get();
+        Column(Modifier::new().fill_max_width()).child((
+            SwitchRow("Show weekday", cfg.weekday, {
+                let config = self.config.clone();
+                move |on| config.update(|c| c.weekday = on)
+            }),
+            SwitchRow("Show year", cfg.year, {
+                let config = self.config.clone();
+                move |on| config.update(|c| c.year = on)
+            }),
+        ))
+    }
+}
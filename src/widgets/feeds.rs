@@ -0,0 +1,401 @@
+use std::rc::Rc;
+
+use repose_core::{CursorIcon, PaddingValues, prelude::*};
+use repose_ui::*;
+use serde::{Deserialize, Serialize};
+
+use super::{SmallButton, Widget, WidgetConfig, WidgetContext, WidgetKind, unix_now};
+use crate::app::open_url;
+use crate::commands::CommandRegistry;
+use crate::feed::{FeedCache, FeedStore};
+
+/// Read item ids kept; older ones are forgotten first.
+const MAX_READ: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedSub {
+    pub url: String,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+}
+
+fn default_limit() -> u32 {
+    8
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedsData {
+    pub feeds: Vec<FeedSub>,
+    pub refresh_mins: u32,
+    /// Optional CORS proxy; the URL-encoded feed URL is appended to it.
+    pub proxy: String,
+    /// Ids of items that have been opened or marked read.
+    pub read: Vec<String>,
+}
+
+impl Default for FeedsData {
+    fn default() -> Self {
+        Self {
+            feeds: Vec::new(),
+            refresh_mins: 30,
+            proxy: String::new(),
+            read: Vec::new(),
+        }
+    }
+}
+
+impl FeedsData {
+    fn mark_read(&mut self, ids: impl IntoIterator<Item = String>) {
+        for id in ids {
+            if !self.read.contains(&id) {
+                self.read.push(id);
+            }
+        }
+        if self.read.len() > MAX_READ {
+            let excess = self.read.len() - MAX_READ;
+            self.read.drain(..excess);
+        }
+    }
+
+    fn urls(&self) -> Vec<String> {
+        self.feeds.iter().map(|f| f.url.clone()).collect()
+    }
+}
+
+pub struct FeedsWidget {
+    data: WidgetConfig<FeedsData>,
+    store: Rc<FeedStore>,
+    new_url: Signal<String>,
+    epoch: Signal<u64>,
+}
+
+impl FeedsWidget {
+    pub fn new(ctx: WidgetContext) -> Self {
+        Self {
+            data: WidgetConfig::load(WidgetKind::Feeds),
+            store: Rc::new(FeedStore::new(ctx.http)),
+            new_url: signal(String::new()),
+            epoch: signal(0),
+        }
+    }
+}
+
+impl Widget for FeedsWidget {
+    fn kind(&self) -> WidgetKind {
+        WidgetKind::Feeds
+    }
+
+    fn commands(&self, reg: &mut CommandRegistry) {
+        reg.add("Feeds", "Refresh all feeds", {
+            let data = self.data.clone();
+            let store = self.store.clone();
+            move || {
+                let d = data.get();
+                for url in d.urls() {
+                    store.fetch(&url, &d.proxy, unix_now());
+                }
+            }
+        });
+        reg.add("Feeds", "Mark all feed items read", {
+            let data = self.data.clone();
+            let store = self.store.clone();
+            move || {
+                let ids: Vec<String> = store
+                    .cache
+                    .get()
+                    .values()
+                    .flat_map(|c| c.items.iter().map(|i| i.id.clone()))
+                    .collect();
+                data.update(|d| d.mark_read(ids));
+            }
+        });
+    }
+
+    fn view(&self) -> View {
+        let data = self.data.get();
+        self.store.refresh_due(
+            &data.urls(),
+            u64::from(data.refresh_mins.max(1)) * 60,
+            &data.proxy,
+            unix_now(),
+        );
+        let cache = self.store.cache.get();
+
+        let sections = if data.feeds.is_empty() {
+            vec![
+                Text("No feeds yet — add one in the widget settings (⚙)")
+                    .size(13.0)
+                    .color(Color::from_hex("#6B7280")),
+            ]
+        } else {
+            data.feeds
+                .iter()
+                .map(|sub| {
+                    FeedSection(sub, cache.get(&sub.url), &data.read, self.data.clone(), {
+                        let store = self.store.clone();
+                        let url = sub.url.clone();
+                        let proxy = data.proxy.clone();
+                        move || store.fetch(&url, &proxy, unix_now())
+                    })
+                })
+                .collect()
+        };
+
+        Box(Modifier::new()
+            .fill_max_width()
+            .background(theme().surface)
+            .border(1.0, theme().outline, 12.0)
+            .clip_rounded(12.0)
+            .padding(16.0))
+        .child(Column(Modifier::new().fill_max_width()).child(sections))
+    }
+
+    fn settings(&self) -> View {
+        let cfg = self.data.get();
+        let add = {
+            let data = self.data.clone();
+            let new_url = self.new_url.clone();
+            let epoch = self.epoch.clone();
+            Rc::new(move || {
+                let url = new_url.get().trim().to_string();
+                if !(url.starts_with("https://") || url.starts_with("http://")) {
+                    return;
+                }
+                data.update(|d| {
+                    if !d.feeds.iter().any(|f| f.url == url) {
+                        d.feeds.push(FeedSub {
+                            url,
+                            limit: default_limit(),
+                        });
+                    }
+                });
+                new_url.set(String::new());
+                epoch.update(|e| *e = e.wrapping_add(1));
+            })
+        };
+
+        let subs = cfg
+            .feeds
+            .iter()
+            .map(|sub| {
+                let url = sub.url.clone();
+                Column(Modifier::new().fill_max_width()).child((
+                    Row(Modifier::new()
+                        .fill_max_width()
+                        .align_items(AlignItems::Center))
+                    .child((
+                        Text(sub.url.clone())
+                            .size(12.0)
+                            .single_line()
+                            .overflow_ellipsize()
+                            .color(theme().on_surface)
+                            .modifier(Modifier::new().weight(1.0).min_width(0.0)),
+                        Text(format!("{} items", sub.limit))
+                            .size(12.0)
+                            .color(Color::from_hex("#6B7280")),
+                        SmallButton("×", {
+                            let data = self.data.clone();
+                            let store = self.store.clone();
+                            let url = url.clone();
+                            move || {
+                                data.update(|d| d.feeds.retain(|f| f.url != url));
+                                store.retain(&data.get().urls());
+                            }
+                        }),
+                    )),
+                    Slider(sub.limit as f32, (1.0, 30.0), Some(1.0), {
+                        let data = self.data.clone();
+                        move |v| {
+                            data.update(|d| {
+                                if let Some(f) = d.feeds.iter_mut().find(|f| f.url == url) {
+                                    f.limit = v.round() as u32;
+                                }
+                            })
+                        }
+                    }),
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        Column(Modifier::new().fill_max_width()).child((
+            Column(Modifier::new().fill_max_width()).child(subs),
+            Row(Modifier::new()
+                .fill_max_width()
+                .align_items(AlignItems::Center)
+                .padding_values(PaddingValues {
+                    top: 4.0,
+                    bottom: 8.0,
+                    ..Default::default()
+                }))
+            .child((
+                TextField(
+                    "Feed URL (RSS or Atom)",
+                    Modifier::new()
+                        .key(0xFEED_u64 ^ self.epoch.get().rotate_left(16))
+                        .height(36.0)
+                        .weight(1.0)
+                        .min_width(0.0)
+                        .background(Color::from_hex("#0F172A"))
+                        .border(1.0, theme().outline, 8.0)
+                        .clip_rounded(8.0),
+                    Some({
+                        let new_url = self.new_url.clone();
+                        move |s| new_url.set(s)
+                    }),
+                    Some({
+                        let add = add.clone();
+                        move |_: String| add()
+                    }),
+                ),
+                SmallButton("+", move || add()),
+            )),
+            Text(format!("Refresh every {} min", cfg.refresh_mins))
+                .size(13.0)
+                .color(Color::from_hex("#9CA3AF")),
+            Slider(cfg.refresh_mins as f32, (5.0, 240.0), Some(5.0), {
+                let data = self.data.clone();
+                move |v| data.update(|d| d.refresh_mins = v.round() as u32)
+            }),
+            TextField(
+                if cfg.proxy.is_empty() {
+                    "CORS proxy prefix (optional)".to_string()
+                } else {
+                    cfg.proxy.clone()
+                },
+                Modifier::new()
+                    .key(0xFEED_0001_u64)
+                    .height(36.0)
+                    .fill_max_width()
+                    .background(Color::from_hex("#0F172A"))
+                    .border(1.0, theme().outline, 8.0)
+                    .clip_rounded(8.0),
+                Some({
+                    let data = self.data.clone();
+                    move |s: String| data.update(|d| d.proxy = s.trim().to_string())
+                }),
+                None::<fn(String)>,
+            ),
+        ))
+    }
+}
+
+fn FeedSection(
+    sub: &FeedSub,
+    cache: Option<&FeedCache>,
+    read: &[String],
+    data: WidgetConfig<FeedsData>,
+    on_refresh: impl Fn() + 'static,
+) -> View {
+    let title = cache
+        .map(|c| c.title.clone())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| sub.url.clone());
+    let items: Vec<_> = cache
+        .map(|c| c.items.iter().take(sub.limit as usize).cloned().collect())
+        .unwrap_or_default();
+    let unread: Vec<String> = items
+        .iter()
+        .filter(|i| !read.contains(&i.id))
+        .map(|i| i.id.clone())
+        .collect();
+
+    let status = match cache {
+        None => Some("Loading…".to_string()),
+        Some(c) => c.error.as_ref().map(|e| format!("Couldn't refresh: {e}")),
+    };
+
+    let rows = items
+        .into_iter()
+        .map(|item| {
+            let is_unread = unread.contains(&item.id);
+            let data = data.clone();
+            Row(Modifier::new()
+                .fill_max_width()
+                .min_height(28.0)
+                .align_items(AlignItems::Center)
+                .clickable()
+                .on_pointer_down({
+                    let id = item.id.clone();
+                    let link = item.link.clone();
+                    move |_| {
+                        data.update(|d| d.mark_read([id.clone()]));
+                        if !link.is_empty() {
+                            open_url(&link);
+                        }
+                    }
+                })
+                .cursor(CursorIcon::Pointer))
+            .child((
+                Box(Modifier::new()
+                    .size(6.0, 6.0)
+                    .clip_rounded(3.0)
+                    .background(if is_unread {
+                        theme().primary
+                    } else {
+                        Color(0, 0, 0, 0)
+                    })),
+                Text(item.title)
+                    .size(14.0)
+                    .single_line()
+                    .overflow_ellipsize()
+                    .color(if is_unread {
+                        theme().on_surface
+                    } else {
+                        Color::from_hex("#6B7280")
+                    })
+                    .modifier(Modifier::new().weight(1.0).min_width(0.0).padding_values(
+                        PaddingValues {
+                            left: 8.0,
+                            ..Default::default()
+                        },
+                    )),
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    Column(
+        Modifier::new()
+            .fill_max_width()
+            .padding_values(PaddingValues {
+                bottom: 12.0,
+                ..Default::default()
+            }),
+    )
+    .child((
+        Row(Modifier::new()
+            .fill_max_width()
+            .align_items(AlignItems::Center))
+        .child((
+            Text(title)
+                .size(14.0)
+                .single_line()
+                .overflow_ellipsize()
+                .color(Color::from_hex("#9CA3AF"))
+                .modifier(Modifier::new().weight(1.0).min_width(0.0)),
+            if unread.is_empty() {
+                Box(Modifier::new())
+            } else {
+                Box(Modifier::new()
+                    .clickable()
+                    .on_pointer_down({
+                        let data = data.clone();
+                        let unread = unread.clone();
+                        move |_| data.update(|d| d.mark_read(unread.clone()))
+                    })
+                    .cursor(CursorIcon::Pointer))
+                .child(
+                    Text(format!("{} unread · mark read", unread.len()))
+                        .size(12.0)
+                        .color(Color::from_hex("#6B7280")),
+                )
+            },
+            SmallButton("↻", on_refresh),
+        )),
+        match status {
+            Some(s) => Text(s).size(12.0).color(Color::from_hex("#6B7280")),
+            None => Box(Modifier::new()),
+        },
+        Column(Modifier::new().fill_max_width()).child(rows),
+    ))
+}
@@ -0,0 +1,78 @@
+use repose_core::prelude::*;
+use repose_ui::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Widget, WidgetConfig, WidgetKind};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GreetingConfig {
+    pub name: String,
+}
+
+pub struct GreetingWidget {
+    config: WidgetConfig<GreetingConfig>,
+}
+
+impl GreetingWidget {
+    pub fn new() -> Self {
+        Self {
+            config: WidgetConfig::load(WidgetKind::Greeting),
+        }
+    }
+}
+
+impl Widget for GreetingWidget {
+    fn kind(&self) -> WidgetKind {
+        WidgetKind::Greeting
+    }
+
+    fn view(&self) -> View {
+        let hour = js_sys::Date::new_0().get_hours();
+        let name = self.config.get().name;
+        let text = if name.trim().is_empty() {
+            greeting_for_hour(hour).to_string()
+        } else {
+            format!("{}, {}", greeting_for_hour(hour), name.trim())
+        };
+
+        Text(text)
+            .size(28.0)
+            .single_line()
+            .overflow_ellipsize()
+            .color(theme().on_surface)
+    }
+
+    fn settings(&self) -> View {
+        let name = self.config.get().name;
+        // TextFields are uncontrolled, so the saved name doubles as the hint.
+        TextField(
+            if name.is_empty() {
+                "Your name".to_string()
+            } else {
+                name
+            },
+            Modifier::new()
+                .key(0x6EE7_u64)
+                .height(36.0)
+                .fill_max_width()
+                .background(Color::from_hex("#0F172A"))
+                .border(1.0, theme().outline, 8.0)
+                .clip_rounded(8.0),
+            Some({
+                let config = self.config.clone();
+                move |s: String| config.update(|c| c.name = s)
+            }),
+            None::<fn(String)>,
+        )
+    }
+}
+
+fn greeting_for_hour(hour: u32) -> &'static str {
+    match hour {
+        5..=11 => "Good morning",
+        12..=16 => "Good afternoon",
+        17..=21 => "Good evening",
+        _ => "Good night",
+    }
+}
@@ -0,0 +1,450 @@
+//! Pluggable page widgets.
+//!
+//! A widget renders a `View` and owns its config, persisted under its own
+//! storage key. Which widgets are shown, and in what order, lives in
+//! `WidgetLayout`, persisted separately. Both are per dashboard.
+
+#![allow(non_snake_case)]
+
+mod clock;
+mod feeds;
+mod greeting;
+mod notes;
+mod timer;
+mod todo;
+mod weather;
+
+use std::rc::Rc;
+
+use repose_core::{PaddingValues, prelude::*};
+use repose_ui::overlay::SnackbarController;
+use repose_ui::*;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::commands::CommandRegistry;
+use crate::net::HttpClient;
+use crate::storage;
+
+pub use clock::{ClockWidget, DateWidget};
+pub use feeds::FeedsWidget;
+pub use greeting::GreetingWidget;
+pub use notes::NotesWidget;
+pub use timer::TimerWidget;
+pub use todo::TodoWidget;
+pub use weather::WeatherWidget;
+
+const LAYOUT_KEY: &str = "startpage.widgets.layout.v1";
+
+pub trait Widget {
+    fn kind(&self) -> WidgetKind;
+
+    fn view(&self) -> View;
+
+    /// Controls for the widget's own config, shown in the customize panel.
+    fn settings(&self) -> View;
+
+    /// Add the widget's actions to the command palette. Only called while the
+    /// widget is shown.
+    fn commands(&self, _reg: &mut CommandRegistry) {}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WidgetKind {
+    Clock,
+    Date,
+    Greeting,
+    Notes,
+    Todo,
+    Timer,
+    Feeds,
+    Weather,
+}
+
+/// Where on the page a widget is placed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WidgetArea {
+    Header,
+    Page,
+}
+
+impl WidgetKind {
+    pub const ALL: [WidgetKind; 8] = [
+        WidgetKind::Greeting,
+        WidgetKind::Clock,
+        WidgetKind::Date,
+        WidgetKind::Weather,
+        WidgetKind::Notes,
+        WidgetKind::Todo,
+        WidgetKind::Timer,
+        WidgetKind::Feeds,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WidgetKind::Clock => "Clock",
+            WidgetKind::Date => "Date",
+            WidgetKind::Greeting => "Greeting",
+            WidgetKind::Notes => "Notes",
+            WidgetKind::Todo => "Todo",
+            WidgetKind::Timer => "Timer",
+            WidgetKind::Feeds => "Feeds",
+            WidgetKind::Weather => "Weather",
+        }
+    }
+
+    pub fn area(self) -> WidgetArea {
+        match self {
+            WidgetKind::Clock | WidgetKind::Date | WidgetKind::Greeting | WidgetKind::Weather => {
+                WidgetArea::Header
+            }
+            WidgetKind::Notes | WidgetKind::Todo | WidgetKind::Timer | WidgetKind::Feeds => {
+                WidgetArea::Page
+            }
+        }
+    }
+
+    pub fn config_key(self) -> String {
+        format!("startpage.widget.{}.v1", self.label().to_lowercase())
+    }
+}
+
+/// Which widgets are shown in each area, in display order.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WidgetLayout {
+    pub header: Vec<WidgetKind>,
+    pub page: Vec<WidgetKind>,
+}
+
+impl Default for WidgetLayout {
+    fn default() -> Self {
+        Self {
+            header: vec![WidgetKind::Greeting, WidgetKind::Clock, WidgetKind::Date],
+            page: vec![WidgetKind::Notes],
+        }
+    }
+}
+
+impl WidgetLayout {
+    fn area_mut(&mut self, area: WidgetArea) -> &mut Vec<WidgetKind> {
+        match area {
+            WidgetArea::Header => &mut self.header,
+            WidgetArea::Page => &mut self.page,
+        }
+    }
+
+    fn area(&self, area: WidgetArea) -> &[WidgetKind] {
+        match area {
+            WidgetArea::Header => &self.header,
+            WidgetArea::Page => &self.page,
+        }
+    }
+}
+
+pub fn load_layout() -> WidgetLayout {
+    storage::load_json(&storage::scoped(LAYOUT_KEY))
+}
+
+pub fn save_layout(layout: &WidgetLayout) {
+    storage::save_json(&storage::scoped(LAYOUT_KEY), layout)
+}
+
+/// A widget config held in a signal and written back to storage on every update.
+#[derive(Clone)]
+pub struct WidgetConfig<T: 'static> {
+    key: String,
+    value: Signal<T>,
+}
+
+impl<T: Clone + Default + Serialize + DeserializeOwned + 'static> WidgetConfig<T> {
+    pub fn load(kind: WidgetKind) -> Self {
+        let key = storage::scoped(&kind.config_key());
+        let value = signal(storage::load_json(&key));
+        Self { key, value }
+    }
+
+    pub fn get(&self) -> T {
+        self.value.get()
+    }
+
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        self.value.update(f);
+        storage::save_json(&self.key, &self.value.get());
+    }
+
+    /// Write `value` under this config's key without touching the in-memory copy.
+    pub fn persist(&self, value: &T) {
+        storage::save_json(&self.key, value);
+    }
+}
+
+/// Shared app handles a widget may use.
+#[derive(Clone)]
+pub struct WidgetContext {
+    pub snackbar: Rc<SnackbarController>,
+    pub http: Rc<dyn HttpClient>,
+}
+
+/// One instance of every widget, created once per app.
+pub struct Widgets {
+    all: Vec<Rc<dyn Widget>>,
+}
+
+impl Widgets {
+    pub fn new(ctx: WidgetContext) -> Self {
+        Self {
+            all: vec![
+                Rc::new(GreetingWidget::new()),
+                Rc::new(ClockWidget::new()),
+                Rc::new(DateWidget::new()),
+                Rc::new(NotesWidget::new()),
+                Rc::new(TodoWidget::new()),
+                Rc::new(TimerWidget::new(ctx.clone())),
+                Rc::new(FeedsWidget::new(ctx.clone())),
+                Rc::new(WeatherWidget::new(ctx)),
+            ],
+        }
+    }
+
+    pub fn get(&self, kind: WidgetKind) -> Option<Rc<dyn Widget>> {
+        self.all.iter().find(|w| w.kind() == kind).cloned()
+    }
+}
+
+/// Show/hide commands for every widget, plus the commands of the widgets on the page.
+pub fn register_commands(
+    widgets: &Widgets,
+    layout: Rc<Signal<WidgetLayout>>,
+    reg: &mut CommandRegistry,
+) {
+    let current = layout.get();
+    for kind in WidgetKind::ALL {
+        let shown = current.area(kind.area()).contains(&kind);
+        let verb = if shown { "Hide" } else { "Show" };
+        reg.add("Widgets", format!("{verb} {} widget", kind.label()), {
+            let layout = layout.clone();
+            move || {
+                layout.update(|l| {
+                    let v = l.area_mut(kind.area());
+                    match v.iter().position(|k| *k == kind) {
+                        Some(pos) => {
+                            v.remove(pos);
+                        }
+                        None => v.push(kind),
+                    }
+                });
+                save_layout(&layout.get());
+            }
+        });
+        if shown && let Some(w) = widgets.get(kind) {
+            w.commands(reg);
+        }
+    }
+}
+
+/// The header area: configured widgets stacked and centered.
+pub fn HeaderWidgets(widgets: &Widgets, layout: &WidgetLayout) -> View {
+    let header = Column(
+        Modifier::new()
+            .fill_max_width()
+            .align_items(AlignItems::Center)
+            .padding_values(PaddingValues {
+                top: 40.0,
+                bottom: 32.0,
+                ..Default::default()
+            }),
+    );
+
+    if layout.header.is_empty() {
+        return header.child(Text("Startpage").size(32.0).color(theme().on_surface));
+    }
+
+    header.child(
+        layout
+            .header
+            .iter()
+            .filter_map(|kind| widgets.get(*kind))
+            .map(|w| w.view())
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// The page area below the bookmarks: configured widgets stacked full width.
+pub fn PageWidgets(widgets: &Widgets, layout: &WidgetLayout) -> View {
+    Column(Modifier::new().fill_max_width()).child(
+        layout
+            .page
+            .iter()
+            .filter_map(|kind| widgets.get(*kind))
+            .map(|w| {
+                Box(Modifier::new()
+                    .fill_max_width()
+                    .padding_values(PaddingValues {
+                        top: 24.0,
+                        ..Default::default()
+                    }))
+                .child(w.view())
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Lets the user pick which widgets are shown, reorder them and edit their config.
+pub fn WidgetsSettings(widgets: &Widgets, layout: Rc<Signal<WidgetLayout>>) -> View {
+    Column(Modifier::new().fill_max_width()).child((
+        AreaSection(
+            "Header widgets",
+            WidgetArea::Header,
+            widgets,
+            layout.clone(),
+        ),
+        AreaSection("Page widgets", WidgetArea::Page, widgets, layout),
+    ))
+}
+
+fn AreaSection(
+    title: &str,
+    area: WidgetArea,
+    widgets: &Widgets,
+    layout: Rc<Signal<WidgetLayout>>,
+) -> View {
+    let current = layout.get();
+    let shown = current.area(area).to_vec();
+    let mut order = shown.clone();
+    order.extend(
+        WidgetKind::ALL
+            .iter()
+            .filter(|k| k.area() == area && !shown.contains(k)),
+    );
+
+    let rows = order
+        .into_iter()
+        .map(|kind| {
+            let enabled = shown.contains(&kind);
+            let update = {
+                let layout = layout.clone();
+                move |f: &dyn Fn(&mut Vec<WidgetKind>)| {
+                    layout.update(|l| f(l.area_mut(area)));
+                    save_layout(&layout.get());
+                }
+            };
+
+            Column(
+                Modifier::new()
+                    .fill_max_width()
+                    .padding_values(PaddingValues {
+                        bottom: 8.0,
+                        ..Default::default()
+                    }),
+            )
+            .child((
+                Row(Modifier::new()
+                    .fill_max_width()
+                    .align_items(AlignItems::Center))
+                .child((
+                    Checkbox(enabled, {
+                        let update = update.clone();
+                        move |on| {
+                            update(&|h| {
+                                h.retain(|k| *k != kind);
+                                if on {
+                                    h.push(kind);
+                                }
+                            })
+                        }
+                    }),
+                    Text(kind.label())
+                        .size(14.0)
+                        .color(theme().on_surface)
+                        .modifier(Modifier::new().padding_values(PaddingValues {
+                            left: 8.0,
+                            ..Default::default()
+                        })),
+                    Spacer(),
+                    if enabled {
+                        Row(Modifier::new()).child((
+                            SmallButton("↑", {
+                                let update = update.clone();
+                                move || update(&|h| move_item(h, kind, -1))
+                            }),
+                            SmallButton("↓", move || update(&|h| move_item(h, kind, 1))),
+                        ))
+                    } else {
+                        Box(Modifier::new())
+                    },
+                )),
+                match widgets.get(kind) {
+                    Some(w) if enabled => {
+                        Box(Modifier::new()
+                            .fill_max_width()
+                            .padding_values(PaddingValues {
+                                left: 28.0,
+                                top: 4.0,
+                                ..Default::default()
+                            }))
+                        .child(w.settings())
+                    }
+                    _ => Box(Modifier::new()),
+                },
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    Column(Modifier::new().fill_max_width()).child((
+        Text(title)
+            .size(14.0)
+            .color(Color::from_hex("#9CA3AF"))
+            .modifier(Modifier::new().padding_values(PaddingValues {
+                top: 4.0,
+                bottom: 12.0,
+                ..Default::default()
+            })),
+        Column(Modifier::new().fill_max_width()).child(rows),
+    ))
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A labelled on/off row used by widget settings.
+pub fn SwitchRow(label: &str, checked: bool, on_change: impl Fn(bool) + 'static) -> View {
+    Row(Modifier::new()
+        .fill_max_width()
+        .align_items(AlignItems::Center)
+        .padding_values(PaddingValues {
+            top: 4.0,
+            bottom: 4.0,
+            ..Default::default()
+        }))
+    .child((
+        Text(label).size(13.0).color(Color::from_hex("#9CA3AF")),
+        Spacer(),
+        Switch(checked, on_change),
+    ))
+}
+
+pub fn SmallButton(label: &str, on_click: impl Fn() + 'static) -> View {
+    Box(Modifier::new()
+        .size(28.0, 28.0)
+        .clip_rounded(6.0)
+        .align_items(AlignItems::Center)
+        .justify_content(JustifyContent::Center)
+        .clickable()
+        .on_pointer_down(move |_| on_click()))
+    .child(Text(label).size(14.0).color(Color::from_hex("#9CA3AF")))
+}
+
+fn move_item(items: &mut [WidgetKind], kind: WidgetKind, delta: isize) {
+    let Some(pos) = items.iter().position(|k| *k == kind) else {
+        return;
+    };
+    let target = pos as isize + delta;
+    if target >= 0 && (target as usize) < items.len() {
+        items.swap(pos, target as usize);
+    }
+}
@@ -0,0 +1,477 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use repose_core::{CursorIcon, PaddingValues, prelude::*};
+use repose_ui::*;
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+use super::{SmallButton, Widget, WidgetConfig, WidgetKind};
+use crate::app::open_url;
+
+/// How long typing must pause before the draft is written to storage.
+const AUTOSAVE_DEBOUNCE: Duration = Duration::from_millis(600);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note {
+    pub id: u64,
+    pub body: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotesData {
+    pub notes: Vec<Note>,
+    pub active: usize,
+}
+
+impl Default for NotesData {
+    fn default() -> Self {
+        Self {
+            notes: vec![Note {
+                id: 1,
+                body: String::new(),
+            }],
+            active: 0,
+        }
+    }
+}
+
+impl NotesData {
+    fn active_index(&self) -> usize {
+        self.active.min(self.notes.len().saturating_sub(1))
+    }
+
+    /// Append `text` as new lines at the end of the active note.
+    fn append(&mut self, text: &str) {
+        let i = self.active_index();
+        let Some(note) = self.notes.get_mut(i) else {
+            return;
+        };
+        if !note.body.is_empty() && !note.body.ends_with('\n') {
+            note.body.push('\n');
+        }
+        note.body.push_str(text.trim_end());
+    }
+}
+
+/// Tabbed scratchpad.
+///
+/// Text fields can't be seeded with existing content, so the note is shown
+/// rendered and new text is written in a composer underneath. The composer's
+/// draft is autosaved (debounced) as part of the note, so closing the tab
+/// mid-sentence loses nothing.
+pub struct NotesWidget {
+    data: WidgetConfig<NotesData>,
+    draft: Signal<String>,
+    epoch: Signal<u64>,
+    last_edit: Rc<Cell<Option<Instant>>>,
+}
+
+impl NotesWidget {
+    pub fn new() -> Self {
+        Self {
+            data: WidgetConfig::load(WidgetKind::Notes),
+            draft: signal(String::new()),
+            epoch: signal(0),
+            last_edit: Rc::new(Cell::new(None)),
+        }
+    }
+
+    fn autosave_if_idle(&self) {
+        let Some(t) = self.last_edit.get() else {
+            return;
+        };
+        if t.elapsed() < AUTOSAVE_DEBOUNCE {
+            return;
+        }
+        self.last_edit.set(None);
+        let mut merged = self.data.get();
+        let draft = self.draft.get();
+        if !draft.trim().is_empty() {
+            merged.append(&draft);
+        }
+        self.data.persist(&merged);
+    }
+}
+
+/// Moves the composer's draft into the active note and clears the composer.
+fn commit_draft(
+    data: &WidgetConfig<NotesData>,
+    draft: &Signal<String>,
+    epoch: &Signal<u64>,
+    last_edit: &Cell<Option<Instant>>,
+) {
+    let text = draft.get();
+    if !text.trim().is_empty() {
+        data.update(|d| d.append(&text));
+    }
+    draft.set(String::new());
+    epoch.update(|e| *e = e.wrapping_add(1));
+    last_edit.set(None);
+}
+
+impl Widget for NotesWidget {
+    fn kind(&self) -> WidgetKind {
+        WidgetKind::Notes
+    }
+
+    fn view(&self) -> View {
+        self.autosave_if_idle();
+
+        let data = self.data.get();
+        let active = data.active_index();
+        let note = data.notes.get(active).cloned();
+
+        let commit = {
+            let data = self.data.clone();
+            let draft = self.draft.clone();
+            let epoch = self.epoch.clone();
+            let last_edit = self.last_edit.clone();
+            Rc::new(move || commit_draft(&data, &draft, &epoch, &last_edit))
+        };
+
+        let tabs = data
+            .notes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                NoteTab(&note_title(n, i), i == active, {
+                    let data = self.data.clone();
+                    let commit = commit.clone();
+                    move || {
+                        commit();
+                        data.update(|d| d.active = i);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let lines = note
+            .as_ref()
+            .map(|n| {
+                n.body
+                    .lines()
+                    .enumerate()
+                    .map(|(i, line)| NoteLine(line, i, self.data.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let note_id = note.as_ref().map(|n| n.id).unwrap_or(0);
+
+        Box(Modifier::new()
+            .fill_max_width()
+            .background(theme().surface)
+            .border(1.0, theme().outline, 12.0)
+            .clip_rounded(12.0)
+            .padding(16.0))
+        .child(
+            Column(Modifier::new().fill_max_width()).child((
+                Row(Modifier::new()
+                    .fill_max_width()
+                    .align_items(AlignItems::Center)
+                    .flex_wrap(FlexWrap::Wrap)
+                    .padding_values(PaddingValues {
+                        bottom: 12.0,
+                        ..Default::default()
+                    }))
+                .child((
+                    Row(Modifier::new().flex_wrap(FlexWrap::Wrap)).child(tabs),
+                    SmallButton("+", {
+                        let data = self.data.clone();
+                        let commit = commit.clone();
+                        move || {
+                            commit();
+                            data.update(|d| {
+                                let id = d.notes.iter().map(|n| n.id).max().unwrap_or(0) + 1;
+                                d.notes.push(Note {
+                                    id,
+                                    body: String::new(),
+                                });
+                                d.active = d.notes.len() - 1;
+                            });
+                        }
+                    }),
+                    Spacer(),
+                    SmallButton("🗑", {
+                        let data = self.data.clone();
+                        let draft = self.draft.clone();
+                        let epoch = self.epoch.clone();
+                        move || {
+                            draft.set(String::new());
+                            epoch.update(|e| *e = e.wrapping_add(1));
+                            data.update(|d| {
+                                let i = d.active_index();
+                                if d.notes.len() > 1 {
+                                    d.notes.remove(i);
+                                    d.active = i.saturating_sub(1);
+                                } else if let Some(n) = d.notes.first_mut() {
+                                    n.body.clear();
+                                }
+                            });
+                        }
+                    }),
+                )),
+                Column(Modifier::new().fill_max_width()).child(lines),
+                TextArea(
+                    "Write a note… (- [ ] for a checklist item)",
+                    Modifier::new()
+                        .key(0x4E07E5_u64 ^ note_id.rotate_left(20) ^ self.epoch.get())
+                        .height(88.0)
+                        .fill_max_width()
+                        .background(Color::from_hex("#0F172A"))
+                        .border(1.0, theme().outline, 10.0)
+                        .clip_rounded(10.0),
+                    Some({
+                        let draft = self.draft.clone();
+                        let last_edit = self.last_edit.clone();
+                        move |s: String| {
+                            draft.set(s);
+                            last_edit.set(Some(Instant::now()));
+                        }
+                    }),
+                    None::<fn(String)>,
+                ),
+                Row(Modifier::new()
+                    .fill_max_width()
+                    .padding_values(PaddingValues {
+                        top: 8.0,
+                        ..Default::default()
+                    }))
+                .child((
+                    Spacer(),
+                    Button(Text("Add to note").size(13.0).color(theme().on_primary), {
+                        let commit = commit.clone();
+                        move || commit()
+                    })
+                    .modifier(
+                        Modifier::new()
+                            .padding_values(PaddingValues {
+                                left: 12.0,
+                                right: 12.0,
+                                top: 6.0,
+                                bottom: 6.0,
+                            })
+                            .background(theme().primary)
+                            .clip_rounded(8.0),
+                    ),
+                )),
+            )),
+        )
+    }
+
+    fn settings(&self) -> View {
+        Text(format!(
+            "{} note(s), saved in this browser",
+            self.data.get().notes.len()
+        ))
+        .size(13.0)
+        .color(Color::from_hex("#6B7280"))
+    }
+}
+
+fn NoteTab(title: &str, selected: bool, on_click: impl Fn() + 'static) -> View {
+    Box(Modifier::new()
+        .padding_values(PaddingValues {
+            left: 10.0,
+            right: 10.0,
+            top: 4.0,
+            bottom: 4.0,
+        })
+        .background(if selected {
+            Color(theme().primary.0, theme().primary.1, theme().primary.2, 48)
+        } else {
+            Color(0, 0, 0, 0)
+        })
+        .clip_rounded(999.0)
+        .clickable()
+        .on_pointer_down(move |_| on_click())
+        .cursor(CursorIcon::Pointer))
+    .child(Text(title).size(13.0).single_line().color(if selected {
+        theme().primary
+    } else {
+        Color::from_hex("#9CA3AF")
+    }))
+}
+
+/// One rendered line of a note, with a button to delete it.
+fn NoteLine(line: &str, index: usize, data: WidgetConfig<NotesData>) -> View {
+    let content = match parse_line(line) {
+        Line::Task { done, text } => Row(Modifier::new()
+            .weight(1.0)
+            .min_width(0.0)
+            .align_items(AlignItems::Center))
+        .child((
+            Checkbox(done, {
+                let data = data.clone();
+                move |_| data.update(|d| edit_line(d, index, toggle_task))
+            }),
+            Box(Modifier::new().width(8.0).height(1.0)),
+            Spans(text, if done { "#6B7280" } else { "#E5E7EB" }),
+        )),
+        Line::Heading(text) => Box(Modifier::new().weight(1.0).min_width(0.0))
+            .child(Text(text).size(16.0).color(theme().on_surface)),
+        Line::Text(text) => {
+            Box(Modifier::new().weight(1.0).min_width(0.0)).child(Spans(text, "#E5E7EB"))
+        }
+    };
+
+    Row(Modifier::new()
+        .fill_max_width()
+        .align_items(AlignItems::Center)
+        .min_height(28.0))
+    .child((
+        content,
+        SmallButton("×", move || {
+            data.update(|d| edit_line(d, index, |_| None));
+        }),
+    ))
+}
+
+/// Inline text with `[label](url)` and bare http(s) links made clickable.
+fn Spans(text: &str, color: &str) -> View {
+    Row(Modifier::new().flex_wrap(FlexWrap::Wrap)).child(
+        spans(text)
+            .into_iter()
+            .map(|span| match span {
+                Span::Text(t) => Text(t).size(14.0).color(Color::from_hex(color)),
+                Span::Link { label, url } => Box(Modifier::new()
+                    .clickable()
+                    .on_pointer_down(move |_| open_url(&url))
+                    .cursor(CursorIcon::Pointer))
+                .child(Text(label).size(14.0).color(theme().primary)),
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn note_title(note: &Note, index: usize) -> String {
+    let first = note
+        .body
+        .lines()
+        .map(|l| match parse_line(l) {
+            Line::Task { text, .. } | Line::Heading(text) | Line::Text(text) => text.trim(),
+        })
+        .find(|l| !l.is_empty());
+    match first {
+        Some(t) if t.chars().count() > 18 => {
+            format!("{}…", t.chars().take(17).collect::<String>())
+        }
+        Some(t) => t.to_string(),
+        None => format!("Note {}", index + 1),
+    }
+}
+
+/// Replace line `index` of the active note with `f(line)`, or remove it on `None`.
+fn edit_line(data: &mut NotesData, index: usize, f: impl Fn(&str) -> Option<String>) {
+    let i = data.active_index();
+    let Some(note) = data.notes.get_mut(i) else {
+        return;
+    };
+    let mut lines: Vec<String> = note.body.lines().map(str::to_string).collect();
+    if index >= lines.len() {
+        return;
+    }
+    match f(&lines[index]) {
+        Some(l) => lines[index] = l,
+        None => {
+            lines.remove(index);
+        }
+    }
+    note.body = lines.join("\n");
+}
+
+fn toggle_task(line: &str) -> Option<String> {
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let rest = line.trim_start();
+    Some(if let Some(t) = rest.strip_prefix("- [ ] ") {
+        format!("{indent}- [x] {t}")
+    } else if let Some(t) = rest
+        .strip_prefix("- [x] ")
+        .or_else(|| rest.strip_prefix("- [X] "))
+    {
+        format!("{indent}- [ ] {t}")
+    } else {
+        line.to_string()
+    })
+}
+
+enum Line<'a> {
+    Task { done: bool, text: &'a str },
+    Heading(&'a str),
+    Text(&'a str),
+}
+
+fn parse_line(line: &str) -> Line<'_> {
+    let t = line.trim_start();
+    if let Some(text) = t.strip_prefix("- [ ] ") {
+        Line::Task { done: false, text }
+    } else if let Some(text) = t
+        .strip_prefix("- [x] ")
+        .or_else(|| t.strip_prefix("- [X] "))
+    {
+        Line::Task { done: true, text }
+    } else if let Some(text) = t.strip_prefix("# ") {
+        Line::Heading(text)
+    } else {
+        Line::Text(line)
+    }
+}
+
+enum Span {
+    Text(String),
+    Link { label: String, url: String },
+}
+
+fn spans(text: &str) -> Vec<Span> {
+    let mut out = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        if let Some((label, url, len)) = markdown_link(rest) {
+            flush(&mut out, &mut plain);
+            out.push(Span::Link { label, url });
+            rest = &rest[len..];
+            continue;
+        }
+        if rest.starts_with("https://") || rest.starts_with("http://") {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let url = rest[..end].trim_end_matches(['.', ',', ')', ';']);
+            flush(&mut out, &mut plain);
+            out.push(Span::Link {
+                label: url.to_string(),
+                url: url.to_string(),
+            });
+            rest = &rest[url.len()..];
+            continue;
+        }
+        let ch = rest.chars().next().unwrap_or(' ');
+        plain.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    flush(&mut out, &mut plain);
+    out
+}
+
+fn flush(out: &mut Vec<Span>, plain: &mut String) {
+    if !plain.is_empty() {
+        out.push(Span::Text(std::mem::take(plain)));
+    }
+}
+
+/// Parses `[label](url)` at the start of `s`, returning the byte length consumed.
+fn markdown_link(s: &str) -> Option<(String, String, usize)> {
+    let inner = s.strip_prefix('[')?;
+    let close = inner.find("](")?;
+    let label = &inner[..close];
+    let after = &inner[close + 2..];
+    let end = after.find(')')?;
+    let url = after[..end].trim();
+    if label.contains(']') || !(url.starts_with("https://") || url.starts_with("http://")) {
+        return None;
+    }
+    Some((label.to_string(), url.to_string(), 1 + close + 2 + end + 1))
+}
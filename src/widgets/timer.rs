@@ -0,0 +1,485 @@
+use repose_core::{CursorIcon, PaddingValues, prelude::*};
+use repose_ui::*;
+use serde::{Deserialize, Serialize};
+use web_sys::{Notification, NotificationOptions, NotificationPermission};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use super::{SwitchRow, Widget, WidgetConfig, WidgetContext, WidgetKind};
+use crate::app::notify;
+use crate::commands::CommandRegistry;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimerMode {
+    #[default]
+    Pomodoro,
+    Countdown,
+    Stopwatch,
+}
+
+impl TimerMode {
+    const ALL: [TimerMode; 3] = [
+        TimerMode::Pomodoro,
+        TimerMode::Countdown,
+        TimerMode::Stopwatch,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            TimerMode::Pomodoro => "Pomodoro",
+            TimerMode::Countdown => "Countdown",
+            TimerMode::Stopwatch => "Stopwatch",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PomodoroPhase {
+    #[default]
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl PomodoroPhase {
+    fn label(self) -> &'static str {
+        match self {
+            PomodoroPhase::Work => "Focus",
+            PomodoroPhase::ShortBreak => "Short break",
+            PomodoroPhase::LongBreak => "Long break",
+        }
+    }
+}
+
+/// Timer config plus the running state.
+///
+/// A run is stored as the wall-clock time it (re)started plus whatever had
+/// elapsed before the last pause, so a reload picks up where it left off.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimerData {
+    pub mode: TimerMode,
+    pub countdown_mins: u32,
+    pub work_mins: u32,
+    pub short_break_mins: u32,
+    pub long_break_mins: u32,
+    /// Work sessions before a long break.
+    pub cycles: u32,
+    pub browser_notifications: bool,
+
+    /// Unix milliseconds when the current run segment started; `None` when stopped or paused.
+    pub started_at: Option<u64>,
+    pub elapsed_before_ms: u64,
+    pub phase: PomodoroPhase,
+    pub completed_work: u32,
+}
+
+impl Default for TimerData {
+    fn default() -> Self {
+        Self {
+            mode: TimerMode::default(),
+            countdown_mins: 10,
+            work_mins: 25,
+            short_break_mins: 5,
+            long_break_mins: 15,
+            cycles: 4,
+            browser_notifications: false,
+            started_at: None,
+            elapsed_before_ms: 0,
+            phase: PomodoroPhase::default(),
+            completed_work: 0,
+        }
+    }
+}
+
+impl TimerData {
+    fn elapsed_ms(&self, now: u64) -> u64 {
+        self.elapsed_before_ms + self.started_at.map_or(0, |t| now.saturating_sub(t))
+    }
+
+    /// Length of the current run, or `None` for the stopwatch.
+    fn target_ms(&self) -> Option<u64> {
+        let mins = match self.mode {
+            TimerMode::Stopwatch => return None,
+            TimerMode::Countdown => self.countdown_mins,
+            TimerMode::Pomodoro => match self.phase {
+                PomodoroPhase::Work => self.work_mins,
+                PomodoroPhase::ShortBreak => self.short_break_mins,
+                PomodoroPhase::LongBreak => self.long_break_mins,
+            },
+        };
+        Some(u64::from(mins.max(1)) * 60_000)
+    }
+
+    fn running(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    fn start(&mut self, now: u64) {
+        if self.started_at.is_none() {
+            self.started_at = Some(now);
+        }
+    }
+
+    fn pause(&mut self, now: u64) {
+        self.elapsed_before_ms = self.elapsed_ms(now);
+        self.started_at = None;
+    }
+
+    fn reset(&mut self) {
+        self.started_at = None;
+        self.elapsed_before_ms = 0;
+    }
+
+    /// Move to the next pomodoro phase; keeps running if it was running.
+    fn advance_phase(&mut self, now: u64) {
+        match self.phase {
+            PomodoroPhase::Work => {
+                self.completed_work += 1;
+                self.phase = if self.completed_work.is_multiple_of(self.cycles.max(1)) {
+                    PomodoroPhase::LongBreak
+                } else {
+                    PomodoroPhase::ShortBreak
+                };
+            }
+            PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => {
+                self.phase = PomodoroPhase::Work;
+            }
+        }
+        let was_running = self.running();
+        self.reset();
+        if was_running {
+            self.start(now);
+        }
+    }
+
+    /// If the current run has reached its target, advance and return a message for the user.
+    fn check_finished(&mut self, now: u64) -> Option<String> {
+        let target = self.target_ms()?;
+        if !self.running() || self.elapsed_ms(now) < target {
+            return None;
+        }
+        match self.mode {
+            TimerMode::Countdown => {
+                self.reset();
+                Some("Countdown finished".to_string())
+            }
+            TimerMode::Pomodoro => {
+                let finished = self.phase;
+                self.advance_phase(now);
+                Some(format!(
+                    "{} finished — {} next",
+                    finished.label(),
+                    self.phase.label().to_lowercase()
+                ))
+            }
+            TimerMode::Stopwatch => None,
+        }
+    }
+}
+
+pub struct TimerWidget {
+    data: WidgetConfig<TimerData>,
+    ctx: WidgetContext,
+}
+
+impl TimerWidget {
+    pub fn new(ctx: WidgetContext) -> Self {
+        Self {
+            data: WidgetConfig::load(WidgetKind::Timer),
+            ctx,
+        }
+    }
+}
+
+impl Widget for TimerWidget {
+    fn kind(&self) -> WidgetKind {
+        WidgetKind::Timer
+    }
+
+    fn commands(&self, reg: &mut CommandRegistry) {
+        let running = self.data.get().running();
+        reg.add(
+            "Timer",
+            if running {
+                "Pause timer"
+            } else {
+                "Start timer"
+            },
+            {
+                let config = self.data.clone();
+                move || {
+                    let now = unix_millis();
+                    config.update(|d| {
+                        if d.running() {
+                            d.pause(now)
+                        } else {
+                            d.start(now)
+                        }
+                    })
+                }
+            },
+        );
+        reg.add("Timer", "Reset timer", {
+            let config = self.data.clone();
+            move || config.update(|d| d.reset())
+        });
+        for mode in TimerMode::ALL {
+            reg.add("Timer", format!("Timer mode: {}", mode.label()), {
+                let config = self.data.clone();
+                move || {
+                    config.update(|d| {
+                        if d.mode != mode {
+                            d.mode = mode;
+                            d.reset();
+                        }
+                    })
+                }
+            });
+        }
+    }
+
+    fn view(&self) -> View {
+        let now = unix_millis();
+        let mut data = self.data.get();
+        if let Some(message) = data.check_finished(now) {
+            self.data.update(|d| *d = data.clone());
+            notify(&self.ctx.snackbar, &message);
+            if data.browser_notifications {
+                browser_notification(&message);
+            }
+        }
+
+        let elapsed = data.elapsed_ms(now);
+        let shown = match data.target_ms() {
+            Some(target) => target.saturating_sub(elapsed),
+            None => elapsed,
+        };
+        let subtitle = match data.mode {
+            TimerMode::Pomodoro => format!(
+                "{} · {} of {} done",
+                data.phase.label(),
+                data.completed_work % data.cycles.max(1),
+                data.cycles.max(1)
+            ),
+            TimerMode::Countdown => format!("{} min countdown", data.countdown_mins.max(1)),
+            TimerMode::Stopwatch => "Stopwatch".to_string(),
+        };
+
+        let modes = TimerMode::ALL
+            .iter()
+            .map(|&mode| {
+                ModeChip(mode.label(), data.mode == mode, {
+                    let config = self.data.clone();
+                    move || {
+                        config.update(|d| {
+                            if d.mode != mode {
+                                d.mode = mode;
+                                d.reset();
+                            }
+                        })
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Box(Modifier::new()
+            .fill_max_width()
+            .background(theme().surface)
+            .border(1.0, theme().outline, 12.0)
+            .clip_rounded(12.0)
+            .padding(16.0))
+        .child(
+            Column(
+                Modifier::new()
+                    .fill_max_width()
+                    .align_items(AlignItems::Center),
+            )
+            .child((
+                Row(Modifier::new()).child(modes),
+                Text(format_duration(shown))
+                    .size(40.0)
+                    .single_line()
+                    .color(theme().on_surface)
+                    .modifier(Modifier::new().padding_values(PaddingValues {
+                        top: 12.0,
+                        ..Default::default()
+                    })),
+                Text(subtitle).size(13.0).color(Color::from_hex("#9CA3AF")),
+                Row(Modifier::new().padding_values(PaddingValues {
+                    top: 12.0,
+                    ..Default::default()
+                }))
+                .child((
+                    TimerButton(if data.running() { "Pause" } else { "Start" }, true, {
+                        let config = self.data.clone();
+                        move || {
+                            let now = unix_millis();
+                            config.update(|d| {
+                                if d.running() {
+                                    d.pause(now)
+                                } else {
+                                    d.start(now)
+                                }
+                            })
+                        }
+                    }),
+                    Box(Modifier::new().width(8.0).height(1.0)),
+                    TimerButton("Reset", false, {
+                        let config = self.data.clone();
+                        move || config.update(|d| d.reset())
+                    }),
+                    if data.mode == TimerMode::Pomodoro {
+                        Row(Modifier::new()).child((
+                            Box(Modifier::new().width(8.0).height(1.0)),
+                            TimerButton("Skip", false, {
+                                let config = self.data.clone();
+                                move || config.update(|d| d.advance_phase(unix_millis()))
+                            }),
+                        ))
+                    } else {
+                        Box(Modifier::new())
+                    },
+                )),
+            )),
+        )
+    }
+
+    fn settings(&self) -> View {
+        let cfg = self.data.get();
+        let slider = |label: String, value: u32, max: f32, set: fn(&mut TimerData, u32)| {
+            let config = self.data.clone();
+            Column(Modifier::new().fill_max_width()).child((
+                Text(label).size(13.0).color(Color::from_hex("#9CA3AF")),
+                Slider(value as f32, (1.0, max), Some(1.0), move |v| {
+                    config.update(|d| set(d, v.round() as u32))
+                }),
+            ))
+        };
+
+        Column(Modifier::new().fill_max_width()).child((
+            slider(
+                format!("Countdown: {} min", cfg.countdown_mins),
+                cfg.countdown_mins,
+                120.0,
+                |d, v| d.countdown_mins = v,
+            ),
+            slider(
+                format!("Focus: {} min", cfg.work_mins),
+                cfg.work_mins,
+                90.0,
+                |d, v| d.work_mins = v,
+            ),
+            slider(
+                format!("Short break: {} min", cfg.short_break_mins),
+                cfg.short_break_mins,
+                30.0,
+                |d, v| d.short_break_mins = v,
+            ),
+            slider(
+                format!("Long break: {} min", cfg.long_break_mins),
+                cfg.long_break_mins,
+                60.0,
+                |d, v| d.long_break_mins = v,
+            ),
+            slider(
+                format!("Focus sessions before a long break: {}", cfg.cycles),
+                cfg.cycles,
+                8.0,
+                |d, v| d.cycles = v,
+            ),
+            SwitchRow("Browser notifications", cfg.browser_notifications, {
+                let config = self.data.clone();
+                move |on| {
+                    if on {
+                        request_notification_permission();
+                    }
+                    config.update(|d| d.browser_notifications = on)
+                }
+            }),
+        ))
+    }
+}
+
+fn ModeChip(label: &str, selected: bool, on_click: impl Fn() + 'static) -> View {
+    Box(Modifier::new()
+        .padding_values(PaddingValues {
+            left: 10.0,
+            right: 10.0,
+            top: 4.0,
+            bottom: 4.0,
+        })
+        .background(if selected {
+            Color(theme().primary.0, theme().primary.1, theme().primary.2, 48)
+        } else {
+            Color(0, 0, 0, 0)
+        })
+        .clip_rounded(999.0)
+        .clickable()
+        .on_pointer_down(move |_| on_click())
+        .cursor(CursorIcon::Pointer))
+    .child(Text(label).size(13.0).color(if selected {
+        theme().primary
+    } else {
+        Color::from_hex("#9CA3AF")
+    }))
+}
+
+fn TimerButton(label: &str, primary: bool, on_click: impl Fn() + 'static) -> View {
+    Button(
+        Text(label).size(14.0).color(if primary {
+            theme().on_primary
+        } else {
+            Color::from_hex("#9CA3AF")
+        }),
+        on_click,
+    )
+    .modifier(
+        Modifier::new()
+            .padding_values(PaddingValues {
+                left: 16.0,
+                right: 16.0,
+                top: 8.0,
+                bottom: 8.0,
+            })
+            .background(if primary {
+                theme().primary
+            } else {
+                Color(0, 0, 0, 0)
+            })
+            .border(1.0, theme().outline, 8.0)
+            .clip_rounded(8.0),
+    )
+}
+
+fn format_duration(ms: u64) -> String {
+    let secs = ms.div_ceil(1000);
+    let (h, m, s) = (secs / 3600, (secs / 60) % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m:02}:{s:02}")
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn request_notification_permission() {
+    if Notification::permission() == NotificationPermission::Default {
+        let _ = Notification::request_permission();
+    }
+}
+
+/// Shows a system notification if the user has granted permission; otherwise does nothing.
+fn browser_notification(message: &str) {
+    if Notification::permission() != NotificationPermission::Granted {
+        return;
+    }
+    let opts = NotificationOptions::new();
+    opts.set_body(message);
+    let _ = Notification::new_with_options("Startpage timer", &opts);
+}
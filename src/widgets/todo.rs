@@ -0,0 +1,560 @@
+use std::rc::Rc;
+
+use repose_core::{CursorIcon, DragPayload, PaddingValues, prelude::*};
+use repose_ui::*;
+use serde::{Deserialize, Serialize};
+
+use super::{SmallButton, SwitchRow, Widget, WidgetConfig, WidgetKind, unix_now};
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoItem {
+    pub id: u64,
+    pub title: String,
+    /// Local calendar date, `YYYY-MM-DD`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    #[serde(default)]
+    pub done: bool,
+    /// Unix seconds when the item was checked off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub done_at: Option<u64>,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TodoData {
+    pub items: Vec<TodoItem>,
+    /// Completed items are archived this many days after being checked off.
+    pub archive_after_days: u32,
+    pub show_archived: bool,
+}
+
+impl Default for TodoData {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            archive_after_days: 7,
+            show_archived: false,
+        }
+    }
+}
+
+impl TodoData {
+    /// Archive completed items older than `archive_after_days`. Returns true if anything changed.
+    fn archive_completed(&mut self, now: u64) -> bool {
+        let cutoff = u64::from(self.archive_after_days) * DAY_SECS;
+        let mut changed = false;
+        for item in &mut self.items {
+            if item.done
+                && !item.archived
+                && item
+                    .done_at
+                    .is_some_and(|t| now.saturating_sub(t) >= cutoff)
+            {
+                item.archived = true;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Move item `id` so it sits just before `before`.
+    fn move_before(&mut self, id: u64, before: u64) {
+        if id == before {
+            return;
+        }
+        let Some(from) = self.items.iter().position(|i| i.id == id) else {
+            return;
+        };
+        let item = self.items.remove(from);
+        let to = self
+            .items
+            .iter()
+            .position(|i| i.id == before)
+            .unwrap_or(self.items.len());
+        self.items.insert(to, item);
+    }
+}
+
+#[derive(Clone)]
+pub struct TodoWidget {
+    data: WidgetConfig<TodoData>,
+    new_title: Signal<String>,
+    new_due: Signal<String>,
+    epoch: Signal<u64>,
+    error: Signal<Option<String>>,
+}
+
+impl TodoWidget {
+    pub fn new() -> Self {
+        Self {
+            data: WidgetConfig::load(WidgetKind::Todo),
+            new_title: signal(String::new()),
+            new_due: signal(String::new()),
+            epoch: signal(0),
+            error: signal(None),
+        }
+    }
+
+    fn add_item(&self) {
+        let title = self.new_title.get().trim().to_string();
+        if title.is_empty() {
+            return;
+        }
+        let due = match parse_due(&self.new_due.get()) {
+            Ok(due) => due,
+            Err(e) => {
+                self.error.set(Some(e));
+                return;
+            }
+        };
+        self.data.update(|d| {
+            let id = d.items.iter().map(|i| i.id).max().unwrap_or(0) + 1;
+            d.items.push(TodoItem {
+                id,
+                title,
+                due,
+                done: false,
+                done_at: None,
+                archived: false,
+            });
+        });
+        self.new_title.set(String::new());
+        self.new_due.set(String::new());
+        self.error.set(None);
+        self.epoch.update(|e| *e = e.wrapping_add(1));
+    }
+}
+
+impl Widget for TodoWidget {
+    fn kind(&self) -> WidgetKind {
+        WidgetKind::Todo
+    }
+
+    fn view(&self) -> View {
+        let mut data = self.data.get();
+        if data.archive_completed(unix_now()) {
+            self.data.update(|d| {
+                d.archive_completed(unix_now());
+            });
+        }
+
+        let today = local_date(0);
+        let archived = data.items.iter().filter(|i| i.archived).count();
+        let rows = data
+            .items
+            .iter()
+            .filter(|i| data.show_archived || !i.archived)
+            .map(|item| TodoRow(item.clone(), &today, self.data.clone()))
+            .collect::<Vec<_>>();
+
+        let epoch = self.epoch.get();
+        let this = Rc::new(self.clone());
+
+        Box(Modifier::new()
+            .fill_max_width()
+            .background(theme().surface)
+            .border(1.0, theme().outline, 12.0)
+            .clip_rounded(12.0)
+            .padding(16.0))
+        .child(
+            Column(Modifier::new().fill_max_width()).child((
+                Row(Modifier::new()
+                    .fill_max_width()
+                    .align_items(AlignItems::Center)
+                    .padding_values(PaddingValues {
+                        bottom: 8.0,
+                        ..Default::default()
+                    }))
+                .child((
+                    Text("Todo").size(14.0).color(Color::from_hex("#9CA3AF")),
+                    Spacer(),
+                    if archived > 0 {
+                        Box(Modifier::new()
+                            .clickable()
+                            .on_pointer_down({
+                                let data = self.data.clone();
+                                move |_| data.update(|d| d.show_archived = !d.show_archived)
+                            })
+                            .cursor(CursorIcon::Pointer))
+                        .child(
+                            Text(if data.show_archived {
+                                format!("Hide archived ({archived})")
+                            } else {
+                                format!("Show archived ({archived})")
+                            })
+                            .size(12.0)
+                            .color(Color::from_hex("#6B7280")),
+                        )
+                    } else {
+                        Box(Modifier::new())
+                    },
+                )),
+                Column(Modifier::new().fill_max_width()).child(rows),
+                Row(Modifier::new()
+                    .fill_max_width()
+                    .align_items(AlignItems::Center)
+                    .padding_values(PaddingValues {
+                        top: 8.0,
+                        ..Default::default()
+                    }))
+                .child((
+                    TextField(
+                        "Add a todo…",
+                        Modifier::new()
+                            .key(0x70D0_u64 ^ epoch.rotate_left(16))
+                            .height(36.0)
+                            .weight(2.0)
+                            .min_width(0.0)
+                            .background(Color::from_hex("#0F172A"))
+                            .border(1.0, theme().outline, 8.0)
+                            .clip_rounded(8.0),
+                        Some({
+                            let new_title = self.new_title.clone();
+                            move |s| new_title.set(s)
+                        }),
+                        Some({
+                            let this = this.clone();
+                            move |_: String| this.add_item()
+                        }),
+                    ),
+                    Box(Modifier::new().width(8.0).height(1.0)),
+                    TextField(
+                        "Due (YYYY-MM-DD)",
+                        Modifier::new()
+                            .key(0x70D1_u64 ^ epoch.rotate_left(16))
+                            .height(36.0)
+                            .weight(1.0)
+                            .min_width(0.0)
+                            .background(Color::from_hex("#0F172A"))
+                            .border(1.0, theme().outline, 8.0)
+                            .clip_rounded(8.0),
+                        Some({
+                            let new_due = self.new_due.clone();
+                            move |s| new_due.set(s)
+                        }),
+                        Some({
+                            let this = this.clone();
+                            move |_: String| this.add_item()
+                        }),
+                    ),
+                    SmallButton("+", move || this.add_item()),
+                )),
+                match self.error.get() {
+                    Some(e) => Text(e).size(12.0).color(theme().error),
+                    None => Box(Modifier::new()),
+                },
+            )),
+        )
+    }
+
+    fn settings(&self) -> View {
+        let cfg = self.data.get();
+        Column(Modifier::new().fill_max_width()).child((
+            Row(Modifier::new()
+                .fill_max_width()
+                .align_items(AlignItems::Center))
+            .child((
+                Text(format!(
+                    "Archive completed after {} day(s)",
+                    cfg.archive_after_days
+                ))
+                .size(13.0)
+                .color(Color::from_hex("#9CA3AF")),
+                Spacer(),
+            )),
+            Slider(cfg.archive_after_days as f32, (0.0, 30.0), Some(1.0), {
+                let data = self.data.clone();
+                move |v| data.update(|d| d.archive_after_days = v.round() as u32)
+            }),
+            SwitchRow("Show archived items", cfg.show_archived, {
+                let data = self.data.clone();
+                move |on| data.update(|d| d.show_archived = on)
+            }),
+        ))
+    }
+}
+
+/// One todo, draggable to reorder.
+fn TodoRow(item: TodoItem, today: &str, data: WidgetConfig<TodoData>) -> View {
+    let overdue = !item.done && item.due.as_deref().is_some_and(|d| d < today);
+    let due_today = !item.done && item.due.as_deref() == Some(today);
+    let id = item.id;
+
+    let title_color = if item.done {
+        Color::from_hex("#6B7280")
+    } else if overdue {
+        theme().error
+    } else {
+        theme().on_surface
+    };
+
+    Row(Modifier::new()
+        .key(0x70D0_0000 ^ id)
+        .fill_max_width()
+        .min_height(32.0)
+        .align_items(AlignItems::Center)
+        .background(if overdue {
+            Color(theme().error.0, theme().error.1, theme().error.2, 24)
+        } else {
+            Color(0, 0, 0, 0)
+        })
+        .clip_rounded(6.0)
+        .on_drag_start(move |_| Some(Rc::new(id) as DragPayload))
+        .on_drop({
+            let data = data.clone();
+            move |e| {
+                let Some(src) = e.payload.downcast_ref::<u64>().copied() else {
+                    return false;
+                };
+                data.update(|d| d.move_before(src, id));
+                true
+            }
+        }))
+    .child((
+        Text("⋮⋮")
+            .size(12.0)
+            .color(Color::from_hex("#4B5563"))
+            .modifier(Modifier::new().padding_values(PaddingValues {
+                left: 4.0,
+                right: 6.0,
+                ..Default::default()
+            })),
+        Checkbox(item.done, {
+            let data = data.clone();
+            move |done| {
+                data.update(|d| {
+                    if let Some(i) = d.items.iter_mut().find(|i| i.id == id) {
+                        i.done = done;
+                        i.done_at = done.then(unix_now);
+                        if !done {
+                            i.archived = false;
+                        }
+                    }
+                })
+            }
+        }),
+        Text(item.title)
+            .size(14.0)
+            .single_line()
+            .overflow_ellipsize()
+            .color(title_color)
+            .modifier(
+                Modifier::new()
+                    .weight(1.0)
+                    .min_width(0.0)
+                    .padding_values(PaddingValues {
+                        left: 8.0,
+                        ..Default::default()
+                    }),
+            ),
+        match item.due {
+            Some(due) => Text(if overdue {
+                format!("overdue · {due}")
+            } else if due_today {
+                "today".to_string()
+            } else {
+                due
+            })
+            .size(12.0)
+            .single_line()
+            .color(if overdue {
+                theme().error
+            } else if due_today {
+                theme().primary
+            } else {
+                Color::from_hex("#6B7280")
+            }),
+            None => Box(Modifier::new()),
+        },
+        SmallButton("×", move || {
+            data.update(|d| d.items.retain(|i| i.id != id))
+        }),
+    ))
+}
+
+/// Furthest `+N` a due date accepts, about ten years.
+const MAX_OFFSET_DAYS: u32 = 3650;
+
+/// Local calendar date `offset_days` from today, as `YYYY-MM-DD`.
+pub fn local_date(offset_days: u32) -> String {
+    let now = js_sys::Date::new_0();
+    // The constructor carries days past the end of the month into later months.
+    let d = js_sys::Date::new_with_year_month_day(
+        now.get_full_year(),
+        now.get_month() as i32,
+        now.get_date() as i32 + offset_days.min(MAX_OFFSET_DAYS) as i32,
+    );
+    format!(
+        "{:04}-{:02}-{:02}",
+        d.get_full_year(),
+        d.get_month() + 1,
+        d.get_date()
+    )
+}
+
+/// Accepts an empty string, `today`, `tomorrow`, `+N` (days) or `YYYY-MM-DD`.
+fn parse_due(s: &str) -> Result<Option<String>, String> {
+    let s = s.trim();
+    match s.to_ascii_lowercase().as_str() {
+        "" => return Ok(None),
+        "today" => return Ok(Some(local_date(0))),
+        "tomorrow" => return Ok(Some(local_date(1))),
+        _ => {}
+    }
+    if let Some(n) = parse_offset(s) {
+        return Ok(Some(local_date(n)));
+    }
+    if is_iso_date(s) {
+        return Ok(Some(s.to_string()));
+    }
+    Err(format!(
+        "“{s}” is not a date; use YYYY-MM-DD, today, tomorrow or +N"
+    ))
+}
+
+/// `+N` with `N` no more than [`MAX_OFFSET_DAYS`].
+fn parse_offset(s: &str) -> Option<u32> {
+    s.strip_prefix('+')
+        .filter(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|n| n.parse::<u32>().ok())
+        .filter(|n| *n <= MAX_OFFSET_DAYS)
+}
+
+fn is_iso_date(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    let [y, m, d] = parts.as_slice() else {
+        return false;
+    };
+    if y.len() != 4
+        || m.len() != 2
+        || d.len() != 2
+        || !s.bytes().all(|b| b == b'-' || b.is_ascii_digit())
+    {
+        return false;
+    }
+    let (Ok(y), Ok(m), Ok(d)) = (y.parse::<u32>(), m.parse::<u32>(), d.parse::<u32>()) else {
+        return false;
+    };
+    let leap = y % 4 == 0 && (y % 100 != 0 || y % 400 == 0);
+    let days = match m {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn done(id: u64, done_at: u64) -> TodoItem {
+        TodoItem {
+            id,
+            title: format!("item {id}"),
+            due: None,
+            done: true,
+            done_at: Some(done_at),
+            archived: false,
+        }
+    }
+
+    #[test]
+    fn offsets_are_bounded() {
+        for (input, want) in [
+            ("+0", Some(0)),
+            ("+1", Some(1)),
+            ("+3650", Some(MAX_OFFSET_DAYS)),
+            ("+3651", None),
+            ("+99999", None),
+            ("+99999999999", None),
+            ("+-1", None),
+            ("++1", None),
+            ("+", None),
+            ("+ 1", None),
+            ("1", None),
+        ] {
+            assert_eq!(parse_offset(input), want, "{input}");
+        }
+    }
+
+    #[test]
+    fn iso_dates_must_exist() {
+        for (input, want) in [
+            ("2024-01-31", true),
+            ("2024-02-29", true),
+            ("2000-02-29", true),
+            ("2023-02-29", false),
+            ("1900-02-29", false),
+            ("2024-02-31", false),
+            ("2024-04-31", false),
+            ("2024-13-01", false),
+            ("2024-00-10", false),
+            ("2024-01-00", false),
+            ("2024-1-01", false),
+            ("24-01-01", false),
+            ("2024-01-01-01", false),
+            ("2024/01/01", false),
+            ("+024-01-01", false),
+        ] {
+            assert_eq!(is_iso_date(input), want, "{input}");
+        }
+    }
+
+    #[test]
+    fn bad_due_dates_are_errors() {
+        assert_eq!(parse_due("  "), Ok(None));
+        assert_eq!(
+            parse_due(" 2024-03-05 "),
+            Ok(Some("2024-03-05".to_string()))
+        );
+        for input in ["+-1", "+99999", "2024-13-01", "2024-02-31", "soon"] {
+            assert!(parse_due(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn items_archive_once_the_cutoff_has_passed() {
+        let now = 100 * DAY_SECS;
+        let mut data = TodoData {
+            items: vec![
+                done(1, now - 7 * DAY_SECS),
+                done(2, now - 7 * DAY_SECS + 1),
+                TodoItem {
+                    done: false,
+                    done_at: None,
+                    ..done(3, 0)
+                },
+                TodoItem {
+                    done_at: None,
+                    ..done(4, 0)
+                },
+            ],
+            ..TodoData::default()
+        };
+        assert!(data.archive_completed(now));
+        let archived: Vec<bool> = data.items.iter().map(|i| i.archived).collect();
+        assert_eq!(archived, [true, false, false, false]);
+        assert!(!data.archive_completed(now), "nothing left to archive");
+        assert!(data.archive_completed(now + 1));
+        assert!(data.items[1].archived);
+    }
+
+    #[test]
+    fn zero_days_archives_immediately() {
+        let mut data = TodoData {
+            items: vec![done(1, 50)],
+            archive_after_days: 0,
+            ..TodoData::default()
+        };
+        assert!(data.archive_completed(50));
+        assert!(data.items[0].archived);
+    }
+}
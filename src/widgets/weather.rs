@@ -0,0 +1,287 @@
+use std::rc::Rc;
+
+use repose_core::{PaddingValues, prelude::*};
+use repose_ui::*;
+use serde::{Deserialize, Serialize};
+
+use super::{SmallButton, SwitchRow, Widget, WidgetConfig, WidgetContext, WidgetKind, unix_now};
+use crate::weather::{
+    Forecast, Location, OpenMeteo, Units, WeatherProvider, WeatherStore, describe,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProviderChoice {
+    #[default]
+    // Configs saved while sample data was offered still load.
+    #[serde(alias = "Sample")]
+    OpenMeteo,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeatherConfig {
+    pub location: Option<Location>,
+    pub units: Units,
+    pub provider: ProviderChoice,
+    /// How long a fetched forecast is reused before refetching.
+    pub cache_mins: u32,
+    pub show_forecast: bool,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        Self {
+            location: None,
+            units: Units::Celsius,
+            provider: ProviderChoice::OpenMeteo,
+            cache_mins: 30,
+            show_forecast: true,
+        }
+    }
+}
+
+pub struct WeatherWidget {
+    cfg: WidgetConfig<WeatherConfig>,
+    store: Rc<WeatherStore>,
+    open_meteo: Rc<dyn WeatherProvider>,
+    query: Signal<String>,
+    /// Result of the last place search, shown under the search field.
+    search_status: Signal<Option<String>>,
+    epoch: Signal<u64>,
+}
+
+impl WeatherWidget {
+    pub fn new(ctx: WidgetContext) -> Self {
+        Self {
+            cfg: WidgetConfig::load(WidgetKind::Weather),
+            store: Rc::new(WeatherStore::new()),
+            open_meteo: Rc::new(OpenMeteo::new(ctx.http)),
+            query: signal(String::new()),
+            search_status: signal(None),
+            epoch: signal(0),
+        }
+    }
+
+    fn provider(&self, choice: ProviderChoice) -> Rc<dyn WeatherProvider> {
+        match choice {
+            ProviderChoice::OpenMeteo => self.open_meteo.clone(),
+        }
+    }
+}
+
+impl Widget for WeatherWidget {
+    fn kind(&self) -> WidgetKind {
+        WidgetKind::Weather
+    }
+
+    fn view(&self) -> View {
+        let cfg = self.cfg.get();
+        let Some(location) = cfg.location.clone() else {
+            return Text("Set a weather location in the widget settings (⚙)")
+                .size(13.0)
+                .color(Color::from_hex("#6B7280"));
+        };
+
+        let provider = self.provider(cfg.provider);
+        self.store.refresh_if_stale(
+            provider.as_ref(),
+            &location,
+            cfg.units,
+            u64::from(cfg.cache_mins.max(1)) * 60,
+            unix_now(),
+        );
+        let cache = self.store.cache.get();
+        let current = WeatherStore::cache_key(provider.as_ref(), &location, cfg.units);
+
+        match cache.forecast.filter(|_| cache.key == current) {
+            Some(f) => Current(&f, &location, &cfg),
+            None => Text(match cache.error.filter(|_| cache.key == current) {
+                Some(e) => format!("Weather unavailable: {e}"),
+                None => "Loading weather…".to_string(),
+            })
+            .size(13.0)
+            .color(Color::from_hex("#6B7280")),
+        }
+    }
+
+    fn settings(&self) -> View {
+        let cfg = self.cfg.get();
+        let search = {
+            let cfg_handle = self.cfg.clone();
+            let provider = self.provider(cfg.provider);
+            let query = self.query.clone();
+            let status = self.search_status.clone();
+            let epoch = self.epoch.clone();
+            Rc::new(move || {
+                let q = query.get().trim().to_string();
+                if q.is_empty() {
+                    return;
+                }
+                status.set(Some("Searching…".to_string()));
+                let cfg_handle = cfg_handle.clone();
+                let status = status.clone();
+                let query = query.clone();
+                let epoch = epoch.clone();
+                provider.geocode(
+                    &q,
+                    Box::new(move |result| match result {
+                        Ok(loc) => {
+                            status.set(None);
+                            query.set(String::new());
+                            epoch.update(|e| *e = e.wrapping_add(1));
+                            cfg_handle.update(|c| c.location = Some(loc));
+                        }
+                        Err(e) => status.set(Some(format!("{e}"))),
+                    }),
+                );
+            })
+        };
+
+        let hint = cfg
+            .location
+            .as_ref()
+            .map(|l| l.name.clone())
+            .unwrap_or_else(|| "City or place".to_string());
+
+        Column(Modifier::new().fill_max_width()).child((
+            Row(Modifier::new()
+                .fill_max_width()
+                .align_items(AlignItems::Center)
+                .padding_values(PaddingValues {
+                    bottom: 4.0,
+                    ..Default::default()
+                }))
+            .child((
+                TextField(
+                    hint,
+                    Modifier::new()
+                        .key(0x3EA7_u64 ^ self.epoch.get().rotate_left(16))
+                        .height(36.0)
+                        .weight(1.0)
+                        .min_width(0.0)
+                        .background(Color::from_hex("#0F172A"))
+                        .border(1.0, theme().outline, 8.0)
+                        .clip_rounded(8.0),
+                    Some({
+                        let query = self.query.clone();
+                        move |s| query.set(s)
+                    }),
+                    Some({
+                        let search = search.clone();
+                        move |_: String| search()
+                    }),
+                ),
+                SmallButton("⌕", move || search()),
+            )),
+            match self.search_status.get() {
+                Some(s) => Text(s).size(12.0).color(Color::from_hex("#6B7280")),
+                None => Box(Modifier::new()),
+            },
+            SwitchRow("Fahrenheit", cfg.units == Units::Fahrenheit, {
+                let c = self.cfg.clone();
+                move |on| {
+                    c.update(|c| {
+                        c.units = if on {
+                            Units::Fahrenheit
+                        } else {
+                            Units::Celsius
+                        }
+                    })
+                }
+            }),
+            SwitchRow("Show forecast", cfg.show_forecast, {
+                let c = self.cfg.clone();
+                move |on| c.update(|c| c.show_forecast = on)
+            }),
+            Text(format!("Refresh every {} min", cfg.cache_mins))
+                .size(13.0)
+                .color(Color::from_hex("#9CA3AF")),
+            Slider(cfg.cache_mins as f32, (10.0, 180.0), Some(10.0), {
+                let c = self.cfg.clone();
+                move |v| c.update(|c| c.cache_mins = v.round() as u32)
+            }),
+        ))
+    }
+}
+
+fn Current(f: &Forecast, location: &Location, cfg: &WeatherConfig) -> View {
+    let (desc, symbol) = describe(f.code);
+    let muted = Color::from_hex("#9CA3AF");
+
+    let forecast = if cfg.show_forecast {
+        Row(Modifier::new().padding_values(PaddingValues {
+            top: 4.0,
+            ..Default::default()
+        }))
+        .child(
+            f.daily
+                .iter()
+                .skip(1)
+                .map(|d| {
+                    Text(format!(
+                        "{} {} {:.0}°/{:.0}°",
+                        weekday(&d.date),
+                        describe(d.code).1,
+                        d.max,
+                        d.min
+                    ))
+                    .size(12.0)
+                    .color(muted)
+                    .modifier(Modifier::new().padding_values(PaddingValues {
+                        left: 6.0,
+                        right: 6.0,
+                        ..Default::default()
+                    }))
+                })
+                .collect::<Vec<_>>(),
+        )
+    } else {
+        Box(Modifier::new())
+    };
+
+    Column(Modifier::new().align_items(AlignItems::Center)).child((
+        Text(format!(
+            "{symbol} {:.0}{} · {desc} · {}",
+            f.temperature,
+            cfg.units.symbol(),
+            location.name
+        ))
+        .size(15.0)
+        .color(muted),
+        forecast,
+    ))
+}
+
+/// Short weekday name for a `YYYY-MM-DD` date (Sakamoto's method).
+fn weekday(date: &str) -> &'static str {
+    const NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+    const T: [i32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+
+    let mut parts = date.splitn(3, '-').map(|p| p.parse::<i32>().ok());
+    let (Some(Some(mut y)), Some(Some(m)), Some(Some(d))) =
+        (parts.next(), parts.next(), parts.next())
+    else {
+        return "";
+    };
+    if !(1..=12).contains(&m) {
+        return "";
+    }
+    if m < 3 {
+        y -= 1;
+    }
+    let w = (y + y / 4 - y / 100 + y / 400 + T[(m - 1) as usize] + d).rem_euclid(7);
+    NAMES[w as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_saved_with_sample_data_still_loads() {
+        let cfg: WeatherConfig =
+            serde_json::from_str(r#"{"provider":"Sample","cache_mins":60}"#).unwrap();
+        assert_eq!(cfg.provider, ProviderChoice::OpenMeteo);
+        assert_eq!(cfg.cache_mins, 60);
+    }
+}